@@ -0,0 +1,348 @@
+//! Relay server of network game.
+//!
+//! Server knows nothing about game rules and never simulates anything. It only
+//! collects player inputs, merges them into a stream of ticks at fixed rate,
+//! broadcasts that stream to everybody and compares state hashes reported by
+//! clients to detect desync. It also forwards game snapshots from publishing
+//! clients to spectators. See `snake_3d_rs::network::protocol`
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, ErrorKind},
+    net::{TcpListener, TcpStream},
+    time::{Duration, Instant, SystemTime},
+};
+
+use snake_3d_rs::{
+    models::{Direction, GameRules, PlayerId},
+    network::protocol::{ClientMessage, ServerMessage, PROTOCOL_VERSION},
+};
+use tungstenite::{Message, WebSocket};
+
+// players start on different cube sides, so there cannot be more of them
+const MAX_PLAYERS_COUNT: usize = 6;
+
+// longest time to wait for websocket upgrade request of new client
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_millis(100);
+
+pub struct ServerConfig {
+    // game starts as soon as this number of players joined
+    pub players_count: usize,
+    pub tick_period: Duration,
+    pub rules: GameRules,
+
+    // random seed is generated for each session if not set
+    pub seed: Option<u32>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            players_count: 2,
+            tick_period: Duration::from_millis(150),
+            rules: GameRules::default(),
+            seed: None,
+        }
+    }
+}
+
+struct Client {
+    socket: WebSocket<TcpStream>,
+    player_id: Option<PlayerId>,
+    is_spectator: bool,
+
+    // set once client has published a snapshot
+    is_publisher: bool,
+    is_closed: bool,
+}
+
+#[derive(Default)]
+struct Session {
+    clients: Vec<Client>,
+    next_player_id: PlayerId,
+    is_started: bool,
+    next_tick: u32,
+    next_tick_time: Option<Instant>,
+    inputs: BTreeMap<u32, Vec<(PlayerId, Direction)>>,
+    left_player_ids: Vec<PlayerId>,
+
+    // first reported hash and number of reports per tick
+    hashes: HashMap<u32, (u64, usize)>,
+    desync_tick: Option<u32>,
+}
+
+/// Serves single game session: waits for players to join, runs the game and
+/// returns when all players have left
+pub fn serve(listener: &TcpListener, config: &ServerConfig) -> io::Result<()> {
+    assert!(config.players_count > 0 && config.players_count <= MAX_PLAYERS_COUNT);
+
+    listener.set_nonblocking(true)?;
+
+    let mut session = Session::default();
+
+    loop {
+        accept_clients(listener, &mut session)?;
+
+        for idx in 0..session.clients.len() {
+            read_messages(&mut session, idx, config);
+        }
+
+        remove_closed_clients(&mut session);
+
+        if session.is_started {
+            // spectators are just dropped along with the session
+            if get_player_ids(&session).is_empty() {
+                return Ok(());
+            }
+
+            broadcast_ticks(&mut session, config);
+        }
+
+        for client in &mut session.clients {
+            flush(client);
+        }
+
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn accept_clients(listener: &TcpListener, session: &mut Session) -> io::Result<()> {
+    loop {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        // make handshake in blocking mode, it's simpler and only takes a moment.
+        // client that never sends upgrade request is dropped by timeout, so it
+        // doesn't stall ticks of everybody else
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        stream.set_write_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let socket = match tungstenite::accept(stream) {
+            Ok(socket) => socket,
+            Err(_) => continue,
+        };
+        socket.get_ref().set_read_timeout(None)?;
+        socket.get_ref().set_write_timeout(None)?;
+        socket.get_ref().set_nonblocking(true)?;
+        socket.get_ref().set_nodelay(true)?;
+
+        session.clients.push(Client {
+            socket,
+            player_id: None,
+            is_spectator: false,
+            is_publisher: false,
+            is_closed: false,
+        });
+    }
+}
+
+fn read_messages(session: &mut Session, idx: usize, config: &ServerConfig) {
+    loop {
+        let client = &mut session.clients[idx];
+
+        let bytes = match client.socket.read() {
+            Ok(Message::Binary(bytes)) => bytes,
+            Ok(Message::Close(_)) => {
+                client.is_closed = true;
+                return;
+            }
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => return,
+            Err(_) => {
+                client.is_closed = true;
+                return;
+            }
+        };
+
+        match ClientMessage::decode(&bytes) {
+            Ok(msg) => handle_message(session, idx, msg, config),
+            Err(_) => session.clients[idx].is_closed = true,
+        }
+    }
+}
+
+fn handle_message(session: &mut Session, idx: usize, msg: ClientMessage, config: &ServerConfig) {
+    match msg {
+        ClientMessage::Join { version, name } => {
+            let reason = if version != PROTOCOL_VERSION {
+                Some(format!("unsupported protocol version: {version}"))
+            } else if session.is_started {
+                Some("game already started".to_string())
+            } else if session.clients[idx].player_id.is_some() {
+                Some("already joined".to_string())
+            } else {
+                None
+            };
+
+            if let Some(reason) = reason {
+                let client = &mut session.clients[idx];
+                send(client, &ServerMessage::Rejected { reason });
+                client.socket.close(None).ok();
+                client.is_closed = true;
+                return;
+            }
+
+            let player_id = session.next_player_id;
+            session.next_player_id += 1;
+            session.clients[idx].player_id = Some(player_id);
+
+            send(
+                &mut session.clients[idx],
+                &ServerMessage::Welcome { player_id },
+            );
+            broadcast(session, &ServerMessage::PlayerJoined { player_id, name });
+
+            if get_player_ids(session).len() == config.players_count {
+                start(session, config);
+            }
+        }
+        ClientMessage::Input { tick, direction } => {
+            if let Some(player_id) = session.clients[idx].player_id {
+                // input can't be applied in the past, since all clients already
+                // simulated it, so move it to the nearest tick
+                let tick = tick.max(session.next_tick);
+                session
+                    .inputs
+                    .entry(tick)
+                    .or_default()
+                    .push((player_id, direction));
+            }
+        }
+        ClientMessage::StateHash { tick, hash } => {
+            let players_count = get_player_ids(session).len();
+            let (first_hash, reports_count) = session.hashes.entry(tick).or_insert((hash, 0));
+            *reports_count += 1;
+
+            if *first_hash != hash && session.desync_tick.is_none() {
+                session.desync_tick = Some(tick);
+                broadcast(session, &ServerMessage::Desync { tick });
+            }
+
+            if session.hashes[&tick].1 >= players_count {
+                session.hashes.remove(&tick);
+            }
+        }
+        ClientMessage::Spectate => {
+            session.clients[idx].is_spectator = true;
+
+            for client in &mut session.clients {
+                if client.is_publisher && !client.is_closed {
+                    send(client, &ServerMessage::SpectatorJoined);
+                }
+            }
+        }
+        ClientMessage::Publish { snapshot } => {
+            session.clients[idx].is_publisher = true;
+
+            let msg = ServerMessage::Snapshot { snapshot };
+            for client in &mut session.clients {
+                if client.is_spectator && !client.is_closed {
+                    send(client, &msg);
+                }
+            }
+        }
+    }
+}
+
+fn start(session: &mut Session, config: &ServerConfig) {
+    let seed = config.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .subsec_nanos()
+    });
+
+    let msg = ServerMessage::Start {
+        seed,
+        rules: config.rules.clone(),
+        player_ids: get_player_ids(session),
+        tick_period_ms: config.tick_period.as_millis() as u32,
+    };
+
+    broadcast(session, &msg);
+
+    session.is_started = true;
+    session.next_tick_time = Some(Instant::now() + config.tick_period);
+}
+
+fn broadcast_ticks(session: &mut Session, config: &ServerConfig) {
+    let now = Instant::now();
+
+    while session.next_tick_time.unwrap() <= now {
+        let tick = session.next_tick;
+
+        let msg = ServerMessage::Tick {
+            tick,
+            inputs: session.inputs.remove(&tick).unwrap_or_default(),
+            left_player_ids: std::mem::take(&mut session.left_player_ids),
+        };
+
+        broadcast(session, &msg);
+
+        session.next_tick += 1;
+        session.next_tick_time = Some(session.next_tick_time.unwrap() + config.tick_period);
+    }
+}
+
+fn remove_closed_clients(session: &mut Session) {
+    let left_player_ids = &mut session.left_player_ids;
+    let is_started = session.is_started;
+    let mut has_player_left = false;
+
+    session.clients.retain(|client| {
+        if client.is_closed {
+            if let (Some(player_id), true) = (client.player_id, is_started) {
+                left_player_ids.push(player_id);
+                has_player_left = true;
+            }
+        }
+        !client.is_closed
+    });
+
+    // ticks which the rest of players already reported would wait forever for
+    // the player who left
+    if has_player_left {
+        let players_count = get_player_ids(session).len();
+        session
+            .hashes
+            .retain(|_, (_, reports_count)| *reports_count < players_count);
+    }
+}
+
+fn get_player_ids(session: &Session) -> Vec<PlayerId> {
+    session
+        .clients
+        .iter()
+        .filter(|client| !client.is_closed)
+        .filter_map(|client| client.player_id)
+        .collect()
+}
+
+fn broadcast(session: &mut Session, msg: &ServerMessage) {
+    for client in &mut session.clients {
+        if client.player_id.is_some() && !client.is_closed {
+            send(client, msg);
+        }
+    }
+}
+
+fn send(client: &mut Client, msg: &ServerMessage) {
+    // socket is non-blocking, so message may stay in write buffer and go out
+    // with the next flush
+    match client.socket.write(Message::Binary(msg.encode())) {
+        Ok(_) => {}
+        Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => {}
+        Err(_) => client.is_closed = true,
+    }
+}
+
+fn flush(client: &mut Client) {
+    match client.socket.flush() {
+        Ok(_) => {}
+        Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => {}
+        Err(_) => client.is_closed = true,
+    }
+}
@@ -0,0 +1,43 @@
+use std::{net::TcpListener, time::Duration};
+
+use snake_3d_relay_server::{serve, ServerConfig};
+
+const USAGE: &str = "usage: snake-3d-relay-server [--address 127.0.0.1:9001] \
+                     [--players 2] [--tick-period 150]";
+
+// errors like running out of file descriptors last a while, so server doesn't
+// spin retrying them
+const ERROR_PAUSE: Duration = Duration::from_secs(1);
+
+fn main() {
+    let mut address = String::from("127.0.0.1:9001");
+    let mut config = ServerConfig::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_else(|| panic!("{USAGE}"));
+        match arg.as_str() {
+            "--address" => address = value,
+            "--players" => config.players_count = value.parse().expect(USAGE),
+            "--tick-period" => {
+                config.tick_period = Duration::from_millis(value.parse().expect(USAGE))
+            }
+            _ => panic!("{USAGE}"),
+        }
+    }
+
+    let listener = TcpListener::bind(&address).expect("failed to bind address");
+    println!("listening on ws://{address}");
+
+    // each game session lasts until all its players leave, then next one begins.
+    // failed session doesn't stop the server, next one is started in a moment
+    loop {
+        match serve(&listener, &config) {
+            Ok(()) => println!("game session finished"),
+            Err(e) => {
+                eprintln!("game session failed: {e}");
+                std::thread::sleep(ERROR_PAUSE);
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::{
+    collections::BTreeMap,
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use snake_3d_relay_server::{serve, ServerConfig};
+use snake_3d_rs::{
+    actions::network_actions::{handle_server_message, join_network_game, send_snake_direction},
+    models::{Direction, GameState},
+    network::protocol::{ClientMessage, ServerMessage, PROTOCOL_VERSION},
+};
+use tungstenite::{stream::MaybeTlsStream, Message, WebSocket};
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+fn start_server(players_count: usize) -> (SocketAddr, JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = ServerConfig {
+        players_count,
+        tick_period: Duration::from_millis(5),
+        seed: Some(42),
+        ..ServerConfig::default()
+    };
+
+    let server = thread::spawn(move || serve(&listener, &config).unwrap());
+
+    (addr, server)
+}
+
+fn connect(addr: SocketAddr) -> Socket {
+    let (socket, _) = tungstenite::connect(format!("ws://{addr}")).unwrap();
+    socket
+}
+
+fn read_message(socket: &mut Socket) -> Option<ServerMessage> {
+    loop {
+        match socket.read() {
+            Ok(Message::Binary(bytes)) => return Some(ServerMessage::decode(&bytes).unwrap()),
+            Ok(Message::Close(_)) | Err(_) => return None,
+            Ok(_) => continue,
+        }
+    }
+}
+
+struct ClientReport {
+    // hashes reported to server by tick
+    hashes: BTreeMap<u32, u64>,
+    desync_tick: Option<u32>,
+    rivals_count: usize,
+}
+
+/// Plays network game with the same simulation browser client runs, until
+/// reaching `last_tick`. `on_tick` is called after each simulated tick
+fn run_client(
+    addr: SocketAddr,
+    last_tick: u32,
+    mut on_tick: impl FnMut(&mut GameState),
+) -> ClientReport {
+    let mut socket = connect(addr);
+
+    let mut state = GameState::default();
+    join_network_game(&mut state, "player");
+
+    let mut report = ClientReport {
+        hashes: BTreeMap::new(),
+        desync_tick: None,
+        rivals_count: 0,
+    };
+
+    loop {
+        for msg in state.network.as_mut().unwrap().outbox.drain(..) {
+            if let ClientMessage::StateHash { tick, hash } = msg {
+                report.hashes.insert(tick, hash);
+            }
+            socket.send(Message::Binary(msg.encode())).unwrap();
+        }
+
+        if state.tick >= last_tick || report.desync_tick.is_some() {
+            break;
+        }
+
+        let msg = read_message(&mut socket).expect("connection closed");
+        let is_tick = matches!(msg, ServerMessage::Tick { .. });
+
+        if let ServerMessage::Desync { tick } = msg {
+            report.desync_tick = Some(tick);
+        }
+
+        handle_server_message(&mut state, msg).ok();
+
+        if is_tick {
+            on_tick(&mut state);
+        }
+    }
+
+    report.rivals_count = state.rivals.len();
+    socket.close(None).ok();
+    while read_message(&mut socket).is_some() {}
+
+    report
+}
+
+#[test]
+fn clients_stay_in_sync() {
+    let (addr, server) = start_server(2);
+
+    let steering_client = thread::spawn(move || {
+        run_client(addr, 60, |state| match state.tick {
+            5 => send_snake_direction(state, Direction::Up),
+            10 => send_snake_direction(state, Direction::Left),
+            15 => send_snake_direction(state, Direction::Down),
+            _ => {}
+        })
+    });
+    let passive_client = thread::spawn(move || run_client(addr, 30, |_| {}));
+
+    let steering = steering_client.join().unwrap();
+    let passive = passive_client.join().unwrap();
+
+    server.join().unwrap();
+
+    assert_eq!(steering.desync_tick, None);
+    assert_eq!(passive.desync_tick, None);
+
+    for tick in 1..=30 {
+        assert_eq!(steering.hashes[&tick], passive.hashes[&tick], "tick {tick}");
+    }
+
+    // passive client disconnected after tick 30, so its snake was removed
+    assert_eq!(steering.rivals_count, 0);
+    assert_eq!(passive.rivals_count, 1);
+}
+
+#[test]
+fn desync_is_detected() {
+    let (addr, server) = start_server(2);
+
+    let honest_client = thread::spawn(move || run_client(addr, 30, |_| {}));
+    let cheating_client = thread::spawn(move || {
+        run_client(addr, 30, |state| {
+            if state.tick == 10 {
+                state.apples.clear();
+            }
+        })
+    });
+
+    let honest = honest_client.join().unwrap();
+    let cheating = cheating_client.join().unwrap();
+
+    server.join().unwrap();
+
+    // corrupted state is reported with the hash of the next tick
+    assert_eq!(honest.desync_tick, Some(11));
+    assert_eq!(cheating.desync_tick, Some(11));
+}
+
+#[test]
+fn incompatible_client_is_rejected() {
+    let (addr, server) = start_server(1);
+
+    let mut socket = connect(addr);
+    let join = ClientMessage::Join {
+        version: PROTOCOL_VERSION + 1,
+        name: "player".to_string(),
+    };
+    socket.send(Message::Binary(join.encode())).unwrap();
+
+    assert!(matches!(
+        read_message(&mut socket),
+        Some(ServerMessage::Rejected { .. })
+    ));
+
+    // server is still waiting for compatible player
+    let report = run_client(addr, 3, |_| {});
+    assert_eq!(report.hashes.len(), 3);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn silent_client_does_not_stall_players() {
+    let (addr, server) = start_server(1);
+
+    // connects but never sends upgrade request
+    let _silent = TcpStream::connect(addr).unwrap();
+
+    let report = run_client(addr, 3, |_| {});
+    assert_eq!(report.hashes.len(), 3);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn snapshots_are_relayed_to_spectators() {
+    let (addr, server) = start_server(1);
+
+    let mut spectator = connect(addr);
+    spectator
+        .send(Message::Binary(ClientMessage::Spectate.encode()))
+        .unwrap();
+
+    // make sure server handled spectate request before anything is published
+    thread::sleep(Duration::from_millis(50));
+
+    let mut publisher = connect(addr);
+    let publish = ClientMessage::Publish {
+        snapshot: vec![1, 2, 3],
+    };
+    publisher.send(Message::Binary(publish.encode())).unwrap();
+
+    assert_eq!(
+        read_message(&mut spectator),
+        Some(ServerMessage::Snapshot {
+            snapshot: vec![1, 2, 3]
+        })
+    );
+
+    // publisher is asked for full snapshot when another spectator joins
+    let mut late_spectator = connect(addr);
+    late_spectator
+        .send(Message::Binary(ClientMessage::Spectate.encode()))
+        .unwrap();
+
+    assert_eq!(
+        read_message(&mut publisher),
+        Some(ServerMessage::SpectatorJoined)
+    );
+
+    // let session finish
+    run_client(addr, 1, |_| {});
+    server.join().unwrap();
+}
@@ -1,39 +1,157 @@
 use crate::{
-    helpers::direction::get_opposite_direction,
-    models::{CameraMode, CubeSideType, Degrees, Direction, GameState, GameStatus, Point2D},
+    helpers::{
+        direction::get_screen_relative_direction,
+        storage::{get_storage_item, set_storage_item},
+    },
+    models::{
+        CameraMode, ControlMapping, Degrees, Direction, GameState, GameStatus, Point2D, Quaternion,
+        PLAYBACK_SPEEDS, X_AXIS, Y_AXIS,
+    },
 };
 
-use super::{game_actions::start_or_pause_game, snake_actions::set_snake_direction};
+use super::{
+    cube_actions::{request_screenshot, switch_theme, toggle_minimap, toggle_objects_mode},
+    game_actions::start_or_pause_game,
+    network_actions::send_snake_direction,
+    palette_actions::{switch_palette, toggle_shape_coding},
+    perf_actions::toggle_perf_hud,
+    quality_actions::switch_quality,
+    replay_actions::{
+        record_input, seek_playback_by_step, set_playback_speed, stop_playback,
+        toggle_playback_pause,
+    },
+    snake_actions::set_snake_direction,
+};
+
+const CONTROL_MAPPING_STORAGE_KEY: &str = "controls";
 
 pub fn on_keydown(state: &mut GameState, key_code: &str) {
-    let mut direction = None;
+    // switching how objects look is allowed for everyone
+    if key_code == "KeyM" {
+        toggle_objects_mode(state);
+        return;
+    }
+
+    if key_code == "KeyT" {
+        switch_theme(state);
+        return;
+    }
+
+    if key_code == "KeyC" {
+        switch_palette(state);
+        return;
+    }
+
+    if key_code == "KeyX" {
+        toggle_shape_coding(state);
+        return;
+    }
+
+    if key_code == "KeyQ" {
+        switch_quality(state);
+        return;
+    }
+
+    if key_code == "KeyO" {
+        toggle_minimap(state);
+        return;
+    }
+
+    if key_code == "KeyF" {
+        toggle_perf_hud(state);
+        return;
+    }
+
+    if key_code == "KeyP" {
+        request_screenshot(state);
+        return;
+    }
+
+    if key_code == "KeyK" {
+        toggle_control_mapping(state);
+        return;
+    }
+
+    if state.is_spectator {
+        return;
+    }
+
+    if state.playback.is_some() {
+        on_playback_keydown(state, key_code);
+        return;
+    }
+
+    let mut arrow = None;
 
     match key_code {
-        "ArrowUp" | "KeyW" => direction = Some(Direction::Up),
-        "ArrowDown" | "KeyS" => direction = Some(Direction::Down),
-        "ArrowLeft" | "KeyA" => direction = Some(Direction::Left),
-        "ArrowRight" | "KeyD" => direction = Some(Direction::Right),
-        "Space" | "Enter" => start_or_pause_game(state),
+        "ArrowUp" | "KeyW" => arrow = Some(Direction::Up),
+        "ArrowDown" | "KeyS" => arrow = Some(Direction::Down),
+        "ArrowLeft" | "KeyA" => arrow = Some(Direction::Left),
+        "ArrowRight" | "KeyD" => arrow = Some(Direction::Right),
+        // network game cannot be paused
+        "Space" | "Enter" if state.network.is_none() => start_or_pause_game(state),
         _ => {}
     }
 
-    if let Some(mut direction) = direction {
-        let head = state.snake.parts.get(0).unwrap();
-        let grid = &state.scene.cube.grid;
-
-        // adjust direction per current camera rotation
-        if (head.side == CubeSideType::Up && head.row >= grid.rows_count / 2)
-            || (head.side == CubeSideType::Down && head.row < grid.rows_count / 2)
-        {
-            direction = get_opposite_direction(direction);
+    if let Some(arrow) = arrow {
+        let head = state.snake.parts.front().unwrap();
+
+        let direction = match state.control_mapping {
+            ControlMapping::ScreenRelative => {
+                get_screen_relative_direction(head.side, &state.scene.cube.current_rotation, arrow)
+            }
+            ControlMapping::FaceLocal => arrow,
+        };
+
+        if state.network.is_some() {
+            send_snake_direction(state, direction);
+        } else {
+            let prev_direction = state.snake.direction;
+            set_snake_direction(&mut state.snake, direction);
+
+            if state.snake.direction != prev_direction {
+                record_input(state, direction);
+            }
         }
+    }
+}
+
+/// Loads control mapping saved by player
+pub fn load_control_mapping(state: &mut GameState) {
+    state.control_mapping = match get_storage_item(CONTROL_MAPPING_STORAGE_KEY).as_deref() {
+        Some("screen") => ControlMapping::ScreenRelative,
+        _ => ControlMapping::FaceLocal,
+    };
+}
+
+/// Switches between face-local and screen-relative controls, and saves the
+/// choice
+pub fn toggle_control_mapping(state: &mut GameState) {
+    state.control_mapping = match state.control_mapping {
+        ControlMapping::FaceLocal => ControlMapping::ScreenRelative,
+        ControlMapping::ScreenRelative => ControlMapping::FaceLocal,
+    };
 
-        set_snake_direction(state, direction);
+    set_storage_item(CONTROL_MAPPING_STORAGE_KEY, state.control_mapping.name());
+}
+
+fn on_playback_keydown(state: &mut GameState, key_code: &str) {
+    match key_code {
+        "Space" | "Enter" => toggle_playback_pause(state),
+        "ArrowLeft" | "KeyA" => seek_playback_by_step(state, false),
+        "ArrowRight" | "KeyD" => seek_playback_by_step(state, true),
+        "Digit1" => set_playback_speed(state, PLAYBACK_SPEEDS[0]),
+        "Digit2" => set_playback_speed(state, PLAYBACK_SPEEDS[1]),
+        "Digit3" => set_playback_speed(state, PLAYBACK_SPEEDS[2]),
+        "Digit4" => set_playback_speed(state, PLAYBACK_SPEEDS[3]),
+        "Escape" => stop_playback(state),
+        _ => {}
     }
 }
 
 pub fn on_mousedown(state: &mut GameState) {
-    if state.status != GameStatus::InGame {
+    // spectator has free camera all the time
+    if state.status != GameStatus::InGame || state.is_spectator {
         let cube = &mut state.scene.cube;
 
         cube.mouse_is_dragging = true;
@@ -60,11 +178,12 @@ pub fn on_mousemove(state: &mut GameState, mouse_pos: Point2D) {
         let dy = cube_mouse_pos.y - mouse_pos.y;
 
         const ROTATION_VELOCITY: f64 = 0.25;
-        cube.target_rotation.x = Degrees(*cube.target_rotation.x - dy * ROTATION_VELOCITY);
-        cube.target_rotation.y = Degrees(*cube.target_rotation.y - dx * ROTATION_VELOCITY);
-
-        cube.target_rotation.x = cube.target_rotation.x.normalize();
-        cube.target_rotation.y = cube.target_rotation.y.normalize();
+        // rotate around screen axes, so dragging works the same from any side
+        let x_rotation =
+            Quaternion::from_axis_angle(&X_AXIS, Degrees(-dy * ROTATION_VELOCITY).into());
+        let y_rotation =
+            Quaternion::from_axis_angle(&Y_AXIS, Degrees(-dx * ROTATION_VELOCITY).into());
+        cube.target_rotation = (x_rotation * y_rotation * cube.target_rotation).normalize();
 
         cube.current_rotation = cube.target_rotation;
 
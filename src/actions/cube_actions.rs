@@ -1,6 +1,12 @@
 use crate::{
-    helpers::{cube::get_cube_rotation_for_position, ranges::project_to_range},
-    models::{CameraMode, Degrees, GameState, Range},
+    helpers::{
+        cube::{
+            get_cube_rotation_for_point, get_cube_side_axes, get_direction_vector,
+            get_interpolated_position_3d,
+        },
+        ranges::project_to_range,
+    },
+    models::{CameraMode, Degrees, GameState, Point3D, Quaternion, Range, THEMES, Y_AXIS},
 };
 
 const AUTO_ROTATION_STEP_RANGE: Range = Range(0.5, 10.0);
@@ -9,73 +15,108 @@ const AUTO_ROTATION_ANGLE_RANGE: Range = Range(0.0, 180.0);
 pub fn auto_rotate_loop(state: &mut GameState) {
     let cube = &mut state.scene.cube;
 
-    let current_rotation = &mut cube.current_rotation;
-    let target_rotation = &mut cube.target_rotation;
-
     if cube.camera_mode == CameraMode::Overview {
-        target_rotation.y = Degrees(*target_rotation.y - 0.3).normalize();
+        // rounding errors add up over many frames, so it's kept unit length
+        let step = Quaternion::from_axis_angle(&Y_AXIS, Degrees(-0.3).into());
+        cube.target_rotation = (cube.target_rotation * step).normalize();
     }
 
+    // follow head as it is drawn between cells, so camera moves continuously
     if cube.camera_mode == CameraMode::FollowSnake {
-        let head = state.snake.parts.get(0).unwrap();
-        *target_rotation = get_cube_rotation_for_position(head, &cube.grid);
+        let snake = &state.snake;
+        let head = snake.parts.front().unwrap();
+        let prev_head = snake.prev_parts.front().unwrap_or(head);
+        let (head_3d, head_3d_side) =
+            get_interpolated_position_3d(prev_head, head, &cube.grid, state.move_progress);
+
+        // direction is local to the head side, and until the edge is passed
+        // the head moves straight towards that side
+        let heading = if head_3d_side == head.side {
+            get_direction_vector(head.side, snake.direction)
+        } else {
+            let [_, _, normal] = get_cube_side_axes(head.side);
+            Point3D {
+                x: normal[0] as f64,
+                y: normal[1] as f64,
+                z: normal[2] as f64,
+            }
+        };
+        cube.target_rotation = get_cube_rotation_for_point(&head_3d, &heading);
     }
 
-    if current_rotation != target_rotation {
+    if cube.current_rotation != cube.target_rotation {
         cube.needs_redraw = true;
+        cube.current_rotation = make_rotation_step(cube.current_rotation, cube.target_rotation);
     }
+}
 
-    if current_rotation.x != target_rotation.x {
-        current_rotation.x = make_rotation_step(current_rotation.x, target_rotation.x);
+/// Switches between objects painted on side textures and objects drawn as
+/// 3D meshes, if meshes are supported
+pub fn toggle_objects_mode(state: &mut GameState) {
+    if state.scene.objects.program.is_none() {
+        return;
     }
 
-    if current_rotation.y != target_rotation.y {
-        current_rotation.y = make_rotation_step(current_rotation.y, target_rotation.y);
-    }
-}
+    let cube = &mut state.scene.cube;
 
-fn make_rotation_step(current_angle: Degrees, target_angle: Degrees) -> Degrees {
-    let angle_diff = ((*current_angle - *target_angle).abs())
-        .min((*current_angle - *target_angle - 360.0).abs())
-        .min((*current_angle - *target_angle + 360.0).abs());
+    cube.objects_as_meshes = !cube.objects_as_meshes;
+    cube.needs_redraw = true;
+    cube.sides
+        .values_mut()
+        .for_each(|side| side.needs_redraw = true);
+}
 
-    if angle_diff < AUTO_ROTATION_STEP_RANGE.0 {
-        return target_angle;
-    }
+/// Switches to the next built-in theme
+pub fn switch_theme(state: &mut GameState) {
+    let scene = &mut state.scene;
+    scene.theme_index = (scene.theme_index + 1) % THEMES.len();
 
-    let rotation_step = project_to_range(
-        angle_diff,
-        AUTO_ROTATION_ANGLE_RANGE,
-        AUTO_ROTATION_STEP_RANGE,
-    );
+    let cube = &mut scene.cube;
+    cube.needs_redraw = true;
+    cube.sides
+        .values_mut()
+        .for_each(|side| side.needs_redraw = true);
+}
 
-    let next_current_rotation = Degrees(
-        *current_angle + rotation_step * get_rotation_direction(current_angle, target_angle) as f64,
-    );
+/// Shows or hides minimap with unfolded cube
+pub fn toggle_minimap(state: &mut GameState) {
+    state.scene.is_minimap_hidden = !state.scene.is_minimap_hidden;
+}
 
-    next_current_rotation.normalize()
+/// Asks to save the next frame as PNG. The frame is drawn even if nothing
+/// changed, since webgl canvas is not kept between frames
+pub fn request_screenshot(state: &mut GameState) {
+    state.scene.is_screenshot_requested = true;
+    state.scene.cube.needs_redraw = true;
 }
 
-fn get_rotation_direction(from: Degrees, to: Degrees) -> i32 {
-    if from == to {
-        return 0;
-    }
+fn make_rotation_step(current: Quaternion, target: Quaternion) -> Quaternion {
+    let angle = Degrees::from(current.angle_to(&target));
 
-    if *from >= 0.0 && *to >= 0.0 {
-        return if *from > *to { -1 } else { 1 };
+    if *angle < AUTO_ROTATION_STEP_RANGE.0 {
+        return target;
     }
 
-    if *from <= 0.0 && *to <= 0.0 {
-        return if *from > *to { -1 } else { 1 };
-    }
+    let rotation_step =
+        project_to_range(*angle, AUTO_ROTATION_ANGLE_RANGE, AUTO_ROTATION_STEP_RANGE);
 
-    if *from >= 0.0 && *to <= 0.0 {
-        return if *from - *to <= 180.0 { -1 } else { 1 };
-    }
+    current.slerp(&target, rotation_step / *angle)
+}
 
-    if *from <= 0.0 && *to >= 0.0 {
-        return if -*from + *to < 180.0 { 1 } else { -1 };
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overview_rotation_stays_unit() {
+        let mut state = GameState::default();
+        state.scene.cube.camera_mode = CameraMode::Overview;
 
-    panic!("unreachable");
+        for _ in 0..100_000 {
+            auto_rotate_loop(&mut state);
+        }
+
+        let rotation = state.scene.cube.target_rotation;
+        assert!((rotation.dot(&rotation) - 1.0).abs() < 1e-12);
+    }
 }
@@ -1,57 +1,115 @@
 use std::collections::HashSet;
 
+use num_traits::FromPrimitive;
+
 use crate::{
-    helpers::cube::get_random_cube_position,
-    models::{CameraMode, CubePosition, GameState, GameStatus, Snake},
+    helpers::{cube::get_random_cube_position, random::Random},
+    models::{
+        CameraMode, CubePosition, GameEvent, GameState, GameStatus, Grid, PlayerId, Replay, Snake,
+    },
 };
 
-use super::{cube_actions::auto_rotate_loop, snake_actions::move_snake_loop};
-
-const APPLES_COUNT: usize = 10;
-const STONES_COUNT: usize = 10;
+use super::{
+    cube_actions::auto_rotate_loop,
+    ghost_actions::{load_ghost, save_best_run},
+    replay_actions::playback_loop,
+    snake_actions::move_snake_loop,
+};
 
-pub fn init_game_state(state: &mut GameState) {
+pub fn init_game_state(state: &mut GameState, seed: u32) {
     state.status = GameStatus::Welcome;
+    state.seed = seed;
     plant_objects(state);
 }
 
 pub fn update_game_state_loop(state: &mut GameState) {
-    move_snake_loop(state);
+    let tick = state.tick;
+
+    // spectator receives everything but camera from snapshots
+    if state.playback.is_some() {
+        playback_loop(state);
+    } else if !state.is_spectator {
+        move_snake_loop(state);
+    }
+
     auto_rotate_loop(state);
 
+    if !state.is_spectator {
+        check_game_over(state);
+    }
+
+    // restart starts ticks over, so only moves forward are counted
+    state.scene.stats.ticks += state.tick.saturating_sub(tick);
+}
+
+fn check_game_over(state: &mut GameState) {
     let cube = &mut state.scene.cube;
 
     if state.status == GameStatus::InGame {
+        // rivals who left the game are removed, so they count as crashed
+        let had_rivals = state.network.as_ref().is_some_and(|n| n.had_rivals);
+        let rivals_crashed = had_rivals && state.rivals.iter().all(|rival| rival.is_crashed);
+
         if state.snake.is_crashed {
             state.status = GameStatus::Fail;
             cube.camera_mode = CameraMode::Overview;
-            cube.sides
-                .values_mut()
-                .for_each(|side| side.needs_redraw = true);
-        }
-
-        if state.apples.is_empty() {
+        } else if state.apples.is_empty() || rivals_crashed {
             state.status = GameStatus::Win;
+            state.events.push(GameEvent::Win);
             cube.camera_mode = CameraMode::Overview;
-            cube.sides
-                .values_mut()
-                .for_each(|side| side.needs_redraw = true);
+        }
+
+        let is_live_game = state.network.is_none() && state.playback.is_none();
+        if state.status != GameStatus::InGame && is_live_game {
+            save_best_run(state);
         }
     }
 }
 
-fn plant_objects(state: &mut GameState) {
+/// Plants snakes, apples and stones. Layout is determined by seed and rules
+/// only, so all network clients get the same board
+pub fn plant_objects(state: &mut GameState) {
     let mut object_positions: HashSet<CubePosition> = HashSet::new();
 
-    // plant snake
-    state.snake = Snake::default();
+    state.random = Random::new(state.seed);
+    state.tick = 0;
+    state.ghost = None;
+    state.events.clear();
+    state.replay = Replay {
+        seed: state.seed,
+        rules: state.rules.clone(),
+        ..Replay::default()
+    };
+
+    let cube = &mut state.scene.cube;
+    cube.grid = Grid {
+        rows_count: state.rules.grid_size,
+        cols_count: state.rules.grid_size,
+    };
+
+    // plant snakes
+    let mut player_ids: Vec<PlayerId> = std::iter::once(state.snake.player_id)
+        .chain(state.rivals.iter().map(|rival| rival.player_id))
+        .collect();
+    player_ids.sort_unstable();
+
+    state.snake = spawn_snake(state.snake.player_id, &player_ids);
+    state.rivals = state
+        .rivals
+        .iter()
+        .map(|rival| spawn_snake(rival.player_id, &player_ids))
+        .collect();
+
     object_positions.insert(*state.snake.parts.get(0).unwrap());
+    for rival in &state.rivals {
+        object_positions.insert(*rival.parts.front().unwrap());
+    }
 
     // plant apples
     state.apples.clear();
 
-    while state.apples.len() < APPLES_COUNT {
-        let pos = get_random_cube_position(&state.scene.cube);
+    while state.apples.len() < state.rules.apples_count {
+        let pos = get_random_cube_position(&state.scene.cube, &mut state.random);
 
         // do not plant above other objects
         if !object_positions.contains(&pos) {
@@ -63,8 +121,8 @@ fn plant_objects(state: &mut GameState) {
     // plant stones
     state.stones.clear();
 
-    while state.stones.len() < STONES_COUNT {
-        let pos = get_random_cube_position(&state.scene.cube);
+    while state.stones.len() < state.rules.stones_count {
+        let pos = get_random_cube_position(&state.scene.cube, &mut state.random);
 
         if !object_positions.contains(&pos) {
             state.stones.insert(pos);
@@ -77,12 +135,45 @@ fn plant_objects(state: &mut GameState) {
     }
 }
 
+// each player starts in the corner of its own cube side
+fn spawn_snake(player_id: PlayerId, player_ids: &[PlayerId]) -> Snake {
+    let index = player_ids.iter().position(|id| *id == player_id).unwrap();
+
+    let mut snake = Snake {
+        player_id,
+        ..Snake::default()
+    };
+
+    snake.parts.get_mut(0).unwrap().side = FromPrimitive::from_usize(index % 6).unwrap();
+    snake
+}
+
+/// Pauses game which is in progress, since player can't see the board while
+/// webgl context is lost. Network game goes on without the player
+pub fn pause_on_context_loss(state: &mut GameState) {
+    if state.status == GameStatus::InGame && state.network.is_none() && state.playback.is_none() {
+        start_or_pause_game(state);
+        state.is_paused_on_context_loss = true;
+    }
+}
+
+/// Resumes game paused by context loss. Game paused by player stays paused
+pub fn resume_after_context_restore(state: &mut GameState) {
+    if state.is_paused_on_context_loss && state.status == GameStatus::Paused {
+        start_or_pause_game(state);
+    }
+    state.is_paused_on_context_loss = false;
+}
+
 pub fn start_or_pause_game(state: &mut GameState) {
     match state.status {
         GameStatus::Welcome | GameStatus::Paused => {
             state.status = GameStatus::InGame;
         }
         GameStatus::Win | GameStatus::Fail => {
+            if !state.is_seed_fixed {
+                state.seed = state.random.next_u32();
+            }
             plant_objects(state);
             state.status = GameStatus::InGame;
         }
@@ -91,13 +182,93 @@ pub fn start_or_pause_game(state: &mut GameState) {
         }
     }
 
+    if state.status == GameStatus::InGame && state.tick == 0 {
+        load_ghost(state);
+    }
+
     if state.status == GameStatus::InGame {
         state.scene.cube.camera_mode = CameraMode::FollowSnake;
     } else {
         state.scene.cube.camera_mode = CameraMode::Overview;
     }
+}
 
-    for side in state.scene.cube.sides.values_mut() {
-        side.needs_redraw = true;
+#[cfg(test)]
+mod tests {
+    use crate::models::NetworkSession;
+
+    use super::*;
+
+    #[test]
+    fn context_loss_pauses_running_game_only() {
+        let mut state = GameState {
+            status: GameStatus::InGame,
+            tick: 5,
+            ..GameState::default()
+        };
+
+        pause_on_context_loss(&mut state);
+        assert_eq!(state.status, GameStatus::Paused);
+
+        resume_after_context_restore(&mut state);
+        assert_eq!(state.status, GameStatus::InGame);
+
+        // game paused by player is not resumed
+        start_or_pause_game(&mut state);
+        pause_on_context_loss(&mut state);
+        resume_after_context_restore(&mut state);
+        assert_eq!(state.status, GameStatus::Paused);
+    }
+
+    fn start_network_game(rivals_count: usize) -> GameState {
+        let mut state = GameState {
+            network: Some(NetworkSession {
+                had_rivals: rivals_count > 0,
+                ..NetworkSession::default()
+            }),
+            rivals: (1..=rivals_count)
+                .map(|player_id| Snake {
+                    player_id: player_id as PlayerId,
+                    ..Snake::default()
+                })
+                .collect(),
+            ..GameState::default()
+        };
+
+        init_game_state(&mut state, 1);
+        state.status = GameStatus::InGame;
+        state
+    }
+
+    #[test]
+    fn crash_on_the_same_tick_as_last_rival_is_fail() {
+        let mut state = start_network_game(1);
+        state.snake.is_crashed = true;
+        state.rivals[0].is_crashed = true;
+
+        check_game_over(&mut state);
+        assert_eq!(state.status, GameStatus::Fail);
+        assert!(!state.events.contains(&GameEvent::Win));
+    }
+
+    #[test]
+    fn last_rival_leaving_is_win() {
+        let mut state = start_network_game(2);
+
+        state.rivals[0].is_crashed = true;
+        check_game_over(&mut state);
+        assert_eq!(state.status, GameStatus::InGame);
+
+        state.rivals.pop();
+        check_game_over(&mut state);
+        assert_eq!(state.status, GameStatus::Win);
+    }
+
+    #[test]
+    fn network_game_without_rivals_goes_on() {
+        let mut state = start_network_game(0);
+
+        check_game_over(&mut state);
+        assert_eq!(state.status, GameStatus::InGame);
     }
 }
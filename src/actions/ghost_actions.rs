@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use crate::{
+    helpers::{
+        cube::mark_cube_position_changed,
+        replay_file::{decode_replay, encode_replay},
+        storage::{get_storage_bytes, set_storage_bytes},
+    },
+    models::{CubePosition, GameState, Ghost, Replay},
+};
+
+use super::{
+    game_actions::plant_objects, replay_actions::apply_recorded_inputs, snake_actions::move_snakes,
+};
+
+/// Loads best run for current seed and rules, if there was any
+pub fn load_ghost(state: &mut GameState) {
+    state.ghost = get_storage_bytes(&get_best_run_key(state))
+        .and_then(|bytes| decode_replay(&bytes).ok())
+        .map(create_ghost);
+
+    for side in state.scene.cube.sides.values_mut() {
+        side.needs_redraw = true;
+    }
+}
+
+/// Saves finished game as the new best run, if it beats the current one
+pub fn save_best_run(state: &GameState) {
+    // random board is never played again, so its run would only fill storage
+    if !state.is_seed_fixed {
+        return;
+    }
+
+    let apples_eaten = get_apples_eaten(state);
+
+    let is_best = match &state.ghost {
+        Some(ghost) => is_better_run(
+            apples_eaten,
+            state.tick,
+            ghost.best_apples_eaten,
+            ghost.best_ticks_count,
+            state.rules.apples_count,
+        ),
+        None => true,
+    };
+
+    if is_best {
+        set_storage_bytes(&get_best_run_key(state), &encode_replay(&state.replay));
+    }
+}
+
+pub fn create_ghost(replay: Replay) -> Ghost {
+    // play the whole run once beforehand to know its result
+    let mut result = create_ghost_state(&replay);
+    let mut next_input = 0;
+    while step_ghost_state(&mut result, &replay, &mut next_input) {}
+
+    Ghost {
+        state: Box::new(create_ghost_state(&replay)),
+        next_input: 0,
+        best_apples_eaten: get_apples_eaten(&result),
+        best_ticks_count: result.tick,
+        replay,
+    }
+}
+
+/// Moves ghost to the same tick as live game
+pub fn step_ghost(state: &mut GameState) {
+    let ghost = match state.ghost.as_mut() {
+        Some(ghost) => ghost,
+        None => return,
+    };
+
+    let prev_parts: HashSet<CubePosition> = ghost.state.snake.parts.iter().copied().collect();
+
+    while ghost.state.tick < state.tick
+        && step_ghost_state(&mut ghost.state, &ghost.replay, &mut ghost.next_input)
+    {}
+
+    // ghost has no effects
+    ghost.state.events.clear();
+
+    // ghost is drawn over the live board, so redraw cells it has moved over
+    let parts: HashSet<CubePosition> = ghost.state.snake.parts.iter().copied().collect();
+    for pos in prev_parts.symmetric_difference(&parts) {
+        mark_cube_position_changed(&mut state.scene.cube, pos);
+    }
+}
+
+pub fn get_apples_eaten(state: &GameState) -> usize {
+    state.rules.apples_count - state.apples.len()
+}
+
+fn create_ghost_state(replay: &Replay) -> GameState {
+    let mut state = GameState {
+        seed: replay.seed,
+        rules: replay.rules.clone(),
+        ..GameState::default()
+    };
+
+    plant_objects(&mut state);
+    state
+}
+
+// returns false when ghost run is over
+fn step_ghost_state(state: &mut GameState, replay: &Replay, next_input: &mut usize) -> bool {
+    if state.snake.is_crashed || state.apples.is_empty() || state.tick >= replay.ticks_count {
+        return false;
+    }
+
+    apply_recorded_inputs(&mut state.snake, &replay.inputs, next_input, state.tick);
+    move_snakes(state);
+    true
+}
+
+// more apples is better. when all apples eaten, faster is better
+fn is_better_run(
+    apples_eaten: usize,
+    ticks_count: u32,
+    best_apples_eaten: usize,
+    best_ticks_count: u32,
+    apples_count: usize,
+) -> bool {
+    apples_eaten > best_apples_eaten
+        || (apples_eaten == best_apples_eaten
+            && apples_eaten == apples_count
+            && ticks_count < best_ticks_count)
+}
+
+fn get_best_run_key(state: &GameState) -> String {
+    let rules = &state.rules;
+    format!(
+        "best-run-{}-{}-{}-{}",
+        state.seed, rules.grid_size, rules.apples_count, rules.stones_count
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Direction, GameRules, ReplayInput};
+
+    #[test]
+    fn ghost_repeats_recorded_run() {
+        let replay = Replay {
+            seed: 42,
+            rules: GameRules::default(),
+            inputs: vec![
+                ReplayInput {
+                    tick: 5,
+                    direction: Direction::Up,
+                },
+                ReplayInput {
+                    tick: 9,
+                    direction: Direction::Left,
+                },
+            ],
+            ticks_count: 30,
+        };
+
+        // record live game with the same inputs
+        let mut live = create_ghost_state(&replay);
+        for tick in 0..replay.ticks_count {
+            if let Some(input) = replay.inputs.iter().find(|input| input.tick == tick) {
+                live.snake.direction = input.direction;
+            }
+            move_snakes(&mut live);
+        }
+
+        let mut state = create_ghost_state(&replay);
+        state.ghost = Some(create_ghost(replay));
+
+        while state.tick < 30 {
+            move_snakes(&mut state);
+            step_ghost(&mut state);
+        }
+
+        let ghost = state.ghost.as_ref().unwrap();
+        assert_eq!(ghost.state.tick, live.tick);
+        assert_eq!(ghost.state.snake.parts, live.snake.parts);
+        assert_eq!(ghost.best_apples_eaten, get_apples_eaten(&live));
+    }
+
+    #[test]
+    fn better_run() {
+        assert!(is_better_run(3, 100, 2, 50, 10));
+        assert!(!is_better_run(2, 40, 2, 50, 10));
+        assert!(is_better_run(10, 40, 10, 50, 10));
+        assert!(!is_better_run(10, 60, 10, 50, 10));
+    }
+}
@@ -1,4 +1,11 @@
 pub mod control_actions;
 pub mod cube_actions;
 pub mod game_actions;
+pub mod ghost_actions;
+pub mod network_actions;
+pub mod palette_actions;
+pub mod perf_actions;
+pub mod quality_actions;
+pub mod replay_actions;
 pub mod snake_actions;
+pub mod spectator_actions;
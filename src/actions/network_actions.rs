@@ -0,0 +1,147 @@
+use crate::{
+    helpers::{cube::mark_cube_position_changed, state_hash::get_state_hash},
+    models::{CameraMode, Direction, GameState, GameStatus, NetworkSession, Snake},
+    network::protocol::{ClientMessage, ServerMessage, PROTOCOL_VERSION},
+};
+
+use super::{
+    game_actions::plant_objects,
+    snake_actions::{move_snakes, set_snake_direction},
+};
+
+pub fn join_network_game(state: &mut GameState, name: &str) {
+    state.network = Some(NetworkSession {
+        outbox: vec![ClientMessage::Join {
+            version: PROTOCOL_VERSION,
+            name: name.to_string(),
+        }],
+        ..NetworkSession::default()
+    });
+}
+
+/// Queues direction change. It is not applied right away, but when server
+/// broadcasts it back as part of the tick, at the same time on all clients
+pub fn send_snake_direction(state: &mut GameState, direction: Direction) {
+    let tick = state.tick;
+    let session = state.network.as_mut().unwrap();
+
+    if session.is_started && session.desync_tick.is_none() {
+        session
+            .outbox
+            .push(ClientMessage::Input { tick, direction });
+    }
+}
+
+pub fn handle_server_message(state: &mut GameState, msg: ServerMessage) -> Result<(), String> {
+    let session = state.network.as_mut().ok_or("not in network game")?;
+
+    match msg {
+        ServerMessage::Welcome { player_id } => {
+            session.player_id = Some(player_id);
+        }
+        ServerMessage::Rejected { reason } => {
+            return Err(format!("server rejected to join: {reason}"));
+        }
+        ServerMessage::PlayerJoined { .. }
+        | ServerMessage::Snapshot { .. }
+        | ServerMessage::SpectatorJoined => {}
+        ServerMessage::Start {
+            seed,
+            rules,
+            player_ids,
+            ..
+        } => {
+            let player_id = session.player_id.ok_or("game started before welcome")?;
+            session.is_started = true;
+
+            state.seed = seed;
+            state.rules = rules;
+            state.snake.player_id = player_id;
+            state.rivals = player_ids
+                .into_iter()
+                .filter(|id| *id != player_id)
+                .map(|player_id| Snake {
+                    player_id,
+                    ..Snake::default()
+                })
+                .collect();
+
+            let session = state.network.as_mut().unwrap();
+            session.had_rivals = !state.rivals.is_empty();
+
+            plant_objects(state);
+
+            state.status = GameStatus::InGame;
+            state.scene.cube.camera_mode = CameraMode::FollowSnake;
+        }
+        ServerMessage::Tick {
+            tick,
+            inputs,
+            left_player_ids,
+        } => {
+            if !session.is_started || session.desync_tick.is_some() {
+                return Ok(());
+            }
+
+            if tick != state.tick {
+                return Err(format!("expected tick {}, but got {tick}", state.tick));
+            }
+
+            for (player_id, direction) in inputs {
+                if let Some(snake) = get_snake_mut(state, player_id) {
+                    set_snake_direction(snake, direction);
+                }
+            }
+
+            for player_id in left_player_ids {
+                remove_rival(state, player_id);
+            }
+
+            move_snakes(state);
+
+            let hash = get_state_hash(state);
+            state
+                .network
+                .as_mut()
+                .unwrap()
+                .outbox
+                .push(ClientMessage::StateHash {
+                    tick: state.tick,
+                    hash,
+                });
+        }
+        ServerMessage::Desync { tick } => {
+            session.desync_tick = Some(tick);
+            return Err(format!("simulation desynced at tick {tick}"));
+        }
+    }
+
+    Ok(())
+}
+
+fn get_snake_mut(state: &mut GameState, player_id: u32) -> Option<&mut Snake> {
+    if state.snake.player_id == player_id {
+        return Some(&mut state.snake);
+    }
+
+    state
+        .rivals
+        .iter_mut()
+        .find(|rival| rival.player_id == player_id)
+}
+
+fn remove_rival(state: &mut GameState, player_id: u32) {
+    let cube = &mut state.scene.cube;
+
+    state.rivals.retain(|rival| {
+        if rival.player_id != player_id {
+            return true;
+        }
+
+        for part in &rival.parts {
+            mark_cube_position_changed(cube, part);
+        }
+
+        false
+    });
+}
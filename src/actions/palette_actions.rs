@@ -0,0 +1,51 @@
+use crate::{
+    helpers::storage::{get_storage_item, set_storage_item},
+    models::{GameState, PALETTES},
+};
+
+const PALETTE_STORAGE_KEY: &str = "palette";
+const SHAPE_CODING_STORAGE_KEY: &str = "shape_coding";
+
+/// Loads palette and shape coding saved by player
+pub fn load_palette(state: &mut GameState) {
+    let scene = &mut state.scene;
+
+    scene.palette_index = get_storage_item(PALETTE_STORAGE_KEY)
+        .and_then(|name| PALETTES.iter().position(|palette| palette.name == name))
+        .unwrap_or(0);
+    scene.is_shape_coding_on = get_storage_item(SHAPE_CODING_STORAGE_KEY).as_deref() == Some("on");
+}
+
+/// Switches to the next built-in palette, and saves the choice
+pub fn switch_palette(state: &mut GameState) {
+    let scene = &mut state.scene;
+    scene.palette_index = (scene.palette_index + 1) % PALETTES.len();
+    redraw_cube(state);
+
+    set_storage_item(
+        PALETTE_STORAGE_KEY,
+        PALETTES[state.scene.palette_index].name,
+    );
+}
+
+/// Turns shapes of objects painted on sides on or off, and saves the choice
+pub fn toggle_shape_coding(state: &mut GameState) {
+    let scene = &mut state.scene;
+    scene.is_shape_coding_on = !scene.is_shape_coding_on;
+    redraw_cube(state);
+
+    let value = if state.scene.is_shape_coding_on {
+        "on"
+    } else {
+        "off"
+    };
+    set_storage_item(SHAPE_CODING_STORAGE_KEY, value);
+}
+
+fn redraw_cube(state: &mut GameState) {
+    let cube = &mut state.scene.cube;
+    cube.needs_redraw = true;
+    cube.sides
+        .values_mut()
+        .for_each(|side| side.needs_redraw = true);
+}
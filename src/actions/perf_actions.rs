@@ -0,0 +1,135 @@
+use crate::models::{FrameSample, GameState, PerfSummary, PROFILER_SAMPLES_COUNT};
+
+// milliseconds between summary updates
+const SUMMARY_PERIOD: f64 = 500.0;
+
+// longer gaps happen when tab is in background or context is lost, and say
+// nothing about speed
+const MAX_FRAME_TIME: f64 = 1000.0;
+
+/// Shows or hides performance HUD
+pub fn toggle_perf_hud(state: &mut GameState) {
+    state.scene.is_perf_hud_shown = !state.scene.is_perf_hud_shown;
+}
+
+/// Moves counters of finished frame to profiler, and starts counting the next
+/// frame from zero
+pub fn record_frame_stats(state: &mut GameState, now: f64) {
+    let scene = &mut state.scene;
+    let stats = &mut scene.stats;
+    let profiler = &mut scene.profiler;
+
+    let sample = FrameSample {
+        frame_time: profiler.last_frame_time.map_or(0.0, |time| now - time),
+        ticks: std::mem::take(&mut stats.ticks),
+        draw_calls: std::mem::take(&mut stats.draw_calls),
+        side_repaints: std::mem::take(&mut stats.side_repaints),
+        uploaded_bytes: std::mem::take(&mut stats.uploaded_bytes),
+    };
+
+    // the first frame only starts the clock
+    if profiler.last_frame_time.replace(now).is_none() || sample.frame_time > MAX_FRAME_TIME {
+        return;
+    }
+
+    profiler.samples.push_back(sample);
+    profiler.samples_count += 1;
+    if profiler.samples.len() > PROFILER_SAMPLES_COUNT {
+        profiler.samples.pop_front();
+    }
+
+    if now - profiler.last_summary_time >= SUMMARY_PERIOD {
+        profiler.summary = get_perf_summary(profiler.samples.iter());
+        profiler.last_summary_time = now;
+        profiler.is_summary_exported = false;
+    }
+}
+
+/// Sums up frames. Percentiles show stutters, which average frame time hides
+pub fn get_perf_summary<'a>(samples: impl Iterator<Item = &'a FrameSample>) -> PerfSummary {
+    let samples: Vec<&FrameSample> = samples.collect();
+
+    let total_time: f64 = samples.iter().map(|sample| sample.frame_time).sum();
+    if samples.is_empty() || total_time <= 0.0 {
+        return PerfSummary::default();
+    }
+
+    let mut frame_times: Vec<f64> = samples.iter().map(|sample| sample.frame_time).collect();
+    frame_times.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |p: f64| frame_times[((frame_times.len() - 1) as f64 * p).round() as usize];
+
+    let frames_count = samples.len() as f64;
+    let per_frame = |value: fn(&FrameSample) -> f64| {
+        samples.iter().map(|sample| value(sample)).sum::<f64>() / frames_count
+    };
+    let per_second = |value: fn(&FrameSample) -> f64| {
+        samples.iter().map(|sample| value(sample)).sum::<f64>() / total_time * 1000.0
+    };
+
+    PerfSummary {
+        fps: frames_count / total_time * 1000.0,
+        frame_time_p50: percentile(0.5),
+        frame_time_p95: percentile(0.95),
+        frame_time_p99: percentile(0.99),
+        ticks_per_frame: per_frame(|sample| sample.ticks as f64),
+        draw_calls_per_frame: per_frame(|sample| sample.draw_calls as f64),
+        side_repaints_per_second: per_second(|sample| sample.side_repaints as f64),
+        uploaded_bytes_per_second: per_second(|sample| sample.uploaded_bytes as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_shows_slow_frames_in_percentiles() {
+        // 98 smooth frames and two hitches
+        let mut samples: Vec<FrameSample> = (0..98)
+            .map(|_| FrameSample {
+                frame_time: 10.0,
+                draw_calls: 3,
+                uploaded_bytes: 100,
+                ..FrameSample::default()
+            })
+            .collect();
+        for _ in 0..2 {
+            samples.push(FrameSample {
+                frame_time: 110.0,
+                draw_calls: 3,
+                uploaded_bytes: 100,
+                ..FrameSample::default()
+            });
+        }
+
+        let summary = get_perf_summary(samples.iter());
+
+        // 100 frames in 1.2 seconds
+        assert!((summary.fps - 100.0 / 1.2).abs() < 1e-9);
+        assert_eq!(summary.frame_time_p50, 10.0);
+        assert_eq!(summary.frame_time_p99, 110.0);
+        assert_eq!(summary.draw_calls_per_frame, 3.0);
+        assert!((summary.uploaded_bytes_per_second - 10000.0 / 1.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn counters_start_over_each_frame() {
+        let mut state = GameState::default();
+
+        record_frame_stats(&mut state, 0.0);
+        state.scene.stats.side_repaints = 6;
+        state.scene.stats.ticks = 1;
+        record_frame_stats(&mut state, 16.0);
+
+        let profiler = &state.scene.profiler;
+        assert_eq!(profiler.samples.len(), 1);
+        assert_eq!(profiler.samples[0].frame_time, 16.0);
+        assert_eq!(profiler.samples[0].side_repaints, 6);
+        assert_eq!(state.scene.stats.side_repaints, 0);
+        assert_eq!(state.scene.stats.ticks, 0);
+
+        // frames after long pause are not counted
+        record_frame_stats(&mut state, 5000.0);
+        assert_eq!(state.scene.profiler.samples.len(), 1);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{
+    helpers::storage::{get_storage_item, set_storage_item},
+    models::{GameState, QualitySetting, QUALITY_PRESETS},
+};
+
+const QUALITY_STORAGE_KEY: &str = "quality";
+
+// animation frames come once per display refresh, or every few refreshes when
+// drawing doesn't keep up. auto quality steps down when typical frame takes
+// longer than this many refresh intervals, so it works the same on 60 and
+// 144 Hz displays
+const FRAME_TIME_BUDGET: f64 = 1.5;
+
+// device which is slow from the very first frame looks like a slow display, so
+// refresh interval is never taken longer than at 60 Hz
+const MAX_REFRESH_INTERVAL: f64 = 1000.0 / 60.0;
+
+// share of the fastest frames which tells refresh interval. the very fastest
+// ones are off because of timer jitter
+const REFRESH_PERCENTILE: f64 = 0.1;
+
+// frames measured before each decision
+const FRAME_TIMES_COUNT: usize = 60;
+
+/// Loads quality setting saved by player. Must run before scene is initialized,
+/// since antialiasing is set on webgl context creation only
+pub fn load_quality(state: &mut GameState) {
+    let setting = match get_storage_item(QUALITY_STORAGE_KEY) {
+        Some(name) => match QUALITY_PRESETS.iter().position(|p| p.name == name) {
+            Some(index) => QualitySetting::Fixed(index),
+            None => QualitySetting::Auto,
+        },
+        None => QualitySetting::Auto,
+    };
+
+    set_quality_setting(state, setting);
+}
+
+/// Switches between auto setting and presets from the lowest to the highest,
+/// and saves the choice
+pub fn switch_quality(state: &mut GameState) {
+    let setting = match state.scene.quality.setting {
+        QualitySetting::Auto => QualitySetting::Fixed(0),
+        QualitySetting::Fixed(index) if index + 1 < QUALITY_PRESETS.len() => {
+            QualitySetting::Fixed(index + 1)
+        }
+        QualitySetting::Fixed(_) => QualitySetting::Auto,
+    };
+
+    set_quality_setting(state, setting);
+
+    let name = match setting {
+        QualitySetting::Auto => "auto",
+        QualitySetting::Fixed(index) => QUALITY_PRESETS[index].name,
+    };
+    set_storage_item(QUALITY_STORAGE_KEY, name);
+}
+
+/// Steps auto quality down when typical frame of recent ones, as measured by
+/// profiler, misses display refreshes
+pub fn adapt_quality_loop(state: &mut GameState) {
+    let scene = &mut state.scene;
+    let profiler = &scene.profiler;
+    let quality = &mut scene.quality;
+
+    if quality.setting != QualitySetting::Auto || quality.preset_index == 0 {
+        return;
+    }
+
+    if profiler.samples_count - quality.measured_samples_count < FRAME_TIMES_COUNT {
+        return;
+    }
+    quality.measured_samples_count = profiler.samples_count;
+
+    // median is not thrown off by occasional hiccups, like garbage collection
+    let mut frame_times: Vec<f64> = profiler
+        .samples
+        .iter()
+        .rev()
+        .take(FRAME_TIMES_COUNT)
+        .map(|sample| sample.frame_time)
+        .collect();
+    frame_times.sort_by(|a, b| a.total_cmp(b));
+    let median = frame_times[frame_times.len() / 2];
+
+    // refresh rate doesn't change, while frames may only get slower, so the
+    // fastest frames seen so far are the closest to it
+    let fastest = frame_times[(frame_times.len() as f64 * REFRESH_PERCENTILE) as usize];
+    let refresh_interval = quality
+        .refresh_interval
+        .map_or(fastest, |interval| interval.min(fastest));
+    quality.refresh_interval = Some(refresh_interval);
+
+    if median > refresh_interval.min(MAX_REFRESH_INTERVAL) * FRAME_TIME_BUDGET {
+        let index = quality.preset_index - 1;
+        set_quality_preset(state, index);
+    }
+}
+
+fn set_quality_setting(state: &mut GameState, setting: QualitySetting) {
+    state.scene.quality.setting = setting;
+
+    let index = match setting {
+        QualitySetting::Auto => QUALITY_PRESETS.len() - 1,
+        QualitySetting::Fixed(index) => index,
+    };
+    set_quality_preset(state, index);
+}
+
+fn set_quality_preset(state: &mut GameState, index: usize) {
+    let quality = &mut state.scene.quality;
+
+    if quality.preset_index != index {
+        quality.preset_index = index;
+        quality.needs_apply = true;
+    }
+
+    // frames measured with previous preset don't count
+    quality.measured_samples_count = state.scene.profiler.samples_count;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::perf_actions::record_frame_stats;
+
+    fn run_frames(state: &mut GameState, count: usize, frame_time: f64) {
+        let start = match state.scene.profiler.last_frame_time {
+            Some(time) => time,
+            None => {
+                record_frame_stats(state, 0.0);
+                0.0
+            }
+        };
+
+        for i in 1..=count {
+            record_frame_stats(state, start + i as f64 * frame_time);
+            adapt_quality_loop(state);
+        }
+    }
+
+    #[test]
+    fn auto_quality_steps_down_on_slow_frames() {
+        let mut state = GameState::default();
+        let highest = QUALITY_PRESETS.len() - 1;
+
+        run_frames(&mut state, FRAME_TIMES_COUNT * 2, 10.0);
+        assert_eq!(state.scene.quality.preset_index, highest);
+        assert!(!state.scene.quality.needs_apply);
+
+        run_frames(&mut state, FRAME_TIMES_COUNT, 30.0);
+        assert_eq!(state.scene.quality.preset_index, highest - 1);
+        assert!(state.scene.quality.needs_apply);
+
+        // never goes below the lowest
+        run_frames(&mut state, FRAME_TIMES_COUNT * 10, 30.0);
+        assert_eq!(state.scene.quality.preset_index, 0);
+    }
+
+    #[test]
+    fn auto_quality_steps_down_when_slow_from_start() {
+        let mut state = GameState::default();
+        let highest = QUALITY_PRESETS.len() - 1;
+
+        // the fastest frames are as slow as typical ones
+        run_frames(&mut state, FRAME_TIMES_COUNT, 50.0);
+        assert_eq!(state.scene.quality.preset_index, highest - 1);
+    }
+
+    #[test]
+    fn auto_quality_steps_down_on_skipped_refreshes() {
+        let mut state = GameState::default();
+        let highest = QUALITY_PRESETS.len() - 1;
+        let refresh_interval = 1000.0 / 144.0;
+
+        run_frames(&mut state, FRAME_TIMES_COUNT, refresh_interval);
+        assert_eq!(state.scene.quality.preset_index, highest);
+
+        // about 60 fps fits in 20ms, but most refreshes are dropped
+        run_frames(&mut state, FRAME_TIMES_COUNT, refresh_interval * 2.5);
+        assert_eq!(state.scene.quality.preset_index, highest - 1);
+    }
+
+    #[test]
+    fn fixed_quality_stays() {
+        let mut state = GameState::default();
+        set_quality_setting(&mut state, QualitySetting::Fixed(1));
+
+        run_frames(&mut state, FRAME_TIMES_COUNT * 2, 30.0);
+        assert_eq!(state.scene.quality.preset_index, 1);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::models::{
+    CameraMode, Direction, GameState, GameStatus, Playback, Replay, ReplayInput, Snake,
+};
+
+use super::{
+    game_actions::plant_objects,
+    snake_actions::{get_move_progress, move_snakes, set_move_progress, set_snake_direction},
+};
+
+// number of snake moves to skip per seek
+const SEEK_STEP: u32 = 20;
+
+pub fn record_input(state: &mut GameState, direction: Direction) {
+    state.replay.inputs.push(ReplayInput {
+        tick: state.tick,
+        direction,
+    });
+}
+
+pub fn start_playback(state: &mut GameState, replay: Replay) {
+    state.seed = replay.seed;
+    state.rules = replay.rules.clone();
+    plant_objects(state);
+
+    state.playback = Some(Playback::new(replay));
+    state.status = GameStatus::InGame;
+    state.scene.cube.camera_mode = CameraMode::FollowSnake;
+}
+
+pub fn stop_playback(state: &mut GameState) {
+    state.playback = None;
+    if !state.is_seed_fixed {
+        state.seed = state.random.next_u32();
+    }
+    plant_objects(state);
+
+    state.status = GameStatus::Welcome;
+    state.scene.cube.camera_mode = CameraMode::Overview;
+}
+
+pub fn playback_loop(state: &mut GameState) {
+    let playback = state.playback.as_ref().unwrap();
+
+    if playback.is_paused || state.status != GameStatus::InGame {
+        set_move_progress(state, 1.0);
+        return;
+    }
+
+    let now = web_sys::window().unwrap().performance().unwrap().now();
+    let period = state.snake.move_period.as_millis() as f64 / playback.speed;
+
+    let last_move_time = state.snake.last_move_time;
+    if last_move_time.is_none() || now - last_move_time.unwrap() >= period {
+        step_playback(state);
+        state.snake.last_move_time = Some(now);
+    }
+
+    set_move_progress(state, get_move_progress(&state.snake, now, period));
+}
+
+fn step_playback(state: &mut GameState) {
+    let playback = state.playback.as_mut().unwrap();
+
+    if state.tick >= playback.replay.ticks_count {
+        // recording is over, but game was not (eg. player started new one)
+        set_playback_paused(state, true);
+        return;
+    }
+
+    apply_recorded_inputs(
+        &mut state.snake,
+        &playback.replay.inputs,
+        &mut playback.next_input,
+        state.tick,
+    );
+
+    move_snakes(state);
+}
+
+/// Turns snake as recorded for the tick. Inputs are applied in order starting
+/// from the next one, and the index is moved past applied inputs
+pub fn apply_recorded_inputs(
+    snake: &mut Snake,
+    inputs: &[ReplayInput],
+    next_input: &mut usize,
+    tick: u32,
+) {
+    while *next_input < inputs.len() && inputs[*next_input].tick <= tick {
+        set_snake_direction(snake, inputs[*next_input].direction);
+        *next_input += 1;
+    }
+}
+
+pub fn toggle_playback_pause(state: &mut GameState) {
+    match state.status {
+        GameStatus::Win | GameStatus::Fail => {
+            // play again from the start
+            seek_playback(state, 0);
+            set_playback_paused(state, false);
+        }
+        _ => {
+            let is_paused = state.playback.as_ref().unwrap().is_paused;
+            set_playback_paused(state, !is_paused);
+        }
+    }
+}
+
+fn set_playback_paused(state: &mut GameState, is_paused: bool) {
+    state.playback.as_mut().unwrap().is_paused = is_paused;
+
+    if is_paused {
+        state.status = GameStatus::Paused;
+        state.scene.cube.camera_mode = CameraMode::Overview;
+    } else {
+        state.status = GameStatus::InGame;
+        state.scene.cube.camera_mode = CameraMode::FollowSnake;
+    }
+}
+
+pub fn set_playback_speed(state: &mut GameState, speed: f64) {
+    state.playback.as_mut().unwrap().speed = speed;
+}
+
+pub fn seek_playback_by_step(state: &mut GameState, forward: bool) {
+    let tick = if forward {
+        state.tick + SEEK_STEP
+    } else {
+        state.tick.saturating_sub(SEEK_STEP)
+    };
+
+    seek_playback(state, tick);
+}
+
+/// Fast forwards simulation to the tick. Simulation can't go backwards, so
+/// seeking back replays the game from the start
+pub fn seek_playback(state: &mut GameState, tick: u32) {
+    let prev_status = state.status;
+
+    if tick < state.tick || matches!(prev_status, GameStatus::Win | GameStatus::Fail) {
+        let playback = state.playback.as_mut().unwrap();
+        playback.next_input = 0;
+
+        state.seed = playback.replay.seed;
+        state.rules = playback.replay.rules.clone();
+        plant_objects(state);
+    }
+
+    let ticks_count = state.playback.as_ref().unwrap().replay.ticks_count;
+    let tick = tick.min(ticks_count);
+
+    while state.tick < tick && !state.snake.is_crashed && !state.apples.is_empty() {
+        step_playback(state);
+    }
+
+    // skipped moves have no effects
+    state.events.clear();
+
+    // let game over checks decide if seek has reached the end
+    state.status = match prev_status {
+        GameStatus::Win | GameStatus::Fail => GameStatus::InGame,
+        status => status,
+    };
+
+    for side in state.scene.cube.sides.values_mut() {
+        side.needs_redraw = true;
+    }
+}
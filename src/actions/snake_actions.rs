@@ -1,14 +1,24 @@
-use std::time::Duration;
+use std::{collections::HashSet, time::Duration};
 
 use crate::{
-    helpers::{cube::get_next_cube_position_and_direction, direction::get_opposite_direction},
-    models::{Direction, GameState, GameStatus},
+    helpers::{
+        cube::{get_next_cube_position_and_direction, mark_cube_position_changed},
+        direction::get_opposite_direction,
+    },
+    models::{Cube, CubePosition, Direction, GameEvent, GameState, GameStatus, Snake},
 };
 
+use super::ghost_actions::step_ghost;
+
 const SNAKE_MOVE_PERIOD_MULTIPLIER: f64 = 0.05; // higher is faster
 const MOVE_SNAKE: bool = true;
 
 pub fn move_snake_loop(state: &mut GameState) {
+    // network game is moved by ticks from server
+    if state.network.is_some() {
+        return;
+    }
+
     let snake = &state.snake;
 
     let now = web_sys::window().unwrap().performance().unwrap().now();
@@ -18,55 +28,122 @@ pub fn move_snake_loop(state: &mut GameState) {
         && (snake.last_move_time.is_none()
             || (now - snake.last_move_time.unwrap() >= snake.move_period.as_millis() as f64))
     {
-        move_snake(state);
+        move_snakes(state);
+        step_ghost(state);
         state.snake.last_move_time = Some(now);
+        state.replay.ticks_count = state.tick;
     }
+
+    let progress = if state.status == GameStatus::InGame {
+        get_move_progress(
+            &state.snake,
+            now,
+            state.snake.move_period.as_millis() as f64,
+        )
+    } else {
+        1.0
+    };
+    set_move_progress(state, progress);
 }
 
-fn move_snake(state: &mut GameState) {
-    let scene = &mut state.scene;
-    let snake = &mut state.snake;
+pub fn get_move_progress(snake: &Snake, now: f64, period: f64) -> f64 {
+    match snake.last_move_time {
+        Some(last_move_time) => ((now - last_move_time) / period).clamp(0.0, 1.0),
+        None => 1.0,
+    }
+}
+
+pub fn set_move_progress(state: &mut GameState, progress: f64) {
+    // only meshes are drawn between cells, painted sides stay cell-based
+    if state.scene.cube.objects_as_meshes && progress != state.move_progress {
+        state.scene.cube.needs_redraw = true;
+    }
+
+    state.move_progress = progress;
+}
+
+/// Moves all snakes one step ahead. Does not depend on anything but game state,
+/// so it can be used to run the same simulation on different network clients
+pub fn move_snakes(state: &mut GameState) {
+    let GameState {
+        scene,
+        snake,
+        rivals,
+        apples,
+        stones,
+        events,
+        ..
+    } = state;
+
+    // process snakes in the same order on every client
+    let mut snakes: Vec<&mut Snake> = std::iter::once(snake).chain(rivals.iter_mut()).collect();
+    snakes.sort_by_key(|snake| snake.player_id);
+
+    // crashed snakes stay still, so remember parts of all of them
+    for snake in snakes.iter_mut() {
+        snake.prev_parts.clone_from(&snake.parts);
+    }
+
+    for snake in snakes.iter_mut().filter(|snake| !snake.is_crashed) {
+        move_snake(snake, &mut scene.cube);
+        check_for_apples(snake, apples, events);
+    }
+
+    // check crashes after all snakes moved, so result doesn't depend on order
+    let crashes: Vec<bool> = snakes
+        .iter()
+        .map(|snake| snake.is_crashed || check_crash(snake, &snakes, stones))
+        .collect();
+
+    for (snake, is_crashed) in snakes.iter_mut().zip(crashes) {
+        let head = snake.parts.front().unwrap();
+        if is_crashed && !snake.is_crashed && stones.contains(head) {
+            events.push(GameEvent::StoneHit(*head));
+        }
+
+        snake.is_crashed = is_crashed;
+    }
 
+    state.tick += 1;
+}
+
+fn move_snake(snake: &mut Snake, cube: &mut Cube) {
     // instead of moving each snake part one step ahead, move tail to new head
     let head = snake.parts.get(0).unwrap().to_owned();
     let tail = snake.parts.get(snake.parts.len() - 1).unwrap();
 
-    scene.cube.sides.get_mut(&tail.side).unwrap().needs_redraw = true;
+    mark_cube_position_changed(cube, tail);
     snake.parts.pop_back().unwrap();
 
     let (new_head, new_direction) =
-        get_next_cube_position_and_direction(&head, snake.direction, &scene.cube.grid);
+        get_next_cube_position_and_direction(&head, snake.direction, &cube.grid);
 
     snake.parts.push_front(new_head);
     snake.direction = new_direction;
 
-    scene
-        .cube
-        .sides
-        .get_mut(&new_head.side)
-        .unwrap()
-        .needs_redraw = true;
-
-    check_for_apples(state);
-    check_crash(state);
+    // old head is body now, and may look different
+    mark_cube_position_changed(cube, &head);
+    mark_cube_position_changed(cube, &new_head);
 }
 
-pub fn set_snake_direction(state: &mut GameState, direction: Direction) {
-    if state.snake.direction == get_opposite_direction(direction) {
+pub fn set_snake_direction(snake: &mut Snake, direction: Direction) {
+    if snake.direction == get_opposite_direction(direction) {
         return;
     }
 
-    state.snake.direction = direction;
+    snake.direction = direction;
 }
 
-fn check_for_apples(state: &mut GameState) {
-    let snake = &mut state.snake;
-    let apples = &mut state.apples;
-
+fn check_for_apples(
+    snake: &mut Snake,
+    apples: &mut HashSet<CubePosition>,
+    events: &mut Vec<GameEvent>,
+) {
     let head = snake.parts.get(0).unwrap().to_owned();
     let tail = snake.parts.get(snake.parts.len() - 1).unwrap().to_owned();
 
     if apples.remove(&head) {
+        events.push(GameEvent::AppleEaten(head));
         snake.parts.push_back(tail);
         snake.move_period = Duration::from_millis(
             (snake.move_period.as_millis() as f64 * (1.0 - SNAKE_MOVE_PERIOD_MULTIPLIER)) as u64,
@@ -74,22 +151,24 @@ fn check_for_apples(state: &mut GameState) {
     }
 }
 
-fn check_crash(state: &mut GameState) {
-    let snake = &mut state.snake;
-    let stones = &mut state.stones;
-
+fn check_crash(snake: &Snake, snakes: &[&mut Snake], stones: &HashSet<CubePosition>) -> bool {
     let head = snake.parts.get(0).unwrap();
 
     // crash on stone
     if stones.contains(head) {
-        snake.is_crashed = true;
+        return true;
     }
 
     // crash on tail
     for i in 3..snake.parts.len() {
         if head == snake.parts.get(i).unwrap() {
-            snake.is_crashed = true;
-            break;
+            return true;
         }
     }
+
+    // crash on other snakes
+    snakes
+        .iter()
+        .filter(|other| other.player_id != snake.player_id)
+        .any(|other| other.parts.contains(head))
 }
@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+
+use crate::{
+    helpers::cube::mark_cube_position_changed,
+    models::{CameraMode, CubePosition, GameState},
+    network::snapshot::{apply_snapshot_to_state, GameSnapshot},
+};
+
+pub fn start_spectating(state: &mut GameState) {
+    state.is_spectator = true;
+    state.scene.cube.camera_mode = CameraMode::ManualControl;
+}
+
+pub fn apply_snapshot(state: &mut GameState, snapshot: &GameSnapshot) -> Result<(), String> {
+    let prev_positions = get_object_positions(state);
+    let prev_grid = state.scene.cube.grid;
+    apply_snapshot_to_state(snapshot, state)?;
+
+    let positions = get_object_positions(state);
+    let cube = &mut state.scene.cube;
+
+    // grid lines are on every side
+    if cube.grid != prev_grid {
+        cube.needs_redraw = true;
+        cube.sides
+            .values_mut()
+            .for_each(|side| side.needs_redraw = true);
+        return Ok(());
+    }
+
+    // otherwise only redraw sides where something has changed
+    for pos in prev_positions.symmetric_difference(&positions) {
+        mark_cube_position_changed(cube, pos);
+    }
+
+    Ok(())
+}
+
+fn get_object_positions(state: &GameState) -> HashSet<CubePosition> {
+    std::iter::once(&state.snake)
+        .chain(state.rivals.iter())
+        .flat_map(|snake| snake.parts.iter())
+        .chain(state.apples.iter())
+        .chain(state.stones.iter())
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{actions::game_actions::init_game_state, network::snapshot::create_snapshot};
+
+    #[test]
+    fn grid_change_redraws_all_sides() {
+        let mut state = GameState::default();
+        init_game_state(&mut state, 3);
+
+        let mut snapshot = create_snapshot(&state);
+        snapshot.grid_size += 2;
+
+        state
+            .scene
+            .cube
+            .sides
+            .values_mut()
+            .for_each(|side| side.needs_redraw = false);
+
+        apply_snapshot(&mut state, &snapshot).unwrap();
+        assert!(state
+            .scene
+            .cube
+            .sides
+            .values()
+            .all(|side| side.needs_redraw));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{
+    helpers::cube::{get_cube_side_axes, get_side_point},
+    models::{CubeShape, CubeSideType, EdgeShape, Grid},
+};
+
+// each vertex is side index, position (x, y, z) and normal (x, y, z)
+pub const CUBE_VERTEX_SIZE: usize = 7;
+
+// the most of a cell edge can take, so cell centers stay on flat part
+const MAX_EDGE_CELL_SHARE: f64 = 0.45;
+
+const SIDE_TYPES: [CubeSideType; 6] = [
+    CubeSideType::Front,
+    CubeSideType::Back,
+    CubeSideType::Up,
+    CubeSideType::Down,
+    CubeSideType::Left,
+    CubeSideType::Right,
+];
+
+/// Cube vertices, their texture coordinates and indices of triangles
+pub struct CubeMesh {
+    pub vertex_coords: Vec<f32>,
+    pub texture_coords: Vec<f32>,
+    pub indices: Vec<u16>,
+}
+
+/// Builds cube from grid of points on each side, which are then moved onto
+/// shaped edges. Texture coordinates come from the same grid, so side image
+/// wraps over edges without breaks. Sides don't share vertices, since each
+/// side has its own texture
+pub fn create_cube_mesh(shape: &CubeShape, grid: &Grid) -> CubeMesh {
+    let shape = &fit_shape_to_grid(shape, grid);
+    let steps = get_side_steps(shape);
+    let row_size = steps.len();
+
+    assert!(
+        SIDE_TYPES.len() * row_size * row_size <= u16::MAX as usize + 1,
+        "cube mesh is too detailed for 16 bit indices"
+    );
+
+    let mut mesh = CubeMesh {
+        vertex_coords: vec![],
+        texture_coords: vec![],
+        indices: vec![],
+    };
+
+    for side in SIDE_TYPES {
+        let [_, _, normal] = get_cube_side_axes(side);
+        let first = (mesh.vertex_coords.len() / CUBE_VERTEX_SIZE) as u16;
+
+        for &vert_ratio in &steps {
+            for &horiz_ratio in &steps {
+                let point = get_side_point(side, horiz_ratio, vert_ratio);
+                let (position, normal) =
+                    shape_point([point.x, point.y, point.z], normal.map(|c| c as f64), shape);
+
+                mesh.vertex_coords.push(side as i32 as f32);
+                mesh.vertex_coords.extend(position.map(|c| c as f32));
+                mesh.vertex_coords.extend(normal.map(|c| c as f32));
+
+                // rows go up from the bottom of side image
+                mesh.texture_coords.push(horiz_ratio as f32);
+                mesh.texture_coords.push(1.0 - vert_ratio as f32);
+            }
+        }
+
+        // columns and rows are side axes, and they make counter-clockwise
+        // triangles when seen from outside
+        for row in 0..row_size - 1 {
+            for col in 0..row_size - 1 {
+                let index = |row: usize, col: usize| first + (row * row_size + col) as u16;
+                let (a, b) = (index(row, col), index(row, col + 1));
+                let (c, d) = (index(row + 1, col), index(row + 1, col + 1));
+
+                mesh.indices.extend([a, b, c, c, b, d]);
+            }
+        }
+    }
+
+    mesh
+}
+
+// keeps edges within outer cells, and side and edge at least a single quad.
+// edge without size is sharp, since there is nothing to shape
+fn fit_shape_to_grid(shape: &CubeShape, grid: &Grid) -> CubeShape {
+    let cells_count = grid.rows_count.max(grid.cols_count).max(1);
+    let edge_size = shape
+        .edge_size
+        .min(MAX_EDGE_CELL_SHARE / cells_count as f64)
+        .max(0.0);
+
+    CubeShape {
+        subdivisions: shape.subdivisions.max(1),
+        edge_shape: if edge_size > 0.0 {
+            shape.edge_shape
+        } else {
+            EdgeShape::Sharp
+        },
+        edge_size,
+        edge_segments: shape.edge_segments.max(1),
+    }
+}
+
+// ratios of grid lines along side. edges get their own lines, so flat part
+// stays flat
+fn get_side_steps(shape: &CubeShape) -> Vec<f64> {
+    let (edge_size, edge_segments) = match shape.edge_shape {
+        EdgeShape::Sharp => (0.0, 0),
+        EdgeShape::Rounded | EdgeShape::Beveled => (shape.edge_size, shape.edge_segments),
+    };
+
+    let edge_step = |i: u32| edge_size * i as f64 / edge_segments as f64;
+    let flat_size = 1.0 - 2.0 * edge_size;
+
+    let mut steps = vec![];
+    steps.extend((0..edge_segments).map(edge_step));
+    steps.extend(
+        (0..=shape.subdivisions)
+            .map(|i| edge_size + flat_size * i as f64 / shape.subdivisions as f64),
+    );
+    steps.extend((1..=edge_segments).map(|i| 1.0 - edge_size + edge_step(i)));
+
+    steps
+}
+
+// moves point of sharp cube onto shaped one and gets its normal. every point
+// is pushed out from the inner box, which is smaller than cube by edge size, so
+// neighbour sides meet exactly in the middle of an edge
+fn shape_point(point: [f64; 3], normal: [f64; 3], shape: &CubeShape) -> ([f64; 3], [f64; 3]) {
+    let edge_size = shape.edge_size;
+    let inner = 0.5 - edge_size;
+
+    let core = point.map(|c| c.clamp(-inner, inner));
+    let offset: [f64; 3] = std::array::from_fn(|i| point[i] - core[i]);
+
+    match shape.edge_shape {
+        EdgeShape::Sharp => (point, normal),
+        EdgeShape::Rounded => {
+            let length = offset.iter().map(|c| c * c).sum::<f64>().sqrt();
+            let normal = offset.map(|c| c / length);
+
+            (
+                std::array::from_fn(|i| core[i] + normal[i] * edge_size),
+                normal,
+            )
+        }
+        EdgeShape::Beveled => {
+            // cut is a plane, where sum of offsets along axes is edge size
+            let length = offset.iter().map(|c| c.abs()).sum::<f64>();
+            let direction = offset.map(|c| if c.abs() > 1e-9 { c.signum() } else { 0.0 });
+            let direction_length = direction.iter().map(|c| c * c).sum::<f64>().sqrt();
+
+            (
+                std::array::from_fn(|i| core[i] + offset[i] * edge_size / length),
+                direction.map(|c| c / direction_length),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helpers::graphics_math::{cross, dot, subtract_vectors, Vec3};
+
+    use super::*;
+
+    const GRID: Grid = Grid {
+        rows_count: 10,
+        cols_count: 10,
+    };
+
+    fn get_vertex(mesh: &CubeMesh, index: u16) -> (Vec3, Vec3) {
+        let coords = &mesh.vertex_coords[index as usize * CUBE_VERTEX_SIZE..];
+        (
+            [coords[1], coords[2], coords[3]],
+            [coords[4], coords[5], coords[6]],
+        )
+    }
+
+    #[test]
+    fn texture_coords_match_cell_positions() {
+        let shape = CubeShape {
+            edge_shape: EdgeShape::Sharp,
+            ..CubeShape::default()
+        };
+        let mesh = create_cube_mesh(&shape, &GRID);
+
+        for (i, coords) in mesh.vertex_coords.chunks(CUBE_VERTEX_SIZE).enumerate() {
+            let side = SIDE_TYPES[coords[0] as usize];
+            let (u, v) = (mesh.texture_coords[i * 2], mesh.texture_coords[i * 2 + 1]);
+
+            let point = get_side_point(side, u as f64, 1.0 - v as f64);
+            let expected = [point.x as f32, point.y as f32, point.z as f32];
+            let difference = subtract_vectors([coords[1], coords[2], coords[3]], expected);
+            assert!(difference.iter().all(|c| c.abs() < 1e-6));
+        }
+    }
+
+    #[test]
+    fn triangles_face_outward() {
+        for edge_shape in [EdgeShape::Sharp, EdgeShape::Rounded, EdgeShape::Beveled] {
+            let shape = CubeShape {
+                edge_shape,
+                ..CubeShape::default()
+            };
+            let mesh = create_cube_mesh(&shape, &GRID);
+
+            for triangle in mesh.indices.chunks(3) {
+                let (a, normal) = get_vertex(&mesh, triangle[0]);
+                let (b, _) = get_vertex(&mesh, triangle[1]);
+                let (c, _) = get_vertex(&mesh, triangle[2]);
+
+                let face_normal = cross(subtract_vectors(b, a), subtract_vectors(c, a));
+                assert!(dot(face_normal, normal) > 0.0, "{edge_shape:?}");
+                assert!(dot(normal, a) > 0.0, "{edge_shape:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn sides_meet_on_edges() {
+        let mesh = create_cube_mesh(&CubeShape::default(), &GRID);
+        let vertices_count = mesh.vertex_coords.len() / CUBE_VERTEX_SIZE;
+        let positions: Vec<Vec3> = (0..vertices_count)
+            .map(|i| get_vertex(&mesh, i as u16).0)
+            .collect();
+
+        // every vertex on the middle of an edge has a twin on another side
+        for (i, position) in positions.iter().enumerate() {
+            let is_on_edge = position.iter().filter(|c| c.abs() > 0.49).count() >= 2;
+            if !is_on_edge {
+                continue;
+            }
+
+            let has_twin = positions.iter().enumerate().any(|(j, other)| {
+                j / (vertices_count / 6) != i / (vertices_count / 6)
+                    && subtract_vectors(*position, *other)
+                        .iter()
+                        .all(|c| c.abs() < 1e-5)
+            });
+            assert!(has_twin, "{position:?}");
+        }
+    }
+
+    #[test]
+    fn edges_stay_within_outer_cells() {
+        let grid = Grid {
+            rows_count: 64,
+            cols_count: 64,
+        };
+        let shape = fit_shape_to_grid(&CubeShape::default(), &grid);
+        let steps = get_side_steps(&shape);
+
+        // the first flat step is where edge ends
+        let edge_end = steps[shape.edge_segments as usize];
+        assert!(edge_end < 0.5 / grid.cols_count as f64);
+    }
+
+    #[test]
+    fn degenerate_edges_are_built_as_proper_ones() {
+        let mesh = |edge_shape, edge_size, edge_segments| {
+            let shape = CubeShape {
+                edge_shape,
+                edge_size,
+                edge_segments,
+                ..CubeShape::default()
+            };
+            create_cube_mesh(&shape, &GRID).vertex_coords
+        };
+
+        // edge without segments would leave a hole along it
+        assert_eq!(
+            mesh(EdgeShape::Rounded, 0.03, 0),
+            mesh(EdgeShape::Rounded, 0.03, 1)
+        );
+
+        // rounded edge without size has no normal
+        let sharp = mesh(EdgeShape::Sharp, 0.0, 3);
+        assert!(sharp.iter().all(|c| c.is_finite()));
+        assert_eq!(mesh(EdgeShape::Rounded, 0.0, 3), sharp);
+        assert_eq!(mesh(EdgeShape::Beveled, 0.0, 3), sharp);
+    }
+
+    #[test]
+    #[should_panic(expected = "too detailed")]
+    fn too_detailed_mesh_panics() {
+        let shape = CubeShape {
+            subdivisions: 200,
+            ..CubeShape::default()
+        };
+        create_cube_mesh(&shape, &GRID);
+    }
+
+    #[test]
+    fn side_without_subdivisions_is_single_quad() {
+        let shape = CubeShape {
+            subdivisions: 0,
+            ..CubeShape::default()
+        };
+        let mesh = create_cube_mesh(&shape, &GRID);
+
+        assert!(!mesh.indices.is_empty());
+        assert!(mesh.vertex_coords.iter().all(|c| c.is_finite()));
+    }
+}
@@ -1,50 +0,0 @@
-#[rustfmt::skip]
-pub const CUBE_TEXTURE_COORDS: [f32; 72] = [
-    // front side
-    0.0, 1.0,
-    1.0, 1.0,
-    0.0, 0.0,
-    0.0, 0.0,
-    1.0, 1.0,
-    1.0, 0.0,
-
-    // back side
-    1.0, 1.0,
-    1.0, 0.0,
-    0.0, 1.0,
-    1.0, 0.0,
-    0.0, 0.0,
-    0.0, 1.0,
-
-    // up side
-    0.0, 0.0,
-    0.0, 1.0,
-    1.0, 0.0,
-    0.0, 1.0,
-    1.0, 1.0,
-    1.0, 0.0,
-
-    // down side
-    0.0, 1.0,
-    1.0, 1.0,
-    0.0, 0.0,
-    0.0, 0.0,
-    1.0, 1.0,
-    1.0, 0.0,
-
-    // left side
-    0.0, 1.0,
-    1.0, 1.0,
-    0.0, 0.0,
-    1.0, 1.0,
-    1.0, 0.0,
-    0.0, 0.0,
-
-    // right side
-    1.0, 1.0,
-    1.0, 0.0,
-    0.0, 1.0,
-    0.0, 1.0,
-    1.0, 0.0,
-    0.0, 0.0,
-];
@@ -1,7 +1,5 @@
+mod cube_mesh;
 mod cube_side_coords_range;
-mod cube_texture_coords;
-mod cube_vertex_coords;
 
+pub use cube_mesh::*;
 pub use cube_side_coords_range::*;
-pub use cube_texture_coords::*;
-pub use cube_vertex_coords::*;
@@ -1,18 +1,34 @@
 pub mod geometry;
-
-use wasm_bindgen::JsCast;
+mod webgl1;
+mod webgl2;
 
 use crate::{
     helpers::{
-        graphics_math::{self as gmath, Matrix4, Vec3},
+        gl_context::{create_gl_context, GlContext},
+        graphics_math::{self as gmath, Vec3},
         webgl::{get_attribute_location, get_uniform_location, init_program, init_shader},
     },
-    models::{Cube, Degrees, GameState},
+    models::{
+        Cube, CubeSide, CubeView, Degrees, GameState, LightUniformLocations, QualityPreset,
+        Quaternion, Rect,
+    },
+};
+
+use super::{
+    objects_drawer::draw_objects,
+    particles_drawer::draw_particles,
+    post_drawer::{begin_post_processing, end_post_processing},
 };
 
 const FIELD_OF_VIEW: Degrees = Degrees(60.0);
+
+// constants of EXT_texture_filter_anisotropic extension
+const TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FF;
 const VERTEX_SHADER_SRC: &str = include_str!("shaders/vertex.glsl");
 const FRAGMENT_SHADER_SRC: &str = include_str!("shaders/fragment.glsl");
+const VERTEX_SHADER_WEBGL2_SRC: &str = include_str!("shaders/vertex_webgl2.glsl");
+const FRAGMENT_SHADER_WEBGL2_SRC: &str = include_str!("shaders/fragment_webgl2.glsl");
 
 pub fn init_cube_drawer(state: &mut GameState) {
     let program;
@@ -22,24 +38,30 @@ pub fn init_cube_drawer(state: &mut GameState) {
     let cube = &mut scene.cube;
     let canvas = scene.canvas.as_ref().unwrap();
 
-    ctx = canvas
-        .get_context("webgl")
-        .unwrap()
-        .expect("failed to get webgl context")
-        .dyn_into::<web_sys::WebGlRenderingContext>()
-        .unwrap();
+    let preset = scene.quality.preset();
+    ctx = create_gl_context(canvas, is_webgl2_allowed(), preset.antialias)
+        .expect("failed to get webgl context");
+
+    cube.max_anisotropy = get_max_anisotropy(&ctx);
+    set_texture_quality(cube, preset);
+
+    let (vertex_shader_src, fragment_shader_src) = if ctx.is_webgl2() {
+        (VERTEX_SHADER_WEBGL2_SRC, FRAGMENT_SHADER_WEBGL2_SRC)
+    } else {
+        (VERTEX_SHADER_SRC, FRAGMENT_SHADER_SRC)
+    };
 
     // compile GLSL shaders for cube
     let vertex_shader = init_shader(
         &ctx,
         web_sys::WebGlRenderingContext::VERTEX_SHADER,
-        VERTEX_SHADER_SRC,
+        vertex_shader_src,
     )
     .expect("failed to init vertex shader");
     let fragment_shader = init_shader(
         &ctx,
         web_sys::WebGlRenderingContext::FRAGMENT_SHADER,
-        FRAGMENT_SHADER_SRC,
+        fragment_shader_src,
     )
     .expect("failed to init fragment shader");
 
@@ -48,151 +70,270 @@ pub fn init_cube_drawer(state: &mut GameState) {
     ctx.use_program(Some(&program));
 
     // lookup locations for attributes/uniforms
-    let cube_vertex_coord_attr_location =
+    cube.vertex_coord_attr_location =
         get_attribute_location(&ctx, &program, "a_cube_vertex_coord").unwrap();
-    let cube_vertex_side_attr_location =
+    cube.vertex_side_attr_location =
         get_attribute_location(&ctx, &program, "a_cube_vertex_side").unwrap();
-    let cube_texture_coord_attr_location =
+    cube.vertex_normal_attr_location =
+        get_attribute_location(&ctx, &program, "a_cube_vertex_normal").unwrap();
+    cube.texture_coord_attr_location =
         get_attribute_location(&ctx, &program, "a_cube_texture_coord").unwrap();
     cube.matrix_uniform_location = Some(get_uniform_location(&ctx, &program, "u_matrix").unwrap());
+    cube.world_matrix_uniform_location =
+        Some(get_uniform_location(&ctx, &program, "u_world_matrix").unwrap());
+    cube.normal_matrix_uniform_location =
+        Some(get_uniform_location(&ctx, &program, "u_normal_matrix").unwrap());
+    cube.light_uniform_locations = LightUniformLocations {
+        direction: Some(get_uniform_location(&ctx, &program, "u_light_direction").unwrap()),
+        ambient: Some(get_uniform_location(&ctx, &program, "u_ambient_light").unwrap()),
+        specular: Some(get_uniform_location(&ctx, &program, "u_specular_light").unwrap()),
+        shininess: Some(get_uniform_location(&ctx, &program, "u_shininess").unwrap()),
+        camera_pos: Some(get_uniform_location(&ctx, &program, "u_camera_pos").unwrap()),
+    };
+
+    // buffers for vertex coordinates, texture coordinates and indices of
+    // triangles. mesh is passed to them once it's known which grid it is for
+    cube.vertex_coords_buffer = ctx.create_buffer();
+    cube.texture_coords_buffer = ctx.create_buffer();
+    cube.index_buffer = ctx.create_buffer();
+    cube.mesh_grid = None;
+
+    // create textures for cube sides and pass texture data for the first time
+    // (update later in draw loop)
+    match &ctx {
+        GlContext::WebGl1 { .. } => webgl1::init_side_textures(&ctx, &program, cube),
+        GlContext::WebGl2(ctx2) => {
+            webgl2::init_side_texture_array(&ctx, ctx2, &program, cube);
+            webgl2::init_vertex_array(&ctx, ctx2, cube);
+        }
+    }
+
+    state.scene.ctx = Some(ctx);
+    state.scene.cube.program = Some(program);
+}
+
+// passes mesh to existing buffers, so vertex array keeps pointing to them
+fn upload_cube_mesh(ctx: &GlContext, cube: &mut Cube) {
+    let mesh = geometry::create_cube_mesh(&cube.shape, &cube.grid);
 
-    // pass buffer with vertex coordinates
-    let cube_vertex_coords_buffer = ctx.create_buffer();
     ctx.bind_buffer(
         web_sys::WebGlRenderingContext::ARRAY_BUFFER,
-        cube_vertex_coords_buffer.as_ref(),
+        cube.vertex_coords_buffer.as_ref(),
     );
     ctx.buffer_data_with_array_buffer_view(
         web_sys::WebGlRenderingContext::ARRAY_BUFFER,
-        &js_sys::Float32Array::from(&geometry::CUBE_VERTEX_COORDS[..]),
+        &js_sys::Float32Array::from(&mesh.vertex_coords[..]),
         web_sys::WebGlRenderingContext::STATIC_DRAW,
     );
 
-    // pass buffer with texture coordinates
-    let cube_texture_coords_buffer = ctx.create_buffer();
     ctx.bind_buffer(
         web_sys::WebGlRenderingContext::ARRAY_BUFFER,
-        cube_texture_coords_buffer.as_ref(),
+        cube.texture_coords_buffer.as_ref(),
     );
     ctx.buffer_data_with_array_buffer_view(
         web_sys::WebGlRenderingContext::ARRAY_BUFFER,
-        &js_sys::Float32Array::from(&geometry::CUBE_TEXTURE_COORDS[..]),
+        &js_sys::Float32Array::from(&mesh.texture_coords[..]),
         web_sys::WebGlRenderingContext::STATIC_DRAW,
     );
 
+    ctx.bind_buffer(
+        web_sys::WebGlRenderingContext::ELEMENT_ARRAY_BUFFER,
+        cube.index_buffer.as_ref(),
+    );
+    ctx.buffer_data_with_array_buffer_view(
+        web_sys::WebGlRenderingContext::ELEMENT_ARRAY_BUFFER,
+        &js_sys::Uint16Array::from(&mesh.indices[..]),
+        web_sys::WebGlRenderingContext::STATIC_DRAW,
+    );
+
+    cube.indices_count = mesh.indices.len() as i32;
+    cube.mesh_grid = Some(cube.grid);
+    cube.needs_redraw = true;
+}
+
+/// Recreates side textures with size of side canvases and filtering of current
+/// quality preset. Side canvases must be resized beforehand
+pub fn apply_cube_quality(state: &mut GameState) {
+    let scene = &mut state.scene;
+    let ctx = scene.ctx.as_ref().unwrap();
+    let cube = &mut scene.cube;
+
+    set_texture_quality(cube, scene.quality.preset());
+
+    ctx.use_program(cube.program.as_ref());
+    match ctx {
+        GlContext::WebGl1 { .. } => webgl1::allocate_side_textures(ctx, cube),
+        GlContext::WebGl2(ctx2) => webgl2::allocate_side_texture_array(ctx, ctx2, cube),
+    }
+
+    cube.needs_redraw = true;
+}
+
+fn set_texture_quality(cube: &mut Cube, preset: &QualityPreset) {
+    cube.mipmaps = preset.mipmaps;
+    cube.anisotropy = cube.max_anisotropy.map(|max| preset.anisotropy.min(max));
+}
+
+fn get_max_anisotropy(ctx: &GlContext) -> Option<f32> {
+    ctx.get_extension("EXT_texture_filter_anisotropic").ok()??;
+
+    let max = ctx.get_parameter(MAX_TEXTURE_MAX_ANISOTROPY_EXT).ok()?;
+    max.as_f64().map(|max| max as f32)
+}
+
+// mipmaps and anisotropic filtering keep dense grid from shimmering, when side
+// is seen at an angle. texture must be bound to the target
+fn set_side_texture_filter(ctx: &GlContext, target: u32, mipmaps: bool, anisotropy: Option<f32>) {
+    let min_filter = if mipmaps {
+        web_sys::WebGlRenderingContext::LINEAR_MIPMAP_LINEAR
+    } else {
+        web_sys::WebGlRenderingContext::LINEAR
+    };
+    ctx.tex_parameteri(
+        target,
+        web_sys::WebGlRenderingContext::TEXTURE_MIN_FILTER,
+        min_filter as i32,
+    );
+
+    if let Some(anisotropy) = anisotropy {
+        ctx.tex_parameterf(target, TEXTURE_MAX_ANISOTROPY_EXT, anisotropy);
+    }
+}
+
+// webgl 2 can be turned off with url param to check webgl 1 fallback, eg.
+// http://localhost:8080/?webgl=1
+fn is_webgl2_allowed() -> bool {
+    let search = web_sys::window().unwrap().location().search().unwrap();
+    let params = web_sys::UrlSearchParams::new_with_str(&search).unwrap();
+
+    params.get("webgl").as_deref() != Some("1")
+}
+
+// in webgl 1 attributes are defined before each draw, since objects drawer uses
+// its own attributes for the same context. in webgl 2 they are defined once
+// and saved to vertex array object
+fn bind_cube_attributes(ctx: &GlContext, cube: &Cube) {
     // define how to extract coordinates from vertex buffer
-    ctx.enable_vertex_attrib_array(cube_vertex_coord_attr_location);
+    ctx.enable_vertex_attrib_array(cube.vertex_coord_attr_location);
     ctx.bind_buffer(
         web_sys::WebGlRenderingContext::ARRAY_BUFFER,
-        cube_vertex_coords_buffer.as_ref(),
+        cube.vertex_coords_buffer.as_ref(),
     );
     ctx.vertex_attrib_pointer_with_i32(
-        cube_vertex_coord_attr_location,
+        cube.vertex_coord_attr_location,
         3,                                     // 3 components per iteration
         web_sys::WebGlRenderingContext::FLOAT, // the data is 32bit floats
         false,                                 // don't normalize the data
-        16, // (bytes) each vertex consists of 4 x 4-byte floats (side, x, y, z)
+        28, // (bytes) each vertex consists of 7 x 4-byte floats (side, x, y, z, nx, ny, nz)
         4,  // skip side float
     );
 
     // define how to extract cube side index from vertex buffer
-    ctx.enable_vertex_attrib_array(cube_vertex_side_attr_location);
-    ctx.bind_buffer(
-        web_sys::WebGlRenderingContext::ARRAY_BUFFER,
-        cube_vertex_coords_buffer.as_ref(),
-    );
+    ctx.enable_vertex_attrib_array(cube.vertex_side_attr_location);
     ctx.vertex_attrib_pointer_with_i32(
-        cube_vertex_side_attr_location,
+        cube.vertex_side_attr_location,
         1,
         web_sys::WebGlRenderingContext::FLOAT,
         false,
-        16,
+        28,
         0,
     );
 
+    // define how to extract normal from vertex buffer
+    ctx.enable_vertex_attrib_array(cube.vertex_normal_attr_location);
+    ctx.vertex_attrib_pointer_with_i32(
+        cube.vertex_normal_attr_location,
+        3,
+        web_sys::WebGlRenderingContext::FLOAT,
+        false,
+        28,
+        16, // skip side and position floats
+    );
+
+    // triangles are drawn by indices. in webgl 2 index buffer is also saved to
+    // vertex array object
+    ctx.bind_buffer(
+        web_sys::WebGlRenderingContext::ELEMENT_ARRAY_BUFFER,
+        cube.index_buffer.as_ref(),
+    );
+
     // define how to extract coordinates from texture coordinates buffer
-    ctx.enable_vertex_attrib_array(cube_texture_coord_attr_location);
+    ctx.enable_vertex_attrib_array(cube.texture_coord_attr_location);
     ctx.bind_buffer(
         web_sys::WebGlRenderingContext::ARRAY_BUFFER,
-        cube_texture_coords_buffer.as_ref(),
+        cube.texture_coords_buffer.as_ref(),
     );
     ctx.vertex_attrib_pointer_with_i32(
-        cube_texture_coord_attr_location,
+        cube.texture_coord_attr_location,
         2,
         web_sys::WebGlRenderingContext::FLOAT,
         false,
         0,
         0,
     );
+}
 
-    // create textures for cube sides
-    let mut cube_textures: std::vec::Vec<web_sys::WebGlTexture> = Vec::new();
-    cube_textures.reserve(cube.sides.len());
-    for side_type in cube.sides.keys() {
-        let texture = ctx.create_texture().unwrap();
-        cube_textures.push(texture);
+fn should_redraw_cube(cube: &Cube) -> bool {
+    cube.needs_redraw
+        || cube
+            .sides
+            .iter()
+            .any(|(_, side)| side.needs_update_on_cube || !side.dirty_rects.is_empty())
+}
 
-        // bind uniform with texture unit
-        let side_type_idx = *side_type as i32;
-        let uniform_name = format!("u_cube_texture_side_{side_type_idx}");
-        let cube_texture_side_uniform_location =
-            get_uniform_location(&ctx, &program, &uniform_name).unwrap();
+// pixels of repainted region of side canvas, to upload only them to texture
+fn get_side_image_data(side: &CubeSide, rect: &Rect) -> web_sys::ImageData {
+    side.ctx
+        .as_ref()
+        .unwrap()
+        .get_image_data(
+            rect.x as f64,
+            rect.y as f64,
+            rect.width as f64,
+            rect.height as f64,
+        )
+        .unwrap()
+}
 
-        ctx.uniform1i(Some(&cube_texture_side_uniform_location), side_type_idx);
-    }
+/// Computes transformations of the cube with given rotation, for viewport with
+/// given aspect (width / height)
+pub fn get_cube_view(rotation: &Quaternion, aspect: f32) -> CubeView {
+    // compute the projection matrix
+    let projection_matrix = gmath::perspective(FIELD_OF_VIEW.into(), aspect, 1.0, 2000.0);
 
-    cube.textures = cube_textures;
-
-    // pass texture data for the first time (update later in draw loop)
-    for (side_type, side) in &mut cube.sides {
-        let canvas = side.canvas.as_ref().unwrap();
-
-        let side_type_idx = *side_type as u32;
-        ctx.active_texture(web_sys::WebGlRenderingContext::TEXTURE0 + side_type_idx);
-        ctx.bind_texture(
-            web_sys::WebGlRenderingContext::TEXTURE_2D,
-            cube.textures.get(side_type_idx as usize),
-        );
-
-        ctx.tex_image_2d_with_u32_and_u32_and_canvas(
-            web_sys::WebGlRenderingContext::TEXTURE_2D,
-            0,
-            web_sys::WebGlRenderingContext::RGBA as i32,
-            web_sys::WebGlRenderingContext::RGBA,
-            web_sys::WebGlRenderingContext::UNSIGNED_BYTE,
-            canvas,
-        )
-        .unwrap();
-
-        ctx.tex_parameteri(
-            web_sys::WebGlRenderingContext::TEXTURE_2D,
-            web_sys::WebGlRenderingContext::TEXTURE_WRAP_S,
-            web_sys::WebGlRenderingContext::CLAMP_TO_EDGE as i32,
-        );
-        ctx.tex_parameteri(
-            web_sys::WebGlRenderingContext::TEXTURE_2D,
-            web_sys::WebGlRenderingContext::TEXTURE_WRAP_T,
-            web_sys::WebGlRenderingContext::CLAMP_TO_EDGE as i32,
-        );
-        ctx.tex_parameteri(
-            web_sys::WebGlRenderingContext::TEXTURE_2D,
-            web_sys::WebGlRenderingContext::TEXTURE_MIN_FILTER,
-            web_sys::WebGlRenderingContext::LINEAR as i32,
-        );
-
-        side.needs_update_on_cube = false;
-    }
+    let camera_pos: Vec3 = [0.0, 0.0, 2.0];
+    let up: Vec3 = [0.0, 1.0, 0.0];
+    let target: Vec3 = [0.0, 0.0, 0.0];
 
-    state.scene.ctx = Some(ctx);
-    state.scene.cube.program = Some(program);
-}
+    let camera_matrix = gmath::look_at(camera_pos, target, up);
+    let view_matrix = gmath::inverse(camera_matrix);
+    let view_projection_matrix = gmath::multiply(projection_matrix, view_matrix);
 
-fn should_redraw_cube(cube: &Cube) -> bool {
-    cube.needs_redraw || cube.sides.iter().any(|(_, side)| side.needs_update_on_cube)
+    let rotation_matrix = rotation.to_matrix();
+    let matrix = gmath::multiply(view_projection_matrix, rotation_matrix);
+
+    // same rotation without camera and projection, for lighting in world space
+    let world_matrix = rotation_matrix;
+
+    CubeView {
+        matrix,
+        world_matrix,
+        camera_pos,
+    }
 }
 
 pub fn draw_cube_loop(state: &mut GameState) {
-    let mut matrix;
+    // grid changes with rules of a new game, and edges depend on cell size
+    let scene = &mut state.scene;
+    if scene.cube.mesh_grid != Some(scene.cube.grid) {
+        upload_cube_mesh(scene.ctx.as_ref().unwrap(), &mut scene.cube);
+    }
+
+    if !should_redraw_cube(&state.scene.cube) {
+        return;
+    }
+
+    let is_post_processed = begin_post_processing(state);
 
     let scene = &state.scene;
 
@@ -200,10 +341,6 @@ pub fn draw_cube_loop(state: &mut GameState) {
     let ctx = scene.ctx.as_ref().unwrap();
     let cube = &scene.cube;
 
-    if !should_redraw_cube(cube) {
-        return;
-    }
-
     // define how to convert from clip space to canvas pixels
     ctx.viewport(0, 0, canvas.width() as i32, canvas.height() as i32);
 
@@ -216,68 +353,86 @@ pub fn draw_cube_loop(state: &mut GameState) {
             | web_sys::WebGlRenderingContext::DEPTH_BUFFER_BIT,
     );
 
-    // compute the projection matrix
     let aspect: f32 = canvas.client_width() as f32 / canvas.client_height() as f32;
-    let projection_matrix = gmath::perspective(FIELD_OF_VIEW.into(), aspect, 1.0, 2000.0);
+    let view = get_cube_view(&cube.current_rotation, aspect);
+    let matrix = view.matrix;
 
-    let camera_pos: Vec3 = [0.0, 0.0, 2.0];
-    let up: Vec3 = [0.0, 1.0, 0.0];
-    let target: Vec3 = [0.0, 0.0, 0.0];
+    draw_cube(state, &view);
 
-    let camera_matrix = gmath::look_at(camera_pos, target, up);
-    let view_matrix = gmath::inverse(camera_matrix);
-    let view_projection_matrix = gmath::multiply(projection_matrix, view_matrix);
+    if state.scene.cube.objects_as_meshes {
+        draw_objects(state, matrix);
+    }
 
-    matrix = gmath::x_rotate(view_projection_matrix, cube.current_rotation.x.into());
-    matrix = gmath::y_rotate(matrix, cube.current_rotation.y.into());
+    draw_particles(state, matrix);
 
-    draw_cube(state, matrix);
+    if is_post_processed {
+        end_post_processing(state);
+    }
 }
 
-fn draw_cube(state: &mut GameState, matrix: Matrix4) {
+fn draw_cube(state: &mut GameState, view: &CubeView) {
     let scene = &mut state.scene;
 
-    let ctx = scene.ctx.as_mut().unwrap();
+    let ctx = scene.ctx.as_ref().unwrap();
     let cube = &mut scene.cube;
+    let stats = &mut scene.stats;
 
     ctx.use_program(cube.program.as_ref());
 
     // update texture data if needed
-    for (side_type, side) in &mut cube.sides {
-        if side.needs_update_on_cube {
-            let side_type_idx = *side_type as u32;
-            ctx.active_texture(web_sys::WebGlRenderingContext::TEXTURE0 + side_type_idx);
-            ctx.bind_texture(
-                web_sys::WebGlRenderingContext::TEXTURE_2D,
-                cube.textures.get(side_type_idx as usize),
-            );
-            ctx.tex_sub_image_2d_with_u32_and_u32_and_canvas(
-                web_sys::WebGlRenderingContext::TEXTURE_2D,
-                0,
-                0,
-                0,
-                web_sys::WebGlRenderingContext::RGBA,
-                web_sys::WebGlRenderingContext::UNSIGNED_BYTE,
-                side.canvas.as_ref().unwrap(),
-            )
-            .unwrap();
-            side.needs_update_on_cube = false;
+    match ctx {
+        GlContext::WebGl1 { .. } => {
+            bind_cube_attributes(ctx, cube);
+            webgl1::update_side_textures(ctx, cube, stats);
+        }
+        GlContext::WebGl2(ctx) => {
+            ctx.bind_vertex_array(cube.vertex_array.as_ref());
+            webgl2::update_side_texture_array(ctx, cube, stats);
         }
     }
 
     // pass transformation matrix
-    ctx.uniform_matrix4fv_with_f32_array(cube.matrix_uniform_location.as_ref(), false, &matrix);
+    ctx.uniform_matrix4fv_with_f32_array(
+        cube.matrix_uniform_location.as_ref(),
+        false,
+        &view.matrix,
+    );
+    ctx.uniform_matrix4fv_with_f32_array(
+        cube.world_matrix_uniform_location.as_ref(),
+        false,
+        &view.world_matrix,
+    );
+    ctx.uniform_matrix4fv_with_f32_array(
+        cube.normal_matrix_uniform_location.as_ref(),
+        false,
+        &gmath::normal_matrix(view.world_matrix),
+    );
 
-    // draw the geometry
-    const CUBE_VERTICES_COUNT: i32 = 6  // cube sides
-                                   * 2  // triangles per cube side
-                                   * 3; // vertices per triangle
+    // pass light
+    let light = &cube.light;
+    let locations = &cube.light_uniform_locations;
+    ctx.uniform3fv_with_f32_array(
+        locations.direction.as_ref(),
+        &gmath::normalize(light.direction),
+    );
+    ctx.uniform1f(locations.ambient.as_ref(), light.ambient);
+    ctx.uniform1f(locations.specular.as_ref(), light.specular);
+    ctx.uniform1f(locations.shininess.as_ref(), light.shininess);
+    ctx.uniform3fv_with_f32_array(locations.camera_pos.as_ref(), &view.camera_pos);
 
-    ctx.draw_arrays(
+    // draw the geometry
+    ctx.draw_elements_with_i32(
         web_sys::WebGlRenderingContext::TRIANGLES,
+        cube.indices_count,
+        web_sys::WebGlRenderingContext::UNSIGNED_SHORT,
         0,
-        CUBE_VERTICES_COUNT,
     );
+    stats.draw_calls += 1;
+
+    // leave default vertex array for other drawers
+    if let GlContext::WebGl2(ctx) = ctx {
+        ctx.bind_vertex_array(None);
+    }
 
     cube.needs_redraw = false;
 }
@@ -2,6 +2,8 @@ precision mediump float;
 
 varying vec2 v_cube_texture_coord;
 varying float v_cube_vertex_side;
+varying vec3 v_normal;
+varying vec3 v_surface_pos;
 
 // for covering different sides of cube with different textures we associate
 // each vertex with side index, pass this side index from vertex shader to
@@ -21,6 +23,30 @@ uniform sampler2D u_cube_texture_side_3;
 uniform sampler2D u_cube_texture_side_4;
 uniform sampler2D u_cube_texture_side_5;
 
+// directional light, see Light model
+uniform vec3 u_light_direction;
+uniform float u_ambient_light;
+uniform float u_specular_light;
+uniform float u_shininess;
+uniform vec3 u_camera_pos;
+
+vec3 apply_light(vec3 color) {
+  vec3 normal = normalize(v_normal);
+  vec3 to_light = -u_light_direction;
+
+  float diffuse = max(dot(normal, to_light), 0.0);
+
+  // blinn-phong highlight, only on faces turned to light
+  vec3 to_camera = normalize(u_camera_pos - v_surface_pos);
+  vec3 half_vector = normalize(to_light + to_camera);
+  float specular = 0.0;
+  if (diffuse > 0.0) {
+    specular = u_specular_light * pow(max(dot(normal, half_vector), 0.0), u_shininess);
+  }
+
+  return color * (u_ambient_light + (1.0 - u_ambient_light) * diffuse) + specular;
+}
+
 void main() {
   // round cube side index. even though side index equals to the same integer
   // for each vertex of one cube side, and therefore should be the same for each
@@ -29,22 +55,26 @@ void main() {
   // varying between vertices
   int side = int(floor(v_cube_vertex_side + 0.5));
 
+  vec4 color;
+
   if (side == 0) {
-    gl_FragColor = texture2D(u_cube_texture_side_0, v_cube_texture_coord);
+    color = texture2D(u_cube_texture_side_0, v_cube_texture_coord);
   } else
   if (side == 1) {
-    gl_FragColor = texture2D(u_cube_texture_side_1, v_cube_texture_coord);
+    color = texture2D(u_cube_texture_side_1, v_cube_texture_coord);
   } else
   if (side == 2) {
-    gl_FragColor = texture2D(u_cube_texture_side_2, v_cube_texture_coord);
+    color = texture2D(u_cube_texture_side_2, v_cube_texture_coord);
   } else
   if (side == 3) {
-    gl_FragColor = texture2D(u_cube_texture_side_3, v_cube_texture_coord);
+    color = texture2D(u_cube_texture_side_3, v_cube_texture_coord);
   } else
   if (side == 4) {
-    gl_FragColor = texture2D(u_cube_texture_side_4, v_cube_texture_coord);
+    color = texture2D(u_cube_texture_side_4, v_cube_texture_coord);
   } else
   if (side == 5) {
-    gl_FragColor = texture2D(u_cube_texture_side_5, v_cube_texture_coord);
+    color = texture2D(u_cube_texture_side_5, v_cube_texture_coord);
   }
+
+  gl_FragColor = vec4(apply_light(color.rgb), color.a);
 }
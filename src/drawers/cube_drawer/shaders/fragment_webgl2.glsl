@@ -0,0 +1,44 @@
+#version 300 es
+
+precision mediump float;
+precision mediump sampler2DArray;
+
+in vec2 v_cube_texture_coord;
+flat in int v_cube_side;
+in vec3 v_normal;
+in vec3 v_surface_pos;
+
+// textures of all cube sides are layers of single texture array, so texture is
+// selected by side index without branching
+uniform sampler2DArray u_cube_textures;
+
+// directional light, see Light model
+uniform vec3 u_light_direction;
+uniform float u_ambient_light;
+uniform float u_specular_light;
+uniform float u_shininess;
+uniform vec3 u_camera_pos;
+
+out vec4 out_color;
+
+vec3 apply_light(vec3 color) {
+  vec3 normal = normalize(v_normal);
+  vec3 to_light = -u_light_direction;
+
+  float diffuse = max(dot(normal, to_light), 0.0);
+
+  // blinn-phong highlight, only on faces turned to light
+  vec3 to_camera = normalize(u_camera_pos - v_surface_pos);
+  vec3 half_vector = normalize(to_light + to_camera);
+  float specular = 0.0;
+  if (diffuse > 0.0) {
+    specular = u_specular_light * pow(max(dot(normal, half_vector), 0.0), u_shininess);
+  }
+
+  return color * (u_ambient_light + (1.0 - u_ambient_light) * diffuse) + specular;
+}
+
+void main() {
+  vec4 color = texture(u_cube_textures, vec3(v_cube_texture_coord, v_cube_side));
+  out_color = vec4(apply_light(color.rgb), color.a);
+}
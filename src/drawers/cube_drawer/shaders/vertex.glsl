@@ -1,15 +1,24 @@
 attribute vec4 a_cube_vertex_coord;
 attribute vec2 a_cube_texture_coord;
 attribute float a_cube_vertex_side;
+attribute vec3 a_cube_vertex_normal;
 
 uniform mat4 u_matrix;
+uniform mat4 u_world_matrix;
+uniform mat4 u_normal_matrix;
 
 varying vec2 v_cube_texture_coord;
 varying float v_cube_vertex_side;
+varying vec3 v_normal;
+varying vec3 v_surface_pos;
 
 void main() {
   gl_Position = u_matrix * a_cube_vertex_coord;
 
   v_cube_texture_coord = a_cube_texture_coord;
   v_cube_vertex_side = a_cube_vertex_side;
+
+  // lighting is computed in world space, where light direction is defined
+  v_normal = mat3(u_normal_matrix) * a_cube_vertex_normal;
+  v_surface_pos = (u_world_matrix * a_cube_vertex_coord).xyz;
 }
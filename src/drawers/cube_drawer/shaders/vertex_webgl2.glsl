@@ -0,0 +1,29 @@
+#version 300 es
+
+in vec4 a_cube_vertex_coord;
+in vec2 a_cube_texture_coord;
+in float a_cube_vertex_side;
+in vec3 a_cube_vertex_normal;
+
+uniform mat4 u_matrix;
+uniform mat4 u_world_matrix;
+uniform mat4 u_normal_matrix;
+
+out vec2 v_cube_texture_coord;
+out vec3 v_normal;
+out vec3 v_surface_pos;
+
+// flat varying is not interpolated between vertices, so side index reaches
+// fragment shader as exact integer
+flat out int v_cube_side;
+
+void main() {
+  gl_Position = u_matrix * a_cube_vertex_coord;
+
+  v_cube_texture_coord = a_cube_texture_coord;
+  v_cube_side = int(a_cube_vertex_side);
+
+  // lighting is computed in world space, where light direction is defined
+  v_normal = mat3(u_normal_matrix) * a_cube_vertex_normal;
+  v_surface_pos = (u_world_matrix * a_cube_vertex_coord).xyz;
+}
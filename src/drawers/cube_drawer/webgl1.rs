@@ -0,0 +1,116 @@
+use web_sys::WebGlRenderingContext as GL;
+
+use crate::{
+    helpers::{gl_context::GlContext, webgl::get_uniform_location},
+    models::{Cube, RenderStats},
+};
+
+use super::{get_side_image_data, set_side_texture_filter};
+
+/// Creates separate texture for each cube side, bound to texture unit with
+/// the same index as the side
+pub fn init_side_textures(ctx: &GlContext, program: &web_sys::WebGlProgram, cube: &mut Cube) {
+    let mut cube_textures: std::vec::Vec<web_sys::WebGlTexture> = Vec::new();
+    cube_textures.reserve(cube.sides.len());
+    for side_type in cube.sides.keys() {
+        let texture = ctx.create_texture().unwrap();
+        cube_textures.push(texture);
+
+        // bind uniform with texture unit
+        let side_type_idx = *side_type as i32;
+        let uniform_name = format!("u_cube_texture_side_{side_type_idx}");
+        let cube_texture_side_uniform_location =
+            get_uniform_location(ctx, program, &uniform_name).unwrap();
+
+        ctx.uniform1i(Some(&cube_texture_side_uniform_location), side_type_idx);
+    }
+
+    cube.textures = cube_textures;
+
+    allocate_side_textures(ctx, cube);
+}
+
+/// Passes side canvases to textures, with their current size and filtering
+pub fn allocate_side_textures(ctx: &GlContext, cube: &mut Cube) {
+    let (mipmaps, anisotropy) = (cube.mipmaps, cube.anisotropy);
+
+    for (side_type, side) in &mut cube.sides {
+        let canvas = side.canvas.as_ref().unwrap();
+
+        let side_type_idx = *side_type as u32;
+        ctx.active_texture(GL::TEXTURE0 + side_type_idx);
+        ctx.bind_texture(GL::TEXTURE_2D, cube.textures.get(side_type_idx as usize));
+
+        ctx.tex_image_2d_with_canvas(
+            GL::TEXTURE_2D,
+            0,
+            GL::RGBA as i32,
+            GL::RGBA,
+            GL::UNSIGNED_BYTE,
+            canvas,
+        )
+        .unwrap();
+
+        ctx.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
+        ctx.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
+        set_side_texture_filter(ctx, GL::TEXTURE_2D, mipmaps, anisotropy);
+
+        if mipmaps {
+            ctx.generate_mipmap(GL::TEXTURE_2D);
+        }
+
+        side.needs_update_on_cube = false;
+    }
+}
+
+pub fn update_side_textures(ctx: &GlContext, cube: &mut Cube, stats: &mut RenderStats) {
+    let mipmaps = cube.mipmaps;
+
+    for (side_type, side) in &mut cube.sides {
+        if !side.needs_update_on_cube && side.dirty_rects.is_empty() {
+            continue;
+        }
+
+        let side_type_idx = *side_type as u32;
+        ctx.active_texture(GL::TEXTURE0 + side_type_idx);
+        ctx.bind_texture(GL::TEXTURE_2D, cube.textures.get(side_type_idx as usize));
+
+        let canvas = side.canvas.as_ref().unwrap();
+
+        if side.needs_update_on_cube {
+            ctx.tex_sub_image_2d_with_canvas(
+                GL::TEXTURE_2D,
+                0,
+                0,
+                0,
+                GL::RGBA,
+                GL::UNSIGNED_BYTE,
+                canvas,
+            )
+            .unwrap();
+            stats.uploaded_bytes += (canvas.width() * canvas.height() * 4) as usize;
+        } else {
+            for rect in &side.dirty_rects {
+                let image_data = get_side_image_data(side, rect);
+                ctx.tex_sub_image_2d_with_image_data(
+                    GL::TEXTURE_2D,
+                    0,
+                    rect.x,
+                    rect.y,
+                    GL::RGBA,
+                    GL::UNSIGNED_BYTE,
+                    &image_data,
+                )
+                .unwrap();
+                stats.uploaded_bytes += (rect.width * rect.height * 4) as usize;
+            }
+        }
+
+        if mipmaps {
+            ctx.generate_mipmap(GL::TEXTURE_2D);
+        }
+
+        side.needs_update_on_cube = false;
+        side.dirty_rects.clear();
+    }
+}
@@ -0,0 +1,145 @@
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::{
+    helpers::gl_context::GlContext,
+    models::{Cube, RenderStats},
+};
+
+use super::{bind_cube_attributes, get_side_image_data, set_side_texture_filter};
+
+/// Creates single texture array for all cube sides, where layer index is the
+/// side index
+pub fn init_side_texture_array(
+    ctx: &GlContext,
+    ctx2: &GL,
+    program: &web_sys::WebGlProgram,
+    cube: &mut Cube,
+) {
+    allocate_side_texture_array(ctx, ctx2, cube);
+
+    let textures_uniform_location = ctx2
+        .get_uniform_location(program, "u_cube_textures")
+        .expect("failed to get uniform location: u_cube_textures");
+    ctx2.uniform1i(Some(&textures_uniform_location), 0);
+}
+
+/// Replaces texture array with the one of current side canvases size and
+/// filtering, and passes side canvases to it. Storage of texture array can't
+/// be resized, so it's created again
+pub fn allocate_side_texture_array(ctx: &GlContext, ctx2: &GL, cube: &mut Cube) {
+    ctx2.delete_texture(cube.texture_array.as_ref());
+    let texture = ctx2.create_texture();
+
+    ctx2.active_texture(GL::TEXTURE0);
+    ctx2.bind_texture(GL::TEXTURE_2D_ARRAY, texture.as_ref());
+
+    let (width, height) = get_side_size(cube);
+    let levels = if cube.mipmaps {
+        32 - width.max(height).leading_zeros() as i32
+    } else {
+        1
+    };
+    ctx2.tex_storage_3d(
+        GL::TEXTURE_2D_ARRAY,
+        levels,
+        GL::RGBA8,
+        width,
+        height,
+        cube.sides.len() as i32,
+    );
+
+    ctx2.tex_parameteri(
+        GL::TEXTURE_2D_ARRAY,
+        GL::TEXTURE_WRAP_S,
+        GL::CLAMP_TO_EDGE as i32,
+    );
+    ctx2.tex_parameteri(
+        GL::TEXTURE_2D_ARRAY,
+        GL::TEXTURE_WRAP_T,
+        GL::CLAMP_TO_EDGE as i32,
+    );
+    set_side_texture_filter(ctx, GL::TEXTURE_2D_ARRAY, cube.mipmaps, cube.anisotropy);
+
+    cube.texture_array = texture;
+
+    for side in cube.sides.values_mut() {
+        side.needs_update_on_cube = true;
+    }
+    update_side_texture_array(ctx2, cube, &mut RenderStats::default());
+}
+
+/// Saves cube attributes to vertex array object, so they are not defined again
+/// on each draw
+pub fn init_vertex_array(ctx: &GlContext, ctx2: &GL, cube: &mut Cube) {
+    let vertex_array = ctx2.create_vertex_array();
+    ctx2.bind_vertex_array(vertex_array.as_ref());
+
+    bind_cube_attributes(ctx, cube);
+
+    ctx2.bind_vertex_array(None);
+    cube.vertex_array = vertex_array;
+}
+
+pub fn update_side_texture_array(ctx: &GL, cube: &mut Cube, stats: &mut RenderStats) {
+    let (width, height) = get_side_size(cube);
+
+    ctx.active_texture(GL::TEXTURE0);
+    ctx.bind_texture(GL::TEXTURE_2D_ARRAY, cube.texture_array.as_ref());
+
+    let mut is_updated = false;
+
+    for (side_type, side) in &mut cube.sides {
+        is_updated |= side.needs_update_on_cube || !side.dirty_rects.is_empty();
+
+        if side.needs_update_on_cube {
+            ctx.tex_sub_image_3d_with_html_canvas_element(
+                GL::TEXTURE_2D_ARRAY,
+                0,
+                0,
+                0,
+                *side_type as i32,
+                width,
+                height,
+                1,
+                GL::RGBA,
+                GL::UNSIGNED_BYTE,
+                side.canvas.as_ref().unwrap(),
+            )
+            .unwrap();
+            stats.uploaded_bytes += (width * height * 4) as usize;
+        } else {
+            for rect in &side.dirty_rects {
+                let image_data = get_side_image_data(side, rect);
+                ctx.tex_sub_image_3d_with_image_data(
+                    GL::TEXTURE_2D_ARRAY,
+                    0,
+                    rect.x,
+                    rect.y,
+                    *side_type as i32,
+                    rect.width,
+                    rect.height,
+                    1,
+                    GL::RGBA,
+                    GL::UNSIGNED_BYTE,
+                    &image_data,
+                )
+                .unwrap();
+                stats.uploaded_bytes += (rect.width * rect.height * 4) as usize;
+            }
+        }
+
+        side.needs_update_on_cube = false;
+        side.dirty_rects.clear();
+    }
+
+    // all layers share mipmap chain, so it's built once
+    if is_updated && cube.mipmaps {
+        ctx.generate_mipmap(GL::TEXTURE_2D_ARRAY);
+    }
+}
+
+// all side canvases have the same size
+fn get_side_size(cube: &Cube) -> (i32, i32) {
+    let canvas = cube.sides.values().next().unwrap().canvas.as_ref().unwrap();
+    (canvas.width() as i32, canvas.height() as i32)
+}
@@ -1,19 +1,17 @@
 use wasm_bindgen::JsCast;
 
-use crate::{
-    helpers::canvas::{get_canvas_font_string, measure_canvas_text},
-    models::{CubeSideType, GameState, GameStatus},
-};
+use crate::models::{Color, CubeSideType, GameState, Grid, Rect, SpriteAtlas};
+
+use super::side_painter::{paint_side, CellShape, ObjectCell, SideSurface};
 
 // cube sides are drawn in 2D context and passed as textures to 3D cube.
 // this is not very performant approach, since we need to upload entire side
-// image when something small changes on it. faster wound be to upload object
-// positions only and draw them as separate 3D entities, textures untouched.
-// I've dodged this approach because I guess it would be harder to code, while
-// I want it to be as basic as possible without diving into 3D coding hell
-// (for that I would chose some 3D library) ie. need to calculate 3D positions
-// for all objects, apply different textures for different objects (snake,
-// stones, apples, status overlays), etc.
+// image when something small changes on it (or at least changed cells of it,
+// see dirty cells in cube side). so when instancing is supported,
+// objects are drawn as separate 3D meshes by objects drawer instead, and side
+// textures only have grid, which does not change during play. painting objects
+// here is kept as a fallback. status text is not painted here either, but on
+// screen-space HUD
 pub fn init_cube_side_drawer(state: &mut GameState, side_type: CubeSideType) {
     let window = web_sys::window().unwrap();
     let document = window.document().unwrap();
@@ -24,8 +22,9 @@ pub fn init_cube_side_drawer(state: &mut GameState, side_type: CubeSideType) {
         .dyn_into::<web_sys::HtmlCanvasElement>()
         .unwrap();
 
-    canvas.set_width(2u32.pow(9u32));
-    canvas.set_height(2u32.pow(9u32));
+    let size = state.scene.quality.preset().side_texture_size;
+    canvas.set_width(size);
+    canvas.set_height(size);
 
     let ctx = canvas
         .get_context("2d")
@@ -47,13 +46,67 @@ pub fn init_cube_side_drawer(state: &mut GameState, side_type: CubeSideType) {
     side.needs_update_on_cube = true;
 }
 
+/// Resizes side canvas to side texture size of current quality preset
+pub fn resize_cube_side(state: &mut GameState, side_type: CubeSideType) {
+    let size = state.scene.quality.preset().side_texture_size;
+    let side = state.scene.cube.sides.get_mut(&side_type).unwrap();
+    let canvas = side.canvas.as_ref().unwrap();
+
+    if canvas.width() != size || canvas.height() != size {
+        canvas.set_width(size);
+        canvas.set_height(size);
+    }
+
+    side.needs_redraw = true;
+    side.dirty_cells.clear();
+    side.dirty_rects.clear();
+}
+
 pub fn draw_cube_side_loop(state: &mut GameState, side_type: CubeSideType) {
     let cube = &mut state.scene.cube;
     let side = cube.sides.get_mut(&side_type).unwrap();
+    let canvas = side.canvas.as_ref().unwrap();
 
-    if !side.needs_redraw {
-        return;
+    if side.needs_redraw {
+        let rect = Rect {
+            x: 0,
+            y: 0,
+            width: canvas.width() as i32,
+            height: canvas.height() as i32,
+        };
+
+        side.dirty_cells.clear();
+        paint_cube_side(state, side_type, &rect);
+
+        let side = state.scene.cube.sides.get_mut(&side_type).unwrap();
+        side.needs_redraw = false;
+        side.needs_update_on_cube = true;
+        side.dirty_rects.clear();
+
+        state.scene.stats.side_repaints += 1;
+    } else if !side.dirty_cells.is_empty() {
+        // repaint changed cells only, so only they are uploaded to the cube
+        let rects: Vec<Rect> = side
+            .dirty_cells
+            .drain()
+            .map(|(row, col)| get_cell_rect(canvas, &cube.grid, row, col))
+            .collect();
+
+        for rect in &rects {
+            paint_cube_side(state, side_type, rect);
+        }
+
+        state.scene.stats.side_repaints += rects.len() as u32;
+
+        let side = state.scene.cube.sides.get_mut(&side_type).unwrap();
+        side.dirty_rects.extend(rects);
     }
+}
+
+// paints everything on cube side, but only inside the rect
+fn paint_cube_side(state: &GameState, side_type: CubeSideType, rect: &Rect) {
+    let cube = &state.scene.cube;
+    let side = cube.sides.get(&side_type).unwrap();
 
     let canvas = side.canvas.as_ref().unwrap();
     let ctx = side.ctx.as_ref().unwrap();
@@ -61,147 +114,253 @@ pub fn draw_cube_side_loop(state: &mut GameState, side_type: CubeSideType) {
     let width = canvas.width() as f64;
     let height = canvas.height() as f64;
 
-    ctx.clear_rect(0.0, 0.0, width, height);
+    ctx.save();
+    ctx.begin_path();
+    ctx.rect(
+        rect.x as f64,
+        rect.y as f64,
+        rect.width as f64,
+        rect.height as f64,
+    );
+    ctx.clip();
 
-    ctx.set_global_alpha(1.0);
-    ctx.set_fill_style(&"white".into());
-    ctx.fill_rect(0.0, 0.0, width, height);
+    ctx.clear_rect(0.0, 0.0, width, height);
 
-    // draw grid
-    let grid = &cube.grid;
+    // sprites are pixel art, keep them sharp
+    ctx.set_image_smoothing_enabled(false);
+
+    let mut surface = CanvasSurface {
+        ctx,
+        width,
+        height,
+        sprites: get_loaded_sprites(state),
+        is_shape_coding_on: state.scene.is_shape_coding_on,
+        background: state.scene.theme().background,
+    };
+
+    // objects are either painted here or drawn as meshes on top of the cube
+    paint_side(
+        state,
+        side_type,
+        &mut surface,
+        (width, height),
+        !cube.objects_as_meshes,
+    );
+
+    ctx.restore();
+}
 
-    let cell_width = width / grid.cols_count as f64;
-    let cell_height = height / grid.rows_count as f64;
+struct CanvasSurface<'a> {
+    ctx: &'a web_sys::CanvasRenderingContext2d,
+    width: f64,
+    height: f64,
+    sprites: Option<(&'a web_sys::HtmlImageElement, SpriteAtlas)>,
+    is_shape_coding_on: bool,
+    background: Color,
+}
 
-    for i in 1..grid.cols_count {
-        let x = i as f64 * cell_width;
-        ctx.move_to(x, 0.0);
-        ctx.line_to(x, height);
+impl SideSurface for CanvasSurface<'_> {
+    fn fill_background(&mut self, color: Color) {
+        self.ctx.set_global_alpha(1.0);
+        self.ctx.set_fill_style(&color.to_css().into());
+        self.ctx.fill_rect(0.0, 0.0, self.width, self.height);
     }
 
-    for i in 1..grid.rows_count {
-        let y = i as f64 * cell_height;
-        ctx.move_to(0.0, y);
-        ctx.line_to(width, y);
+    fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: Color) {
+        self.ctx.begin_path();
+        self.ctx.move_to(from.0, from.1);
+        self.ctx.line_to(to.0, to.1);
+        self.ctx.set_line_width(width);
+        self.ctx.set_stroke_style(&color.to_css().into());
+        self.ctx.stroke();
     }
 
-    ctx.set_line_width(1.0);
-    ctx.stroke();
-
-    // draw snake
-    ctx.set_fill_style(&"red".into());
-    for part in &state.snake.parts {
-        if part.side == side_type {
-            ctx.fill_rect(
-                part.col as f64 * cell_width,
-                height - part.row as f64 * cell_height - cell_height,
-                cell_width,
-                cell_height,
-            );
+    // paints object with sprite if theme has one for it, or with color otherwise
+    fn paint_cell(&mut self, (x, y, width, height): (f64, f64, f64, f64), cell: &ObjectCell) {
+        let ctx = self.ctx;
+        let sprite =
+            self.sprites
+                .as_ref()
+                .zip(cell.sprite)
+                .and_then(|((image, atlas), sprite_type)| {
+                    let index = atlas.sprites.iter().position(|t| *t == sprite_type)?;
+                    Some((*image, atlas.sprite_size as f64, index as f64))
+                });
+
+        ctx.set_global_alpha(cell.alpha as f64);
+
+        match sprite {
+            Some((image, size, index)) => ctx
+                .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    image,
+                    index * size,
+                    0.0,
+                    size,
+                    size,
+                    x,
+                    y,
+                    width,
+                    height,
+                )
+                .unwrap(),
+            None if self.is_shape_coding_on => {
+                let rect = (x, y, width, height);
+                paint_shape(ctx, cell.shape, rect, cell.color, self.background);
+            }
+            None => {
+                ctx.set_fill_style(&cell.color.to_css().into());
+                ctx.fill_rect(x, y, width, height);
+            }
         }
+
+        ctx.set_global_alpha(1.0);
     }
+}
 
-    // draw apples
-    ctx.set_fill_style(&"green".into());
-    for apple in &state.apples {
-        if apple.side == side_type {
-            ctx.fill_rect(
-                apple.col as f64 * cell_width,
-                height - apple.row as f64 * cell_height - cell_height,
-                cell_width,
-                cell_height,
-            )
+// head is a plain square, body has diagonal stripes, rivals have a hole in the
+// middle, apples are circles and stones are crosses
+fn paint_shape(
+    ctx: &web_sys::CanvasRenderingContext2d,
+    shape: CellShape,
+    (x, y, width, height): (f64, f64, f64, f64),
+    color: Color,
+    background: Color,
+) {
+    let (center_x, center_y) = (x + width / 2.0, y + height / 2.0);
+    let size = width.min(height);
+
+    ctx.set_fill_style(&color.to_css().into());
+
+    match shape {
+        CellShape::Square | CellShape::Striped | CellShape::Ringed => {
+            ctx.fill_rect(x, y, width, height);
+        }
+        CellShape::Circle => {
+            ctx.begin_path();
+            ctx.arc(center_x, center_y, size * 0.4, 0.0, std::f64::consts::TAU)
+                .unwrap();
+            ctx.fill();
+        }
+        CellShape::Cross => {
+            let inset = size * 0.2;
+
+            ctx.begin_path();
+            ctx.move_to(x + inset, y + inset);
+            ctx.line_to(x + width - inset, y + height - inset);
+            ctx.move_to(x + width - inset, y + inset);
+            ctx.line_to(x + inset, y + height - inset);
+
+            ctx.set_line_width(size * 0.18);
+            ctx.set_line_cap("round");
+            ctx.set_stroke_style(&color.to_css().into());
+            ctx.stroke();
+            ctx.set_line_cap("butt");
         }
     }
 
-    // draw stones
-    ctx.set_fill_style(&"black".into());
-    for stone in &state.stones {
-        if stone.side == side_type {
-            ctx.fill_rect(
-                stone.col as f64 * cell_width,
-                height - stone.row as f64 * cell_height - cell_height,
-                cell_width,
-                cell_height,
-            );
+    // patterns are cut out with background color
+    match shape {
+        CellShape::Striped => {
+            ctx.save();
+            ctx.begin_path();
+            ctx.rect(x, y, width, height);
+            ctx.clip();
+
+            ctx.begin_path();
+            for i in -1..=2 {
+                let offset = i as f64 * size / 2.0;
+                ctx.move_to(x + offset, y + height);
+                ctx.line_to(x + offset + size, y + height - size);
+            }
+
+            ctx.set_line_width(size * 0.12);
+            ctx.set_stroke_style(&background.to_css().into());
+            ctx.stroke();
+            ctx.restore();
         }
+        CellShape::Ringed => {
+            ctx.set_fill_style(&background.to_css().into());
+            ctx.begin_path();
+            ctx.arc(center_x, center_y, size * 0.2, 0.0, std::f64::consts::TAU)
+                .unwrap();
+            ctx.fill();
+        }
+        _ => (),
     }
+}
 
-    // draw status overlay
-    if state.status != GameStatus::InGame {
-        const OVERLAY_HEIGHT: f64 = 200.0;
-        const OVERLAY_WIDTH: f64 = 400.0;
-        const OVERLAY_PADDING: f64 = 30.0;
-
-        let overlay_horizontal_margin = (width - OVERLAY_WIDTH) / 2.0;
-        let overlay_vertical_margin = (height - OVERLAY_HEIGHT) / 2.0;
-
-        ctx.set_global_alpha(0.7);
-        ctx.set_fill_style(&"white".into());
-        ctx.fill_rect(
-            overlay_horizontal_margin,
-            overlay_vertical_margin,
-            OVERLAY_WIDTH,
-            OVERLAY_HEIGHT,
-        );
-
-        ctx.set_line_width(3.0);
-        ctx.set_stroke_style(&"black".into());
-        ctx.stroke_rect(
-            overlay_horizontal_margin,
-            overlay_vertical_margin,
-            OVERLAY_WIDTH,
-            OVERLAY_HEIGHT,
-        );
-
-        // title
-        ctx.set_fill_style(&"black".into());
-        let title_font =
-            get_canvas_font_string(Some(70), Some("Consolas"), Some("px"), Some("bold"));
-        ctx.set_font(&title_font);
-
-        let title = match state.status {
-            GameStatus::Paused => "PAUSED",
-            GameStatus::Win => "WIN",
-            GameStatus::Fail => "FAIL",
-            _ => "SNAKE 3D",
-        };
+fn get_loaded_sprites(state: &GameState) -> Option<(&web_sys::HtmlImageElement, SpriteAtlas)> {
+    let scene = &state.scene;
+    let atlas = scene.theme().sprites?;
 
-        let title_size = measure_canvas_text(ctx, title).unwrap();
-        ctx.fill_text(
-            title,
-            width / 2.0 - title_size.width / 2.0,
-            height / 2.0 + title_size.height / 2.0,
-        )
-        .unwrap();
+    if !scene.are_sprites_loaded {
+        return None;
+    }
 
-        // controls hint
-        let controls_hint_font = get_canvas_font_string(Some(20), Some("Consolas"), None, None);
-        ctx.set_font(&controls_hint_font);
-        let constrols_hint = "WSAD/arrows to control";
-        let controls_hint_size = measure_canvas_text(ctx, constrols_hint).unwrap();
-
-        ctx.fill_text(
-            constrols_hint,
-            width / 2.0 - controls_hint_size.width / 2.0,
-            overlay_vertical_margin + OVERLAY_PADDING + controls_hint_size.height,
-        )
-        .unwrap();
+    Some((scene.sprites_image.as_ref()?, atlas))
+}
 
-        // start hint
-        let start_hint_font = get_canvas_font_string(Some(20), Some("Consolas"), None, None);
-        ctx.set_font(&start_hint_font);
-        let start_hint = "space/enter to start";
-        let start_hint_size = measure_canvas_text(ctx, start_hint).unwrap();
-
-        ctx.fill_text(
-            start_hint,
-            width / 2.0 - start_hint_size.width / 2.0,
-            height - overlay_vertical_margin - OVERLAY_PADDING,
-        )
-        .unwrap();
+/// Loads sprite atlas of current theme, and repaints sides once it is loaded.
+/// Until then objects are painted with theme colors
+pub fn load_sprites_loop(state: &mut GameState) {
+    let scene = &mut state.scene;
+    let atlas = match scene.theme().sprites {
+        Some(atlas) => atlas,
+        None => {
+            scene.sprites_image = None;
+            scene.are_sprites_loaded = false;
+            return;
+        }
+    };
+
+    // image src is resolved to absolute url
+    let is_requested = scene
+        .sprites_image
+        .as_ref()
+        .is_some_and(|image| image.src().ends_with(atlas.url));
+
+    if !is_requested {
+        let image = web_sys::HtmlImageElement::new().unwrap();
+        image.set_src(atlas.url);
+        scene.sprites_image = Some(image);
+        scene.are_sprites_loaded = false;
+        return;
     }
 
-    side.needs_redraw = false;
-    side.needs_update_on_cube = true;
+    let image = scene.sprites_image.as_ref().unwrap();
+    if !scene.are_sprites_loaded && image.complete() && image.natural_width() > 0 {
+        scene.are_sprites_loaded = true;
+        scene
+            .cube
+            .sides
+            .values_mut()
+            .for_each(|side| side.needs_redraw = true);
+    }
+}
+
+// cell bounds are expanded by a pixel, so antialiased grid lines around the
+// cell are repainted too
+fn get_cell_rect(canvas: &web_sys::HtmlCanvasElement, grid: &Grid, row: i32, col: i32) -> Rect {
+    let width = canvas.width() as f64;
+    let height = canvas.height() as f64;
+
+    let cell_width = width / grid.cols_count as f64;
+    let cell_height = height / grid.rows_count as f64;
+
+    let left = (col as f64 * cell_width).floor() as i32 - 1;
+    let top = (height - (row + 1) as f64 * cell_height).floor() as i32 - 1;
+    let right = ((col + 1) as f64 * cell_width).ceil() as i32 + 1;
+    let bottom = (height - row as f64 * cell_height).ceil() as i32 + 1;
+
+    let left = left.max(0);
+    let top = top.max(0);
+    let right = right.min(width as i32);
+    let bottom = bottom.min(height as i32);
+
+    Rect {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    }
 }
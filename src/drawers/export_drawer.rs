@@ -0,0 +1,209 @@
+use std::{collections::HashMap, fmt::Write};
+
+use wasm_bindgen::{Clamped, JsCast, JsValue};
+
+use crate::{
+    helpers::{
+        cube_net::{get_cube_net, NetSide, NET_COLS, NET_ROWS},
+        dom::{download_file, download_url},
+    },
+    models::{Color, CubePosition, CubeSideType, GameState},
+};
+
+use super::{side_painter::paint_objects, software_renderer::paint_side_image};
+
+// size of one side in exported net image, in pixels
+const NET_SIDE_SIZE: usize = 256;
+
+// size of one cell in exported net svg, in svg units
+const NET_CELL_SIZE: usize = 20;
+
+/// Saves the frame that has just been drawn, if screenshot is requested. Must
+/// run right after drawing, since webgl canvas is cleared once frame is shown
+pub fn save_screenshot_loop(state: &mut GameState) {
+    if !state.scene.is_screenshot_requested {
+        return;
+    }
+    state.scene.is_screenshot_requested = false;
+
+    let canvas = state.scene.canvas.as_ref().unwrap();
+    let file_name = format!("snake-3d-{}.png", state.seed);
+
+    canvas
+        .to_data_url_with_type("image/png")
+        .and_then(|url| download_url(&file_name, &url))
+        .expect("failed to save screenshot");
+}
+
+/// Saves all six sides of the cube unfolded into a cross as PNG
+pub fn save_net_png(state: &GameState) -> Result<(), JsValue> {
+    let (width, height) = get_net_size(NET_SIDE_SIZE);
+    let pixels = draw_net_image(state, NET_SIDE_SIZE);
+
+    // browser encodes PNG from canvas, so encoder is not bundled
+    let canvas = web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .create_element("canvas")?
+        .dyn_into::<web_sys::HtmlCanvasElement>()?;
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+
+    let ctx = canvas
+        .get_context("2d")?
+        .unwrap()
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()?;
+    let image_data = web_sys::ImageData::new_with_u8_clamped_array_and_sh(
+        Clamped(&pixels),
+        width as u32,
+        height as u32,
+    )?;
+    ctx.put_image_data(&image_data, 0.0, 0.0)?;
+
+    let url = canvas.to_data_url_with_type("image/png")?;
+    download_url(&format!("snake-3d-{}-net.png", state.seed), &url)
+}
+
+/// Saves all six sides of the cube unfolded into a cross as SVG
+pub fn save_net_svg(state: &GameState) -> Result<(), JsValue> {
+    let svg = draw_net_svg(state, NET_CELL_SIZE);
+    let file_name = format!("snake-3d-{}-net.svg", state.seed);
+
+    download_file(&file_name, svg.as_bytes(), "image/svg+xml")
+}
+
+fn get_net_size(side_size: usize) -> (usize, usize) {
+    (NET_COLS * side_size, NET_ROWS * side_size)
+}
+
+/// Paints unfolded cube into RGBA pixels. Space around the cross is transparent
+pub fn draw_net_image(state: &GameState, side_size: usize) -> Vec<u8> {
+    let (width, height) = get_net_size(side_size);
+    let mut pixels = vec![0; width * height * 4];
+
+    // front centered net keeps every side image upright, so images are placed
+    // as they are
+    for NetSide { side, col, row, .. } in get_cube_net(CubeSideType::Front) {
+        let side = paint_side_image(state, side, side_size);
+        let row_len = side_size * 4;
+
+        for (i, side_row) in side.chunks(row_len).enumerate() {
+            let offset = ((row * side_size + i) * width + col * side_size) * 4;
+            pixels[offset..offset + row_len].copy_from_slice(side_row);
+        }
+    }
+
+    pixels
+}
+
+/// Draws unfolded cube as SVG, with one rect per cell
+pub fn draw_net_svg(state: &GameState, cell_size: usize) -> String {
+    let theme = state.scene.theme();
+    let grid = &state.scene.cube.grid;
+
+    let rows_count = grid.rows_count as usize;
+    let cols_count = grid.cols_count as usize;
+
+    // colors of cells with objects, blended the same way as on sides
+    let mut cell_colors: HashMap<CubePosition, Color> = HashMap::new();
+    paint_objects(state, |pos, cell| {
+        let under = cell_colors.get(pos).copied().unwrap_or(theme.background);
+        cell_colors.insert(*pos, under.blend(cell.color, cell.alpha));
+    });
+
+    let width = NET_COLS * cols_count * cell_size;
+    let height = NET_ROWS * rows_count * cell_size;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        width, height, width, height
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"<g stroke="{}" stroke-width="{}">"#,
+        theme.grid_line.to_css(),
+        theme.grid_line_width
+    )
+    .unwrap();
+
+    for net_side in get_cube_net(CubeSideType::Front) {
+        for row in 0..rows_count {
+            for col in 0..cols_count {
+                let pos = CubePosition {
+                    side: net_side.side,
+                    row: row as i32,
+                    col: col as i32,
+                };
+                let color = cell_colors.get(&pos).unwrap_or(&theme.background);
+
+                // row 0 is at the bottom of side
+                let x = (net_side.col * cols_count + col) * cell_size;
+                let y = (net_side.row * rows_count + rows_count - 1 - row) * cell_size;
+
+                writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                    x,
+                    y,
+                    cell_size,
+                    cell_size,
+                    color.to_css()
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_net_image, draw_net_svg, get_net_size};
+    use crate::{
+        actions::game_actions::init_game_state,
+        drawers::software_renderer::paint_side_image,
+        models::{CubeSideType, GameState},
+    };
+
+    fn create_state() -> GameState {
+        let mut state = GameState::default();
+        init_game_state(&mut state, 7);
+        state
+    }
+
+    #[test]
+    fn places_sides_into_cross() {
+        let state = create_state();
+        let (width, _) = get_net_size(8);
+        let pixels = draw_net_image(&state, 8);
+
+        // top left corner is outside of the cross
+        assert_eq!(pixels[0..4], [0, 0, 0, 0]);
+
+        // front is in the middle of the second row
+        let front = paint_side_image(&state, CubeSideType::Front, 8);
+        for row in 0..8 {
+            let offset = ((8 + row) * width + 8) * 4;
+            assert_eq!(pixels[offset..offset + 32], front[row * 32..(row + 1) * 32]);
+        }
+    }
+
+    #[test]
+    fn draws_rect_per_cell() {
+        let state = create_state();
+        let grid = &state.scene.cube.grid;
+        let svg = draw_net_svg(&state, 10);
+
+        let cells_count = 6 * grid.rows_count * grid.cols_count;
+        assert_eq!(svg.matches("<rect").count(), cells_count as usize);
+
+        let head_color = state.scene.theme().snake_head.to_css();
+        assert!(svg.contains(&head_color));
+    }
+}
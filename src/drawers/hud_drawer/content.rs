@@ -0,0 +1,138 @@
+use std::cmp::Ordering;
+
+use crate::models::{GameState, GameStatus, HudContent, HudPanel};
+
+// longer gaps happen when tab is in background, and are not play
+const MAX_FRAME_TIME: f64 = 1000.0;
+
+/// Collects what HUD shows for current state
+pub fn get_hud_content(state: &GameState) -> HudContent {
+    let apples_eaten = state.rules.apples_count - state.apples.len();
+
+    HudContent {
+        score: format!("apples {}/{}", apples_eaten, state.rules.apples_count),
+        timer: format_play_time(state.scene.hud.play_time),
+        message: get_ghost_message(state),
+        panel: get_panel(state),
+        theme: state.scene.theme(),
+    }
+}
+
+/// Adds time since previous frame to play time, if game is running. New game
+/// starts counting from zero
+pub fn update_play_time(state: &mut GameState, now: f64) {
+    let hud = &mut state.scene.hud;
+    let last_time = hud.last_time.replace(now);
+
+    if state.tick == 0 {
+        hud.play_time = 0.0;
+    }
+
+    match last_time {
+        Some(time) if state.status == GameStatus::InGame && now - time < MAX_FRAME_TIME => {
+            hud.play_time += now - time;
+        }
+        _ => (),
+    }
+}
+
+fn format_play_time(time: f64) -> String {
+    let seconds = (time / 1000.0) as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn get_ghost_message(state: &GameState) -> String {
+    match &state.ghost {
+        Some(ghost) if matches!(state.status, GameStatus::InGame | GameStatus::Paused) => {
+            // compare apples eaten by the same tick
+            let apples_eaten = state.rules.apples_count - state.apples.len();
+            let ghost_apples_eaten = ghost.state.rules.apples_count - ghost.state.apples.len();
+
+            match apples_eaten.cmp(&ghost_apples_eaten) {
+                Ordering::Greater => {
+                    format!("{} ahead of best", apples_eaten - ghost_apples_eaten)
+                }
+                Ordering::Less => format!("{} behind best", ghost_apples_eaten - apples_eaten),
+                Ordering::Equal => "even with best".to_string(),
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+fn get_panel(state: &GameState) -> Option<HudPanel> {
+    let title = match state.status {
+        GameStatus::InGame => return None,
+        GameStatus::Paused if state.playback.is_some() => "REPLAY",
+        GameStatus::Paused => "PAUSED",
+        GameStatus::Win => "WIN",
+        GameStatus::Fail => "FAIL",
+        GameStatus::Welcome => "SNAKE 3D",
+    };
+
+    let controls_hint = match state.status {
+        _ if state.playback.is_some() => "arrows seek, 1-4 speed",
+        GameStatus::Win | GameStatus::Fail if state.network.is_none() => "R to download replay",
+        _ => "WSAD/arrows to control",
+    };
+
+    let start_hint = match &state.network {
+        _ if state.is_spectator => "spectating",
+        _ if state.playback.is_some() => "space to play, esc to exit",
+        Some(session) if session.desync_tick.is_some() => "desynced from server",
+        Some(session) if session.is_started => "game over",
+        Some(_) => "waiting for players",
+        None => "space/enter to start",
+    };
+
+    Some(HudPanel {
+        title,
+        controls_hint,
+        start_hint,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panel_is_shown_out_of_play() {
+        let mut state = GameState::default();
+        assert_eq!(get_hud_content(&state).panel.unwrap().title, "SNAKE 3D");
+
+        state.status = GameStatus::InGame;
+        assert_eq!(get_hud_content(&state).panel, None);
+
+        state.status = GameStatus::Paused;
+        assert_eq!(get_hud_content(&state).panel.unwrap().title, "PAUSED");
+    }
+
+    #[test]
+    fn pauses_are_not_counted_in_play_time() {
+        let mut state = GameState {
+            tick: 1,
+            ..GameState::default()
+        };
+
+        let mut now = 0.0;
+        let mut run_frames = |state: &mut GameState, status, count| {
+            state.status = status;
+            for _ in 0..count {
+                update_play_time(state, now);
+                now += 500.0;
+            }
+        };
+
+        // the first frame has nothing to count from, so 63 frames make 31 seconds
+        run_frames(&mut state, GameStatus::InGame, 63);
+        run_frames(&mut state, GameStatus::Paused, 10);
+        run_frames(&mut state, GameStatus::InGame, 2);
+        assert_eq!(get_hud_content(&state).timer, "0:32");
+
+        // new game starts from zero
+        state.tick = 0;
+        run_frames(&mut state, GameStatus::Welcome, 1);
+        assert_eq!(get_hud_content(&state).timer, "0:00");
+    }
+}
@@ -0,0 +1,170 @@
+mod content;
+
+use wasm_bindgen::JsCast;
+
+use crate::{
+    helpers::canvas::{get_canvas_font_string, measure_canvas_text, resize_canvas},
+    models::{GameState, HudContent, HudPanel, Size},
+};
+
+use content::{get_hud_content, update_play_time};
+
+const PANEL_WIDTH: f64 = 400.0;
+const PANEL_HEIGHT: f64 = 200.0;
+const PANEL_PADDING: f64 = 30.0;
+
+const MARGIN: f64 = 10.0;
+const LINE_HEIGHT: f64 = 24.0;
+
+pub fn init_hud_drawer(state: &mut GameState) {
+    let document = web_sys::window().unwrap().document().unwrap();
+
+    let canvas = document
+        .query_selector(".hud")
+        .unwrap()
+        .expect("failed to find hud element")
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+
+    let ctx = canvas
+        .get_context("2d")
+        .unwrap()
+        .expect("failed to get 2D render context")
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .unwrap();
+
+    state.scene.hud.canvas = Some(canvas);
+    state.scene.hud.ctx = Some(ctx);
+}
+
+/// Fits HUD canvas to the page. Unlike cube canvas, it always has full device
+/// resolution, since text is cheap to paint and blurry otherwise
+pub fn resize_hud(state: &mut GameState, css_size: Size) {
+    let pixel_ratio = web_sys::window().unwrap().device_pixel_ratio();
+    let hud = &mut state.scene.hud;
+
+    resize_canvas(hud.canvas.as_ref().unwrap(), css_size, pixel_ratio).unwrap();
+
+    // resizing clears the canvas
+    hud.pixel_ratio = pixel_ratio;
+    hud.content = None;
+}
+
+pub fn draw_hud_loop(state: &mut GameState) {
+    let now = web_sys::window().unwrap().performance().unwrap().now();
+    update_play_time(state, now);
+
+    let content = get_hud_content(state);
+    let hud = &mut state.scene.hud;
+
+    // touch canvas only when something changes
+    if hud.content.as_ref() == Some(&content) {
+        return;
+    }
+
+    let canvas = hud.canvas.as_ref().unwrap();
+    let ctx = hud.ctx.as_ref().unwrap();
+
+    let width = canvas.width() as f64 / hud.pixel_ratio;
+    let height = canvas.height() as f64 / hud.pixel_ratio;
+
+    ctx.set_transform(hud.pixel_ratio, 0.0, 0.0, hud.pixel_ratio, 0.0, 0.0)
+        .unwrap();
+    ctx.clear_rect(0.0, 0.0, width, height);
+
+    paint_counters(ctx, &content);
+    if let Some(panel) = &content.panel {
+        paint_panel(ctx, panel, &content, width, height);
+    }
+
+    hud.content = Some(content);
+}
+
+// score, timer and message in the top left corner, over whatever is behind
+fn paint_counters(ctx: &web_sys::CanvasRenderingContext2d, content: &HudContent) {
+    ctx.save();
+
+    ctx.set_font(&get_canvas_font_string(
+        Some(20),
+        Some(content.theme.hint_font),
+        None,
+        None,
+    ));
+    ctx.set_text_baseline("top");
+    ctx.set_fill_style(&"white".into());
+    ctx.set_shadow_color("rgba(0, 0, 0, 0.7)");
+    ctx.set_shadow_blur(3.0);
+
+    let lines = [&content.score, &content.timer, &content.message];
+    for (i, line) in lines.iter().enumerate() {
+        ctx.fill_text(line, MARGIN, MARGIN + i as f64 * LINE_HEIGHT)
+            .unwrap();
+    }
+
+    ctx.restore();
+}
+
+// status panel in the middle of the screen, shrunk on narrow screens
+fn paint_panel(
+    ctx: &web_sys::CanvasRenderingContext2d,
+    panel: &HudPanel,
+    content: &HudContent,
+    width: f64,
+    height: f64,
+) {
+    let theme = &content.theme;
+    let scale = ((width - MARGIN * 2.0) / PANEL_WIDTH).min(1.0);
+
+    ctx.save();
+    ctx.translate(width / 2.0, height / 2.0).unwrap();
+    ctx.scale(scale, scale).unwrap();
+
+    let left = -PANEL_WIDTH / 2.0;
+    let top = -PANEL_HEIGHT / 2.0;
+
+    ctx.set_global_alpha(0.7);
+    ctx.set_fill_style(&theme.overlay_background.to_css().into());
+    ctx.fill_rect(left, top, PANEL_WIDTH, PANEL_HEIGHT);
+    ctx.set_global_alpha(1.0);
+
+    ctx.set_line_width(3.0);
+    ctx.set_stroke_style(&theme.overlay_border.to_css().into());
+    ctx.stroke_rect(left, top, PANEL_WIDTH, PANEL_HEIGHT);
+
+    ctx.set_fill_style(&theme.overlay_text.to_css().into());
+
+    // title
+    let title_font =
+        get_canvas_font_string(Some(70), Some(theme.title_font), Some("px"), Some("bold"));
+    ctx.set_font(&title_font);
+
+    let title_size = measure_canvas_text(ctx, panel.title).unwrap();
+    ctx.fill_text(
+        panel.title,
+        -title_size.width / 2.0,
+        title_size.height / 2.0,
+    )
+    .unwrap();
+
+    // hints
+    let hint_font = get_canvas_font_string(Some(20), Some(theme.hint_font), None, None);
+    ctx.set_font(&hint_font);
+
+    let controls_hint_size = measure_canvas_text(ctx, panel.controls_hint).unwrap();
+    ctx.fill_text(
+        panel.controls_hint,
+        -controls_hint_size.width / 2.0,
+        top + PANEL_PADDING + controls_hint_size.height,
+    )
+    .unwrap();
+
+    let start_hint_size = measure_canvas_text(ctx, panel.start_hint).unwrap();
+    ctx.fill_text(
+        panel.start_hint,
+        -start_hint_size.width / 2.0,
+        -top - PANEL_PADDING,
+    )
+    .unwrap();
+
+    ctx.restore();
+}
@@ -0,0 +1,118 @@
+use wasm_bindgen::JsCast;
+
+use crate::{
+    helpers::cube_net::{get_cube_net, get_net_cell_rect, NetSide, NET_COLS, NET_ROWS},
+    models::{CubePosition, CubeSideType, GameState},
+};
+
+use super::side_painter::paint_objects;
+
+// size of one side on minimap, in pixels
+const MINIMAP_SIDE_SIZE: f64 = 60.0;
+
+// space between sides, so edges of sides can be told apart
+const MINIMAP_SIDE_GAP: f64 = 1.0;
+
+pub fn init_minimap_drawer(state: &mut GameState) {
+    let document = web_sys::window().unwrap().document().unwrap();
+
+    let canvas = document
+        .query_selector(".minimap")
+        .unwrap()
+        .expect("failed to find minimap element")
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+
+    canvas.set_width((NET_COLS as f64 * MINIMAP_SIDE_SIZE) as u32);
+    canvas.set_height((NET_ROWS as f64 * MINIMAP_SIDE_SIZE) as u32);
+
+    let ctx = canvas
+        .get_context("2d")
+        .unwrap()
+        .expect("failed to get 2D render context")
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .unwrap();
+
+    state.scene.minimap = Some(canvas);
+    state.scene.minimap_ctx = Some(ctx);
+}
+
+/// Draws all sides unfolded around the side with snake head, so sides out of
+/// sight can be seen too. It's repainted only when something on the cube has
+/// changed, so it has to run before the scene is drawn, while redraw flags of
+/// the cube and its sides are still set
+pub fn draw_minimap_loop(state: &mut GameState) {
+    let scene = &state.scene;
+    let canvas = scene.minimap.as_ref().unwrap();
+    let ctx = scene.minimap_ctx.as_ref().unwrap();
+
+    // shown minimap may be stale, since it's not painted while hidden
+    let is_shown = canvas.hidden() && !scene.is_minimap_hidden;
+
+    if canvas.hidden() != scene.is_minimap_hidden {
+        canvas.set_hidden(scene.is_minimap_hidden);
+    }
+    if scene.is_minimap_hidden {
+        return;
+    }
+
+    let cube = &scene.cube;
+    let is_changed = cube.needs_redraw
+        || cube
+            .sides
+            .values()
+            .any(|side| side.needs_redraw || !side.dirty_cells.is_empty());
+
+    if !is_changed && !is_shown {
+        return;
+    }
+
+    let theme = scene.theme();
+    let grid = &scene.cube.grid;
+
+    let head = state.snake.parts.front();
+    let center = head.map_or(CubeSideType::Front, |head| head.side);
+    let net = get_cube_net(center);
+
+    ctx.clear_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
+
+    for net_side in &net {
+        ctx.set_fill_style(&theme.background.to_css().into());
+        ctx.fill_rect(
+            net_side.col as f64 * MINIMAP_SIDE_SIZE + MINIMAP_SIDE_GAP,
+            net_side.row as f64 * MINIMAP_SIDE_SIZE + MINIMAP_SIDE_GAP,
+            MINIMAP_SIDE_SIZE - MINIMAP_SIDE_GAP * 2.0,
+            MINIMAP_SIDE_SIZE - MINIMAP_SIDE_GAP * 2.0,
+        );
+    }
+
+    let get_rect = |pos: &CubePosition| {
+        let net_side: &NetSide = net.iter().find(|s| s.side == pos.side).unwrap();
+        let (pos, size) = get_net_cell_rect(net_side, pos, grid);
+
+        (
+            pos.x * MINIMAP_SIDE_SIZE,
+            pos.y * MINIMAP_SIDE_SIZE,
+            size.width * MINIMAP_SIDE_SIZE,
+            size.height * MINIMAP_SIDE_SIZE,
+        )
+    };
+
+    paint_objects(state, |pos, cell| {
+        let (x, y, width, height) = get_rect(pos);
+
+        ctx.set_global_alpha(cell.alpha as f64);
+        ctx.set_fill_style(&cell.color.to_css().into());
+        ctx.fill_rect(x, y, width, height);
+    });
+    ctx.set_global_alpha(1.0);
+
+    // cells are only a few pixels wide, so head is circled to be found quickly
+    if let Some(head) = head {
+        let (x, y, width, height) = get_rect(head);
+
+        ctx.set_line_width(2.0);
+        ctx.set_stroke_style(&theme.overlay_border.to_css().into());
+        ctx.stroke_rect(x - 2.0, y - 2.0, width + 4.0, height + 4.0);
+    }
+}
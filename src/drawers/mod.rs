@@ -1,3 +1,13 @@
 pub mod cube_drawer;
 pub mod cube_side_drawer;
+pub mod export_drawer;
+pub mod hud_drawer;
+pub mod minimap_drawer;
+pub mod objects_drawer;
+pub mod particles_drawer;
+pub mod perf_hud_drawer;
+pub mod post_drawer;
+pub mod renderer;
 pub mod scene_drawer;
+pub mod side_painter;
+pub mod software_renderer;
@@ -0,0 +1,239 @@
+use crate::helpers::{
+    graphics_math::{cross, dot, normalize, subtract_vectors, Vec3},
+    random::Random,
+};
+
+// meshes are built in cell units: cell spans from -0.5 to 0.5 along X (column)
+// and Y (row) axes, and Z axis points away from cube side
+
+// vertex is position followed by normal
+pub const MESH_VERTEX_SIZE: usize = 6;
+
+const SNAKE_PART_HALF_SIZE: f32 = 0.45;
+const SNAKE_PART_HEIGHT: f32 = 0.4;
+
+// body tube radius, head center is at the same height
+pub const SNAKE_TUBE_RADIUS: f32 = 0.32;
+const SNAKE_HEAD_CENTER: Vec3 = [0.06, 0.0, SNAKE_TUBE_RADIUS];
+const SNAKE_HEAD_RADII: Vec3 = [0.42, 0.4, 0.34];
+const SNAKE_EYE_RADIUS: f32 = 0.09;
+
+const APPLE_RADIUS: f32 = 0.35;
+
+const STONE_RADIUS: f32 = 0.45;
+const STONE_HEIGHT_SCALE: f32 = 0.6;
+const STONE_SEED: u32 = 7;
+
+/// Raised box covering most of the cell
+pub fn create_snake_part_mesh() -> Vec<f32> {
+    let mut vertices = Vec::new();
+
+    let (s, h) = (SNAKE_PART_HALF_SIZE, SNAKE_PART_HEIGHT);
+    let center = [0.0, 0.0, h / 2.0];
+
+    // each face as two triangles with flat normal
+    let quads: [[Vec3; 4]; 5] = [
+        // top
+        [[-s, -s, h], [s, -s, h], [s, s, h], [-s, s, h]],
+        // sides
+        [[-s, -s, 0.0], [s, -s, 0.0], [s, -s, h], [-s, -s, h]],
+        [[s, -s, 0.0], [s, s, 0.0], [s, s, h], [s, -s, h]],
+        [[s, s, 0.0], [-s, s, 0.0], [-s, s, h], [s, s, h]],
+        [[-s, s, 0.0], [-s, -s, 0.0], [-s, -s, h], [-s, s, h]],
+    ];
+
+    // bottom is never visible, since it lies on cube side
+    for [a, b, c, d] in quads {
+        push_flat_triangle(&mut vertices, center, a, b, c);
+        push_flat_triangle(&mut vertices, center, a, c, d);
+    }
+
+    vertices
+}
+
+/// UV sphere laying on the cell
+pub fn create_apple_mesh() -> Vec<f32> {
+    let mut vertices = Vec::new();
+    push_sphere(&mut vertices, [0.0, 0.0, APPLE_RADIUS], [APPLE_RADIUS; 3]);
+
+    vertices
+}
+
+/// Ellipsoid stretched along X axis, which is direction snake moves to. Its
+/// center is at the height of body tube, so head covers the tube end
+pub fn create_snake_head_mesh() -> Vec<f32> {
+    let mut vertices = Vec::new();
+    push_sphere(&mut vertices, SNAKE_HEAD_CENTER, SNAKE_HEAD_RADII);
+
+    vertices
+}
+
+/// Eyes on the front of snake head and arrow on its top, pointing where snake
+/// moves
+pub fn create_snake_eyes_mesh() -> Vec<f32> {
+    let mut vertices = Vec::new();
+    let [cx, _, cz] = SNAKE_HEAD_CENTER;
+    let [a, b, c] = SNAKE_HEAD_RADII;
+
+    // eyes are half sunk into the head
+    for side in [-1.0, 1.0] {
+        let direction = normalize([0.6, side * 0.55, 0.6]);
+        let center = [
+            cx + direction[0] * a * 0.9,
+            direction[1] * b * 0.9,
+            cz + direction[2] * c * 0.9,
+        ];
+        push_sphere(&mut vertices, center, [SNAKE_EYE_RADIUS; 3]);
+    }
+
+    // arrow lies right above head surface
+    let get_top_point = |x: f32, y: f32| -> Vec3 {
+        let height = 1.0 - ((x - cx) / a).powi(2) - (y / b).powi(2);
+        [x, y, cz + c * height.max(0.0).sqrt() + 0.01]
+    };
+    let tip = get_top_point(cx + a * 0.6, 0.0);
+    let left = get_top_point(cx - a * 0.1, b * 0.35);
+    let right = get_top_point(cx - a * 0.1, -b * 0.35);
+    push_flat_triangle(&mut vertices, [tip[0], 0.0, 0.0], tip, left, right);
+
+    vertices
+}
+
+/// Flattened icosahedron with uneven vertices, half buried into cube side
+pub fn create_stone_mesh() -> Vec<f32> {
+    let t = (1.0 + 5f32.sqrt()) / 2.0;
+
+    #[rustfmt::skip]
+    let points: [Vec3; 12] = [
+        [-1.0, t, 0.0], [1.0, t, 0.0], [-1.0, -t, 0.0], [1.0, -t, 0.0],
+        [0.0, -1.0, t], [0.0, 1.0, t], [0.0, -1.0, -t], [0.0, 1.0, -t],
+        [t, 0.0, -1.0], [t, 0.0, 1.0], [-t, 0.0, -1.0], [-t, 0.0, 1.0],
+    ];
+
+    #[rustfmt::skip]
+    const FACES: [[usize; 3]; 20] = [
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    // same seed, so all stones look the same and mesh is built once
+    let mut random = Random::new(STONE_SEED);
+    let center = [0.0, 0.0, STONE_RADIUS * STONE_HEIGHT_SCALE / 3.0];
+
+    let points = points.map(|point| {
+        let radius = STONE_RADIUS * (0.8 + 0.2 * random.next_f64() as f32);
+        let point = normalize(point);
+
+        [
+            center[0] + point[0] * radius,
+            center[1] + point[1] * radius,
+            center[2] + point[2] * radius * STONE_HEIGHT_SCALE,
+        ]
+    });
+
+    let mut vertices = Vec::new();
+    for [a, b, c] in FACES {
+        push_flat_triangle(&mut vertices, center, points[a], points[b], points[c]);
+    }
+
+    vertices
+}
+
+// UV sphere scaled by radii along each axis
+fn push_sphere(vertices: &mut Vec<f32>, center: Vec3, radii: Vec3) {
+    const SEGMENTS: usize = 12;
+    const RINGS: usize = 8;
+
+    let get_point = |ring: usize, segment: usize| -> Vec3 {
+        let theta = std::f32::consts::PI * ring as f32 / RINGS as f32;
+        let phi = 2.0 * std::f32::consts::PI * segment as f32 / SEGMENTS as f32;
+
+        [
+            center[0] + radii[0] * theta.sin() * phi.cos(),
+            center[1] + radii[1] * theta.sin() * phi.sin(),
+            center[2] + radii[2] * theta.cos(),
+        ]
+    };
+
+    for ring in 0..RINGS {
+        for segment in 0..SEGMENTS {
+            let a = get_point(ring, segment);
+            let b = get_point(ring + 1, segment);
+            let c = get_point(ring + 1, segment + 1);
+            let d = get_point(ring, segment + 1);
+
+            // triangles degenerate at poles, skip them
+            if ring != RINGS - 1 {
+                push_smooth_triangle(vertices, center, a, b, c);
+            }
+            if ring != 0 {
+                push_smooth_triangle(vertices, center, a, c, d);
+            }
+        }
+    }
+}
+
+// all meshes are convex-ish around their center, so triangles are turned to
+// face away from the center. this keeps counter-clockwise front faces for
+// backface culling without hand-checking vertex order
+fn orient_triangle(center: Vec3, a: Vec3, b: Vec3, c: Vec3) -> (Vec3, Vec3, Vec3, Vec3) {
+    let normal = normalize(cross(subtract_vectors(b, a), subtract_vectors(c, a)));
+    let outward = subtract_vectors(a, center);
+
+    if dot(normal, outward) < 0.0 {
+        (a, c, b, [-normal[0], -normal[1], -normal[2]])
+    } else {
+        (a, b, c, normal)
+    }
+}
+
+fn push_flat_triangle(vertices: &mut Vec<f32>, center: Vec3, a: Vec3, b: Vec3, c: Vec3) {
+    let (a, b, c, normal) = orient_triangle(center, a, b, c);
+
+    for point in [a, b, c] {
+        vertices.extend_from_slice(&point);
+        vertices.extend_from_slice(&normal);
+    }
+}
+
+fn push_smooth_triangle(vertices: &mut Vec<f32>, center: Vec3, a: Vec3, b: Vec3, c: Vec3) {
+    let (a, b, c, _) = orient_triangle(center, a, b, c);
+
+    for point in [a, b, c] {
+        vertices.extend_from_slice(&point);
+        vertices.extend_from_slice(&normalize(subtract_vectors(point, center)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meshes_are_valid() {
+        for vertices in [
+            create_snake_part_mesh(),
+            create_snake_head_mesh(),
+            create_snake_eyes_mesh(),
+            create_apple_mesh(),
+            create_stone_mesh(),
+        ] {
+            assert!(!vertices.is_empty());
+            assert!(vertices
+                .chunks_exact(MESH_VERTEX_SIZE * 3)
+                .remainder()
+                .is_empty());
+
+            for vertex in vertices.chunks(MESH_VERTEX_SIZE) {
+                // fits into the cell
+                assert!(vertex[0].abs() <= 0.5 && vertex[1].abs() <= 0.5);
+                assert!(vertex[2] >= -0.5 && vertex[2] <= 1.0);
+
+                let normal = [vertex[3], vertex[4], vertex[5]];
+                assert!((dot(normal, normal) - 1.0).abs() < 1e-5);
+            }
+        }
+    }
+}
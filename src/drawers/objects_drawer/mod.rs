@@ -0,0 +1,406 @@
+pub mod geometry;
+mod tube;
+
+use std::collections::HashSet;
+
+use web_sys::WebGlRenderingContext as GL;
+
+use crate::{
+    helpers::{
+        cube::{
+            get_cube_side_axes, get_interpolated_position_3d, get_position_3d_for_cube_position,
+        },
+        graphics_math::{Matrix4, Vec3},
+        webgl::{get_attribute_location, get_uniform_location, init_program, init_shader},
+    },
+    models::{
+        CubePosition, CubeSideType, GameState, Grid, MeshRange, ObjectMeshType, Point3D, Snake,
+    },
+};
+
+use geometry::{
+    create_apple_mesh, create_snake_eyes_mesh, create_snake_head_mesh, create_snake_part_mesh,
+    create_stone_mesh, MESH_VERTEX_SIZE,
+};
+use tube::create_snake_tube;
+
+const VERTEX_SHADER_SRC: &str = include_str!("shaders/vertex.glsl");
+const FRAGMENT_SHADER_SRC: &str = include_str!("shaders/fragment.glsl");
+
+// instance is offset, three axes and color
+const INSTANCE_SIZE: usize = 3 + 3 * 3 + 4;
+const FLOAT_SIZE: i32 = 4;
+
+const GHOST_ALPHA: f32 = 0.3;
+
+// objects of the same mesh and color drawn with single draw call. placements
+// are mesh centers with axes of the mesh, which are usually axes of the side
+// they stand on
+struct InstanceBatch {
+    mesh_type: ObjectMeshType,
+    color: [f32; 4],
+    placements: Vec<(Vec3, [Vec3; 3])>,
+}
+
+// snake bodies of the same color. tube vertices are already placed on cube,
+// so they are drawn as single instance with identity axes
+struct TubeBatch {
+    color: [f32; 4],
+    vertices: Vec<f32>,
+}
+
+pub fn init_objects_drawer(state: &mut GameState) {
+    let scene = &mut state.scene;
+    let ctx = scene.ctx.as_ref().unwrap();
+    let objects = &mut scene.objects;
+
+    // objects are painted on side textures if instancing is not supported
+    if !ctx.supports_instancing() {
+        return;
+    }
+
+    let vertex_shader = init_shader(ctx, GL::VERTEX_SHADER, VERTEX_SHADER_SRC)
+        .expect("failed to init objects vertex shader");
+    let fragment_shader = init_shader(ctx, GL::FRAGMENT_SHADER, FRAGMENT_SHADER_SRC)
+        .expect("failed to init objects fragment shader");
+
+    let program = init_program(ctx, [vertex_shader, fragment_shader])
+        .expect("failed to init objects program");
+
+    // lookup locations for attributes/uniforms
+    let locations = &mut objects.attribute_locations;
+    locations.position = get_attribute_location(ctx, &program, "a_position").unwrap();
+    locations.normal = get_attribute_location(ctx, &program, "a_normal").unwrap();
+    locations.offset = get_attribute_location(ctx, &program, "a_offset").unwrap();
+    locations.axis_x = get_attribute_location(ctx, &program, "a_axis_x").unwrap();
+    locations.axis_y = get_attribute_location(ctx, &program, "a_axis_y").unwrap();
+    locations.axis_z = get_attribute_location(ctx, &program, "a_axis_z").unwrap();
+    locations.color = get_attribute_location(ctx, &program, "a_color").unwrap();
+
+    objects.matrix_uniform_location =
+        Some(get_uniform_location(ctx, &program, "u_matrix").unwrap());
+
+    // put all meshes into single vertex buffer
+    let mut vertices: Vec<f32> = Vec::new();
+    for (mesh_type, mesh) in [
+        (ObjectMeshType::SnakePart, create_snake_part_mesh()),
+        (ObjectMeshType::SnakeHead, create_snake_head_mesh()),
+        (ObjectMeshType::SnakeEyes, create_snake_eyes_mesh()),
+        (ObjectMeshType::Apple, create_apple_mesh()),
+        (ObjectMeshType::Stone, create_stone_mesh()),
+    ] {
+        let range = MeshRange {
+            first: (vertices.len() / MESH_VERTEX_SIZE) as i32,
+            count: (mesh.len() / MESH_VERTEX_SIZE) as i32,
+        };
+
+        objects.mesh_ranges.insert(mesh_type, range);
+        vertices.extend(mesh);
+    }
+
+    let vertex_buffer = ctx.create_buffer();
+    ctx.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
+    ctx.buffer_data_with_array_buffer_view(
+        GL::ARRAY_BUFFER,
+        &js_sys::Float32Array::from(&vertices[..]),
+        GL::STATIC_DRAW,
+    );
+
+    objects.vertex_buffer = vertex_buffer;
+    objects.instance_buffer = ctx.create_buffer();
+    objects.tube_buffer = ctx.create_buffer();
+    objects.program = Some(program);
+
+    // side textures will only have grid from now on
+    let cube = &mut scene.cube;
+    cube.objects_as_meshes = true;
+    cube.sides
+        .values_mut()
+        .for_each(|side| side.needs_redraw = true);
+}
+
+pub fn draw_objects(state: &mut GameState, matrix: Matrix4) {
+    let scene = &state.scene;
+    let ctx = scene.ctx.as_ref().unwrap();
+    let objects = &scene.objects;
+    let locations = &objects.attribute_locations;
+
+    let (batches, tube_batches) = get_instance_batches(state);
+    let grid = &scene.cube.grid;
+
+    // upload instances of all batches at once, tubes go first
+    let mut instances: Vec<f32> = Vec::new();
+    let mut tube_vertices: Vec<f32> = Vec::new();
+
+    for batch in &tube_batches {
+        instances.extend_from_slice(&[0.0; 3]);
+        instances.extend_from_slice(&[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+        instances.extend_from_slice(&batch.color);
+        tube_vertices.extend_from_slice(&batch.vertices);
+    }
+
+    for batch in &batches {
+        for (center, axes) in &batch.placements {
+            push_instance(&mut instances, center, axes, grid, batch.color);
+        }
+    }
+
+    if instances.is_empty() {
+        return;
+    }
+
+    ctx.use_program(objects.program.as_ref());
+    ctx.uniform_matrix4fv_with_f32_array(objects.matrix_uniform_location.as_ref(), false, &matrix);
+
+    ctx.bind_buffer(GL::ARRAY_BUFFER, objects.instance_buffer.as_ref());
+    ctx.buffer_data_with_array_buffer_view(
+        GL::ARRAY_BUFFER,
+        &js_sys::Float32Array::from(&instances[..]),
+        GL::DYNAMIC_DRAW,
+    );
+
+    ctx.bind_buffer(GL::ARRAY_BUFFER, objects.tube_buffer.as_ref());
+    ctx.buffer_data_with_array_buffer_view(
+        GL::ARRAY_BUFFER,
+        &js_sys::Float32Array::from(&tube_vertices[..]),
+        GL::DYNAMIC_DRAW,
+    );
+
+    ctx.enable_vertex_attrib_array(locations.position);
+    ctx.enable_vertex_attrib_array(locations.normal);
+
+    // instance attributes advance once per instance instead of once per vertex
+    let instance_attributes = [
+        (locations.offset, 3),
+        (locations.axis_x, 3),
+        (locations.axis_y, 3),
+        (locations.axis_z, 3),
+        (locations.color, 4),
+    ];
+
+    for (location, _) in instance_attributes {
+        ctx.enable_vertex_attrib_array(location);
+        ctx.vertex_attrib_divisor(location, 1);
+    }
+
+    // point instance attributes to the first instance of the batch
+    let point_to_instance = |first_instance: i32| {
+        ctx.bind_buffer(GL::ARRAY_BUFFER, objects.instance_buffer.as_ref());
+
+        let mut offset = first_instance * INSTANCE_SIZE as i32 * FLOAT_SIZE;
+        for (location, size) in instance_attributes {
+            ctx.vertex_attrib_pointer_with_i32(
+                location,
+                size,
+                GL::FLOAT,
+                false,
+                INSTANCE_SIZE as i32 * FLOAT_SIZE,
+                offset,
+            );
+            offset += size * FLOAT_SIZE;
+        }
+    };
+
+    let mut first_instance = 0;
+    let mut draw_calls = 0;
+
+    // tubes are opaque, so they go before translucent batches
+    point_vertex_attributes(state, objects.tube_buffer.as_ref());
+    let mut first_vertex = 0;
+    for batch in &tube_batches {
+        let count = (batch.vertices.len() / MESH_VERTEX_SIZE) as i32;
+
+        point_to_instance(first_instance);
+        ctx.draw_arrays_instanced(GL::TRIANGLES, first_vertex, count, 1);
+        draw_calls += 1;
+
+        first_vertex += count;
+        first_instance += 1;
+    }
+
+    point_vertex_attributes(state, objects.vertex_buffer.as_ref());
+    for batch in &batches {
+        let instances_count = batch.placements.len() as i32;
+        if instances_count == 0 {
+            continue;
+        }
+
+        point_to_instance(first_instance);
+
+        // translucent batches go last, so they blend with everything else
+        let is_translucent = batch.color[3] < 1.0;
+        if is_translucent {
+            ctx.enable(GL::BLEND);
+            ctx.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+            ctx.depth_mask(false);
+        }
+
+        let range = objects.mesh_ranges[&batch.mesh_type];
+        ctx.draw_arrays_instanced(GL::TRIANGLES, range.first, range.count, instances_count);
+        draw_calls += 1;
+
+        if is_translucent {
+            ctx.disable(GL::BLEND);
+            ctx.depth_mask(true);
+        }
+
+        first_instance += instances_count;
+    }
+
+    // divisors and enabled arrays are context wide, so restore them for cube
+    for (location, _) in instance_attributes {
+        ctx.vertex_attrib_divisor(location, 0);
+        ctx.disable_vertex_attrib_array(location);
+    }
+    ctx.disable_vertex_attrib_array(locations.position);
+    ctx.disable_vertex_attrib_array(locations.normal);
+
+    state.scene.stats.draw_calls += draw_calls;
+}
+
+// define how to extract mesh vertices from given buffer
+fn point_vertex_attributes(state: &GameState, buffer: Option<&web_sys::WebGlBuffer>) {
+    const VERTEX_STRIDE: i32 = MESH_VERTEX_SIZE as i32 * FLOAT_SIZE;
+
+    let ctx = state.scene.ctx.as_ref().unwrap();
+    let locations = &state.scene.objects.attribute_locations;
+
+    ctx.bind_buffer(GL::ARRAY_BUFFER, buffer);
+    ctx.vertex_attrib_pointer_with_i32(locations.position, 3, GL::FLOAT, false, VERTEX_STRIDE, 0);
+    ctx.vertex_attrib_pointer_with_i32(
+        locations.normal,
+        3,
+        GL::FLOAT,
+        false,
+        VERTEX_STRIDE,
+        3 * FLOAT_SIZE,
+    );
+}
+
+fn get_instance_batches(state: &GameState) -> (Vec<InstanceBatch>, Vec<TubeBatch>) {
+    let grid = &state.scene.cube.grid;
+    let theme = state.scene.theme();
+    let progress = state.move_progress;
+
+    let rivals = state
+        .rivals
+        .iter()
+        .filter_map(|rival| create_snake_tube(rival, grid, progress));
+    let snake = create_snake_tube(&state.snake, grid, progress);
+
+    // head is separate batch, to have its own color
+    let mut head_placements = vec![];
+    let mut rival_head_placements = vec![];
+    let mut body_vertices = vec![];
+    let mut rival_vertices = vec![];
+
+    if let Some(tube) = snake {
+        head_placements.push(tube.head);
+        body_vertices = tube.vertices;
+    }
+    for tube in rivals {
+        rival_head_placements.push(tube.head);
+        rival_vertices.extend(tube.vertices);
+    }
+
+    let eye_placements = [&head_placements[..], &rival_head_placements[..]].concat();
+
+    let mut batches = vec![
+        InstanceBatch {
+            mesh_type: ObjectMeshType::SnakeHead,
+            color: theme.snake_head.to_gl(1.0),
+            placements: head_placements,
+        },
+        InstanceBatch {
+            mesh_type: ObjectMeshType::SnakeHead,
+            color: theme.rival.to_gl(1.0),
+            placements: rival_head_placements,
+        },
+        InstanceBatch {
+            mesh_type: ObjectMeshType::SnakeEyes,
+            color: theme.background.to_gl(1.0),
+            placements: eye_placements,
+        },
+        InstanceBatch {
+            mesh_type: ObjectMeshType::Apple,
+            color: theme.apple.to_gl(1.0),
+            placements: get_cell_placements(&state.apples, grid),
+        },
+        InstanceBatch {
+            mesh_type: ObjectMeshType::Stone,
+            color: theme.stone.to_gl(1.0),
+            placements: get_cell_placements(&state.stones, grid),
+        },
+    ];
+
+    // ghost stays made of separate parts, so it doesn't look like a rival
+    if let Some(ghost) = &state.ghost {
+        batches.push(InstanceBatch {
+            mesh_type: ObjectMeshType::SnakePart,
+            color: theme.snake_body.to_gl(GHOST_ALPHA),
+            placements: get_snake_placements(&ghost.state.snake, grid, progress),
+        });
+    }
+
+    let tube_batches = vec![
+        TubeBatch {
+            color: theme.snake_body.to_gl(1.0),
+            vertices: body_vertices,
+        },
+        TubeBatch {
+            color: theme.rival.to_gl(1.0),
+            vertices: rival_vertices,
+        },
+    ]
+    .into_iter()
+    .filter(|batch| !batch.vertices.is_empty())
+    .collect();
+
+    (batches, tube_batches)
+}
+
+fn get_cell_placements(cells: &HashSet<CubePosition>, grid: &Grid) -> Vec<(Vec3, [Vec3; 3])> {
+    cells
+        .iter()
+        .map(|pos| get_placement(get_position_3d_for_cube_position(pos, grid), pos.side))
+        .collect()
+}
+
+// each part is drawn between its previous and current cells
+fn get_snake_placements(snake: &Snake, grid: &Grid, progress: f64) -> Vec<(Vec3, [Vec3; 3])> {
+    snake
+        .parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| {
+            let prev_part = snake.prev_parts.get(i).unwrap_or(part);
+            let (center, side) = get_interpolated_position_3d(prev_part, part, grid, progress);
+            get_placement(center, side)
+        })
+        .collect()
+}
+
+fn get_placement(center: Point3D, side: CubeSideType) -> (Vec3, [Vec3; 3]) {
+    (
+        [center.x as f32, center.y as f32, center.z as f32],
+        get_cube_side_axes(side),
+    )
+}
+
+fn push_instance(
+    instances: &mut Vec<f32>,
+    center: &Vec3,
+    [axis_x, axis_y, axis_z]: &[Vec3; 3],
+    grid: &Grid,
+    color: [f32; 4],
+) {
+    // scale meshes from cell units to cube units
+    let cell_width = 1.0 / grid.cols_count as f32;
+    let cell_height = 1.0 / grid.rows_count as f32;
+
+    instances.extend_from_slice(center);
+    instances.extend(axis_x.iter().map(|v| v * cell_width));
+    instances.extend(axis_y.iter().map(|v| v * cell_height));
+    instances.extend(axis_z.iter().map(|v| v * cell_width));
+    instances.extend_from_slice(&color);
+}
@@ -0,0 +1,8 @@
+precision mediump float;
+
+varying vec4 v_color;
+varying float v_shade;
+
+void main() {
+  gl_FragColor = vec4(v_color.rgb * v_shade, v_color.a);
+}
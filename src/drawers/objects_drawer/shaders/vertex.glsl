@@ -0,0 +1,28 @@
+attribute vec3 a_position;
+attribute vec3 a_normal;
+
+// per instance attributes. axes place mesh onto cube side and scale it from
+// cell units to cube units
+attribute vec3 a_offset;
+attribute vec3 a_axis_x;
+attribute vec3 a_axis_y;
+attribute vec3 a_axis_z;
+attribute vec4 a_color;
+
+uniform mat4 u_matrix;
+
+varying vec4 v_color;
+varying float v_shade;
+
+void main() {
+  mat3 basis = mat3(a_axis_x, a_axis_y, a_axis_z);
+
+  gl_Position = u_matrix * vec4(a_offset + basis * a_position, 1.0);
+
+  // light falls straight onto cube side the object stands on, so object
+  // relief is readable from any camera angle
+  vec3 normal = normalize(basis * a_normal);
+  v_shade = 0.5 + 0.5 * max(dot(normal, normalize(a_axis_z)), 0.0);
+
+  v_color = a_color;
+}
@@ -0,0 +1,355 @@
+use std::f32::consts::PI;
+
+use crate::{
+    helpers::{
+        cube::{
+            get_cube_side_axes, get_interpolated_position_3d, get_next_cube_position_and_direction,
+            get_position_3d_for_cube_position,
+        },
+        graphics_math::{cross, dot, normalize, subtract_vectors, Vec3},
+    },
+    models::{CubeSideType, Grid, Point3D, Snake},
+};
+
+use super::geometry::SNAKE_TUBE_RADIUS;
+
+const RING_SEGMENTS: usize = 10;
+
+// each iteration cuts corners of the path, so turns and edges get round
+const SMOOTH_ITERATIONS: usize = 2;
+
+// cells from the tail tip, along which body gets thinner
+const TAIL_TAPER_LENGTH: f32 = 3.0;
+
+/// Point of tube center line with normal of cube surface under it
+#[derive(Debug, Clone, Copy)]
+struct PathPoint {
+    position: Vec3,
+    normal: Vec3,
+}
+
+/// Cross-section of the tube. Axes are forward (to the head), left and up
+#[derive(Debug, Clone, Copy)]
+pub struct TubeRing {
+    pub center: Vec3,
+    pub axes: [Vec3; 3],
+    pub radius: f32,
+}
+
+/// Snake body as a single mesh. Vertices are in cube units, while normals
+/// are in axes of the ring they belong to, so shading matches other objects,
+/// which are lit from the side they stand on
+pub struct SnakeTube {
+    pub vertices: Vec<f32>,
+
+    // surface point under the head, and head axes
+    pub head: (Vec3, [Vec3; 3]),
+}
+
+pub fn create_snake_tube(snake: &Snake, grid: &Grid, progress: f64) -> Option<SnakeTube> {
+    let rings = get_tube_rings(snake, grid, progress);
+    let head_ring = rings.first()?;
+
+    let mut vertices = Vec::new();
+    for pair in rings.windows(2) {
+        push_tube_segment(&mut vertices, &pair[0], &pair[1]);
+    }
+
+    let [_, _, up] = head_ring.axes;
+    let head_lift = SNAKE_TUBE_RADIUS * get_cell_size(grid);
+
+    Some(SnakeTube {
+        vertices,
+        head: (add(head_ring.center, scale(up, -head_lift)), head_ring.axes),
+    })
+}
+
+/// Rings from head to tail. Tube rests on cube surface, getting thinner to
+/// the tail, and bends over cube edges
+pub fn get_tube_rings(snake: &Snake, grid: &Grid, progress: f64) -> Vec<TubeRing> {
+    let lift = SNAKE_TUBE_RADIUS * get_cell_size(grid);
+
+    let mut path = get_center_line(snake, grid, progress, lift);
+    for _ in 0..SMOOTH_ITERATIONS {
+        path = smooth_path(&path);
+    }
+
+    // distance of each point from the head
+    let mut distances = vec![0.0];
+    for pair in path.windows(2) {
+        let last = distances[distances.len() - 1];
+        distances.push(last + length(subtract_vectors(pair[1].position, pair[0].position)));
+    }
+
+    let total_length = distances[distances.len() - 1];
+    let taper_length = (TAIL_TAPER_LENGTH * get_cell_size(grid)).min(total_length * 0.6);
+
+    path.iter()
+        .enumerate()
+        .map(|(i, point)| {
+            let forward = if path.len() > 1 {
+                let prev = path[i.saturating_sub(1)].position;
+                let next = path[(i + 1).min(path.len() - 1)].position;
+                subtract_vectors(prev, next)
+            } else {
+                get_moving_direction(snake, grid)
+            };
+
+            // keep axes perpendicular, since path slopes near edges
+            let forward = normalize(forward);
+            let up = normalize(subtract_vectors(
+                point.normal,
+                scale(forward, dot(point.normal, forward)),
+            ));
+            let left = cross(up, forward);
+
+            let to_tail = total_length - distances[i];
+            let radius = if taper_length > 0.0 {
+                lift * (to_tail / taper_length).min(1.0).sqrt()
+            } else {
+                lift
+            };
+
+            // thinner rings go down, so the whole tube lies on surface
+            TubeRing {
+                center: add(point.position, scale(up, radius - lift)),
+                axes: [forward, left, up],
+                radius,
+            }
+        })
+        .collect()
+}
+
+// head and tail glide between cells, while parts in between stay in cell
+// centers. path goes through cube edge between cells on different sides, and
+// is lifted above the surface
+fn get_center_line(snake: &Snake, grid: &Grid, progress: f64, lift: f32) -> Vec<PathPoint> {
+    let mut surface_points: Vec<(Point3D, CubeSideType)> = Vec::new();
+    let last = snake.parts.len().saturating_sub(1);
+
+    for (i, part) in snake.parts.iter().enumerate() {
+        if i != 0 {
+            surface_points.push((get_position_3d_for_cube_position(part, grid), part.side));
+        }
+        if i == 0 || i == last {
+            let prev_part = snake.prev_parts.get(i).unwrap_or(part);
+            surface_points.push(get_interpolated_position_3d(
+                prev_part, part, grid, progress,
+            ));
+        }
+    }
+
+    let mut path: Vec<PathPoint> = Vec::new();
+    let mut push_point = |position: Vec3, normal: Vec3| {
+        let position = add(position, scale(normal, lift));
+
+        // parts which haven't moved yet are in the same place
+        let is_duplicate = path.last().is_some_and(|last: &PathPoint| {
+            length(subtract_vectors(last.position, position)) < 1e-6
+        });
+        if !is_duplicate {
+            path.push(PathPoint { position, normal });
+        }
+    };
+
+    for (i, (point, side)) in surface_points.iter().enumerate() {
+        let point = to_vec3(point);
+        let [_, _, normal] = get_cube_side_axes(*side);
+
+        if let Some((prev_point, prev_side)) = i.checked_sub(1).map(|i| surface_points[i]) {
+            let [_, _, prev_normal] = get_cube_side_axes(prev_side);
+
+            // the same way as in interpolation, edge is where the line from
+            // previous point straight to the plane of this side crosses it
+            if prev_side != *side && dot(prev_normal, normal) == 0.0 {
+                let prev_point = to_vec3(&prev_point);
+                let edge = add(prev_point, scale(normal, 0.5 - dot(prev_point, normal)));
+
+                push_point(edge, prev_normal);
+                push_point(edge, normal);
+            }
+        }
+
+        push_point(point, normal);
+    }
+
+    path
+}
+
+// chaikin corner cutting, which keeps both ends in place
+fn smooth_path(path: &[PathPoint]) -> Vec<PathPoint> {
+    if path.len() < 3 {
+        return path.to_vec();
+    }
+
+    let lerp = |a: &PathPoint, b: &PathPoint, t: f32| PathPoint {
+        position: add(scale(a.position, 1.0 - t), scale(b.position, t)),
+        normal: normalize(add(scale(a.normal, 1.0 - t), scale(b.normal, t))),
+    };
+
+    let mut smooth = vec![path[0]];
+    for pair in path.windows(2) {
+        smooth.push(lerp(&pair[0], &pair[1], 0.25));
+        smooth.push(lerp(&pair[0], &pair[1], 0.75));
+    }
+    smooth.push(path[path.len() - 1]);
+
+    smooth
+}
+
+fn push_tube_segment(vertices: &mut Vec<f32>, front: &TubeRing, back: &TubeRing) {
+    // angles go from left axis to up axis, so triangles below are
+    // counter-clockwise when seen from outside
+    let get_vertex = |ring: &TubeRing, segment: usize| -> (Vec3, Vec3) {
+        let angle = 2.0 * PI * segment as f32 / RING_SEGMENTS as f32;
+        let [_, left, up] = ring.axes;
+        let offset = add(scale(left, angle.cos()), scale(up, angle.sin()));
+
+        (
+            add(ring.center, scale(offset, ring.radius)),
+            [0.0, angle.cos(), angle.sin()],
+        )
+    };
+
+    for segment in 0..RING_SEGMENTS {
+        let a = get_vertex(front, segment);
+        let b = get_vertex(front, segment + 1);
+        let c = get_vertex(back, segment);
+        let d = get_vertex(back, segment + 1);
+
+        for (position, normal) in [a, c, b, c, d, b] {
+            vertices.extend_from_slice(&position);
+            vertices.extend_from_slice(&normal);
+        }
+    }
+}
+
+// single part snake has no body to turn its head, so it looks where it goes
+fn get_moving_direction(snake: &Snake, grid: &Grid) -> Vec3 {
+    let Some(head) = snake.parts.front() else {
+        return [1.0, 0.0, 0.0];
+    };
+
+    let (next, _) = get_next_cube_position_and_direction(head, snake.direction, grid);
+    let (next_point, _) = get_interpolated_position_3d(head, &next, grid, 0.1);
+
+    subtract_vectors(
+        to_vec3(&next_point),
+        to_vec3(&get_position_3d_for_cube_position(head, grid)),
+    )
+}
+
+fn get_cell_size(grid: &Grid) -> f32 {
+    1.0 / grid.cols_count as f32
+}
+
+fn to_vec3(point: &Point3D) -> Vec3 {
+    [point.x as f32, point.y as f32, point.z as f32]
+}
+
+fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn scale(v: Vec3, factor: f32) -> Vec3 {
+    v.map(|c| c * factor)
+}
+
+fn length(v: Vec3) -> f32 {
+    dot(v, v).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crate::models::{CubePosition, Direction};
+
+    use super::*;
+
+    fn create_snake(parts: &[CubePosition], prev_parts: &[CubePosition]) -> Snake {
+        Snake {
+            parts: VecDeque::from(parts.to_vec()),
+            prev_parts: VecDeque::from(prev_parts.to_vec()),
+            direction: Direction::Up,
+            ..Snake::default()
+        }
+    }
+
+    const GRID: Grid = Grid {
+        rows_count: 10,
+        cols_count: 10,
+    };
+
+    #[test]
+    fn tube_bends_over_edge() {
+        let grid = GRID;
+        let top_row = grid.rows_count - 1;
+        let cell = |side, row| CubePosition { side, row, col: 2 };
+
+        // head crawls from the top row of front side onto the up side
+        let parts = [
+            cell(CubeSideType::Up, 0),
+            cell(CubeSideType::Front, top_row),
+            cell(CubeSideType::Front, top_row - 1),
+            cell(CubeSideType::Front, top_row - 2),
+        ];
+        let prev_parts = [
+            cell(CubeSideType::Front, top_row),
+            cell(CubeSideType::Front, top_row - 1),
+            cell(CubeSideType::Front, top_row - 2),
+            cell(CubeSideType::Front, top_row - 3),
+        ];
+        let snake = create_snake(&parts, &prev_parts);
+
+        let rings = get_tube_rings(&snake, &grid, 0.9);
+        let cell_size = get_cell_size(&grid);
+
+        // head is already on the up side and lies along it
+        let [_, _, up_normal] = get_cube_side_axes(CubeSideType::Up);
+        let [forward, _, up] = rings[0].axes;
+        assert!(dot(forward, up_normal).abs() < 1e-5);
+        assert!(dot(up, up_normal) > 0.999);
+
+        for pair in rings.windows(2) {
+            // rings go one after another without gaps
+            let distance = length(subtract_vectors(pair[0].center, pair[1].center));
+            assert!(distance < cell_size * 0.6);
+
+            // tube center never sinks into cube
+            let center = pair[1].center;
+            assert!(center.iter().any(|c| c.abs() > 0.5 - 1e-5), "{center:?}");
+        }
+
+        // axes are orthonormal
+        for ring in &rings {
+            let [forward, left, up] = ring.axes;
+            assert!(dot(forward, left).abs() < 1e-5 && dot(left, up).abs() < 1e-5);
+            assert!((length(cross(left, up)) - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn tail_is_tapered() {
+        let grid = GRID;
+        let cell = |col| CubePosition {
+            side: CubeSideType::Front,
+            row: 2,
+            col,
+        };
+        let parts: Vec<CubePosition> = (0..6).rev().map(cell).collect();
+        let snake = create_snake(&parts, &parts);
+
+        let rings = get_tube_rings(&snake, &grid, 0.0);
+        let full_radius = SNAKE_TUBE_RADIUS * get_cell_size(&grid);
+
+        assert_eq!(rings[0].radius, full_radius);
+        assert_eq!(rings[rings.len() - 1].radius, 0.0);
+        for pair in rings.windows(2) {
+            assert!(pair[1].radius <= pair[0].radius);
+        }
+
+        let tube = create_snake_tube(&snake, &grid, 0.0).unwrap();
+        assert!(!tube.vertices.is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{
+    helpers::{
+        graphics_math::{cross, normalize, Vec3},
+        random::Random,
+    },
+    models::{Particle, ParticleEmitter},
+};
+
+/// Creates burst of particles flying from origin in the cone around direction
+pub fn create_particles(
+    emitter: &ParticleEmitter,
+    origin: Vec3,
+    direction: Vec3,
+    now: f64,
+    random: &mut Random,
+) -> Vec<Particle> {
+    let direction = normalize(direction);
+
+    // any two axes perpendicular to direction
+    let helper: Vec3 = if direction[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    let axis_u = normalize(cross(direction, helper));
+    let axis_v = cross(direction, axis_u);
+
+    (0..emitter.count)
+        .map(|_| {
+            let tilt = (*emitter.spread * random.next_f64()) as f32;
+            let turn = (std::f64::consts::TAU * random.next_f64()) as f32;
+            let speed =
+                (emitter.speed.0 + (emitter.speed.1 - emitter.speed.0) * random.next_f64()) as f32;
+
+            let velocity: Vec3 = std::array::from_fn(|i| {
+                let side = axis_u[i] * turn.cos() + axis_v[i] * turn.sin();
+                (direction[i] * tilt.cos() + side * tilt.sin()) * speed
+            });
+
+            Particle {
+                origin,
+                velocity,
+                acceleration: direction.map(|v| -v * emitter.gravity),
+                color: emitter.color,
+                size: emitter.size,
+                start_time: now,
+                lifetime: emitter.lifetime,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        helpers::graphics_math::dot,
+        models::{Radians, Range},
+    };
+
+    use super::*;
+
+    #[test]
+    fn particles_fly_within_spread() {
+        let emitter = ParticleEmitter {
+            count: 100,
+            lifetime: 500.0,
+            speed: Range(0.5, 1.0),
+            spread: Radians(0.5),
+            gravity: 1.0,
+            color: [1.0; 4],
+            size: 1.0,
+        };
+        let direction = [0.0, 0.0, 2.0];
+
+        let particles = create_particles(&emitter, [0.0; 3], direction, 10.0, &mut Random::new(1));
+
+        assert_eq!(particles.len(), 100);
+
+        for particle in particles {
+            let speed = dot(particle.velocity, particle.velocity).sqrt();
+            assert!((0.5 - 1e-5..=1.0 + 1e-5).contains(&speed));
+
+            let cos = dot(particle.velocity, [0.0, 0.0, 1.0]) / speed;
+            assert!(cos >= 0.5f32.cos() - 1e-5);
+
+            assert_eq!(particle.acceleration, [0.0, 0.0, -1.0]);
+        }
+    }
+}
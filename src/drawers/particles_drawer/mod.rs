@@ -0,0 +1,276 @@
+mod emitter;
+
+use web_sys::WebGlRenderingContext as GL;
+
+use crate::{
+    helpers::{
+        cube::{get_cube_side_axes, get_position_3d_for_cube_position},
+        graphics_math::{normalize, Matrix4, Vec3},
+        random::Random,
+        webgl::{get_attribute_location, get_uniform_location, init_program, init_shader},
+    },
+    models::{CubePosition, GameEvent, GameState, Grid, ParticleEmitter, Radians, Range},
+};
+
+use emitter::create_particles;
+
+const VERTEX_SHADER_SRC: &str = include_str!("shaders/vertex.glsl");
+const FRAGMENT_SHADER_SRC: &str = include_str!("shaders/fragment.glsl");
+
+// particle is origin, velocity, acceleration, color, size, start time and
+// lifetime
+const PARTICLE_SIZE: usize = 3 + 3 + 3 + 4 + 1 + 2;
+const FLOAT_SIZE: i32 = 4;
+
+const APPLE_BURST: ParticleEmitter = ParticleEmitter {
+    count: 24,
+    lifetime: 600.0,
+    speed: Range(0.3, 0.7),
+    spread: Radians(0.9),
+    gravity: 1.5,
+    // color of apple in current theme
+    color: [1.0, 1.0, 1.0, 1.0],
+    size: 8.0,
+};
+
+const STONE_DEBRIS: ParticleEmitter = ParticleEmitter {
+    count: 32,
+    lifetime: 900.0,
+    speed: Range(0.2, 0.6),
+    spread: Radians(1.3),
+    gravity: 2.0,
+    // color of stone in current theme
+    color: [1.0, 1.0, 1.0, 1.0],
+    size: 10.0,
+};
+
+// confetti is fired in bursts of different colors from around the cube
+const WIN_CONFETTI: ParticleEmitter = ParticleEmitter {
+    count: 20,
+    lifetime: 1500.0,
+    speed: Range(0.2, 0.5),
+    spread: Radians(1.0),
+    gravity: 0.3,
+    color: [1.0, 1.0, 1.0, 1.0],
+    size: 7.0,
+};
+const WIN_CONFETTI_COLORS: [[f32; 4]; 4] = [
+    [1.0, 0.3, 0.3, 1.0],
+    [1.0, 0.8, 0.2, 1.0],
+    [0.3, 0.6, 1.0, 1.0],
+    [0.8, 0.3, 1.0, 1.0],
+];
+const WIN_CONFETTI_BURSTS: usize = 12;
+const WIN_CONFETTI_DISTANCE: f32 = 0.9;
+
+pub fn init_particles_drawer(state: &mut GameState) {
+    let scene = &mut state.scene;
+    let ctx = scene.ctx.as_ref().unwrap();
+    let particles = &mut scene.particles;
+
+    let vertex_shader = init_shader(ctx, GL::VERTEX_SHADER, VERTEX_SHADER_SRC)
+        .expect("failed to init particles vertex shader");
+    let fragment_shader = init_shader(ctx, GL::FRAGMENT_SHADER, FRAGMENT_SHADER_SRC)
+        .expect("failed to init particles fragment shader");
+
+    let program = init_program(ctx, [vertex_shader, fragment_shader])
+        .expect("failed to init particles program");
+
+    // lookup locations for attributes/uniforms
+    let locations = &mut particles.attribute_locations;
+    locations.origin = get_attribute_location(ctx, &program, "a_origin").unwrap();
+    locations.velocity = get_attribute_location(ctx, &program, "a_velocity").unwrap();
+    locations.acceleration = get_attribute_location(ctx, &program, "a_acceleration").unwrap();
+    locations.color = get_attribute_location(ctx, &program, "a_color").unwrap();
+    locations.size = get_attribute_location(ctx, &program, "a_size").unwrap();
+    locations.time = get_attribute_location(ctx, &program, "a_time").unwrap();
+
+    particles.matrix_uniform_location =
+        Some(get_uniform_location(ctx, &program, "u_matrix").unwrap());
+    particles.time_uniform_location = Some(get_uniform_location(ctx, &program, "u_time").unwrap());
+
+    particles.buffer = ctx.create_buffer();
+    particles.program = Some(program);
+    particles.random = Random::new((js_sys::Math::random() * u32::MAX as f64) as u32);
+}
+
+/// Turns game events into particles. Does nothing if there are no events and
+/// no live particles, otherwise keeps cube redrawing while particles fly
+pub fn emit_particles_loop(state: &mut GameState) {
+    let events = std::mem::take(&mut state.events);
+
+    // bursts are colored as objects they come from, so palettes apply to them
+    let theme = state.scene.theme();
+
+    let scene = &mut state.scene;
+    let particles = &mut scene.particles;
+    let grid = &scene.cube.grid;
+
+    if particles.program.is_none() || (events.is_empty() && particles.particles.is_empty()) {
+        return;
+    }
+
+    let now = web_sys::window().unwrap().performance().unwrap().now();
+    let prev_count = particles.particles.len();
+    particles
+        .particles
+        .retain(|particle| now - particle.start_time < particle.lifetime);
+
+    // buffer must match particles, since all of them are drawn in one go
+    let is_changed = !events.is_empty() || particles.particles.len() != prev_count;
+
+    for event in events {
+        let random = &mut particles.random;
+        let new_particles = match event {
+            GameEvent::AppleEaten(pos) => {
+                let (origin, normal) = get_surface_point(&pos, grid);
+                let emitter = ParticleEmitter {
+                    color: theme.apple.to_gl(1.0),
+                    ..APPLE_BURST
+                };
+                create_particles(&emitter, origin, normal, now, random)
+            }
+            GameEvent::StoneHit(pos) => {
+                let (origin, normal) = get_surface_point(&pos, grid);
+                let emitter = ParticleEmitter {
+                    color: theme.stone.to_gl(1.0),
+                    ..STONE_DEBRIS
+                };
+                create_particles(&emitter, origin, normal, now, random)
+            }
+            GameEvent::Win => (0..WIN_CONFETTI_BURSTS)
+                .flat_map(|i| {
+                    let direction = get_random_direction(random);
+                    let origin = direction.map(|v| v * WIN_CONFETTI_DISTANCE);
+                    let emitter = ParticleEmitter {
+                        color: WIN_CONFETTI_COLORS[i % WIN_CONFETTI_COLORS.len()],
+                        ..WIN_CONFETTI
+                    };
+
+                    create_particles(&emitter, origin, direction, now, random)
+                })
+                .collect(),
+        };
+
+        particles.particles.extend(new_particles);
+    }
+
+    if is_changed {
+        upload_particles(state, now);
+    }
+
+    // redraw one more time after last particle is gone, to clear it
+    state.scene.cube.needs_redraw = true;
+}
+
+// center of the cell and outward normal of its side
+fn get_surface_point(pos: &CubePosition, grid: &Grid) -> (Vec3, Vec3) {
+    let center = get_position_3d_for_cube_position(pos, grid);
+    let [_, _, normal] = get_cube_side_axes(pos.side);
+
+    ([center.x as f32, center.y as f32, center.z as f32], normal)
+}
+
+fn get_random_direction(random: &mut Random) -> Vec3 {
+    loop {
+        let v: Vec3 = std::array::from_fn(|_| (random.next_f64() * 2.0 - 1.0) as f32);
+        let len_sq = v.iter().map(|c| c * c).sum::<f32>();
+
+        // points inside unit ball give uniformly distributed directions
+        if len_sq > 0.01 && len_sq <= 1.0 {
+            return normalize(v);
+        }
+    }
+}
+
+fn upload_particles(state: &mut GameState, now: f64) {
+    let scene = &mut state.scene;
+    let ctx = scene.ctx.as_ref().unwrap();
+    let particles = &mut scene.particles;
+
+    particles.buffer_time = now;
+
+    let mut data: Vec<f32> = Vec::with_capacity(particles.particles.len() * PARTICLE_SIZE);
+    for particle in &particles.particles {
+        data.extend_from_slice(&particle.origin);
+        data.extend_from_slice(&particle.velocity);
+        data.extend_from_slice(&particle.acceleration);
+        data.extend_from_slice(&particle.color);
+        data.push(particle.size);
+        data.push((particle.start_time - particles.buffer_time) as f32);
+        data.push(particle.lifetime as f32);
+    }
+
+    ctx.bind_buffer(GL::ARRAY_BUFFER, particles.buffer.as_ref());
+    ctx.buffer_data_with_array_buffer_view(
+        GL::ARRAY_BUFFER,
+        &js_sys::Float32Array::from(&data[..]),
+        GL::STATIC_DRAW,
+    );
+}
+
+pub fn draw_particles(state: &mut GameState, matrix: Matrix4) {
+    let scene = &state.scene;
+    let ctx = scene.ctx.as_ref().unwrap();
+    let particles = &scene.particles;
+    let locations = &particles.attribute_locations;
+
+    if particles.program.is_none() || particles.particles.is_empty() {
+        return;
+    }
+
+    let now = web_sys::window().unwrap().performance().unwrap().now();
+
+    ctx.use_program(particles.program.as_ref());
+    ctx.uniform_matrix4fv_with_f32_array(
+        particles.matrix_uniform_location.as_ref(),
+        false,
+        &matrix,
+    );
+    ctx.uniform1f(
+        particles.time_uniform_location.as_ref(),
+        (now - particles.buffer_time) as f32,
+    );
+
+    // define how to extract particle fields from buffer
+    let attributes = [
+        (locations.origin, 3),
+        (locations.velocity, 3),
+        (locations.acceleration, 3),
+        (locations.color, 4),
+        (locations.size, 1),
+        (locations.time, 2),
+    ];
+
+    ctx.bind_buffer(GL::ARRAY_BUFFER, particles.buffer.as_ref());
+
+    let mut offset = 0;
+    for (location, size) in attributes {
+        ctx.enable_vertex_attrib_array(location);
+        ctx.vertex_attrib_pointer_with_i32(
+            location,
+            size,
+            GL::FLOAT,
+            false,
+            PARTICLE_SIZE as i32 * FLOAT_SIZE,
+            offset,
+        );
+        offset += size * FLOAT_SIZE;
+    }
+
+    // particles are translucent and don't hide each other
+    ctx.enable(GL::BLEND);
+    ctx.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+    ctx.depth_mask(false);
+
+    ctx.draw_arrays(GL::POINTS, 0, particles.particles.len() as i32);
+
+    ctx.disable(GL::BLEND);
+    ctx.depth_mask(true);
+
+    for (location, _) in attributes {
+        ctx.disable_vertex_attrib_array(location);
+    }
+
+    state.scene.stats.draw_calls += 1;
+}
@@ -0,0 +1,13 @@
+precision mediump float;
+
+varying vec4 v_color;
+
+void main() {
+  // round particles out of square points
+  vec2 offset = gl_PointCoord - vec2(0.5);
+  if (dot(offset, offset) > 0.25) {
+    discard;
+  }
+
+  gl_FragColor = v_color;
+}
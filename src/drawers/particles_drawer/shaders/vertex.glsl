@@ -0,0 +1,36 @@
+attribute vec3 a_origin;
+attribute vec3 a_velocity;
+attribute vec3 a_acceleration;
+attribute vec4 a_color;
+attribute float a_size;
+
+// start time and lifetime, in milliseconds
+attribute vec2 a_time;
+
+uniform mat4 u_matrix;
+
+// milliseconds since particles were uploaded
+uniform float u_time;
+
+varying vec4 v_color;
+
+void main() {
+  float age = u_time - a_time.x;
+  float life = age / a_time.y;
+
+  // particles move in cube space, so they rotate together with the cube
+  float t = age / 1000.0;
+  vec3 position = a_origin + a_velocity * t + 0.5 * a_acceleration * t * t;
+
+  gl_Position = u_matrix * vec4(position, 1.0);
+
+  // farther particles are smaller
+  gl_PointSize = a_size * 2.0 / gl_Position.w;
+
+  v_color = vec4(a_color.rgb, a_color.a * (1.0 - life));
+
+  // dead particles are moved out of clip space
+  if (life < 0.0 || life > 1.0) {
+    gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+  }
+}
@@ -0,0 +1,160 @@
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::models::{GameState, PerfSummary, PROFILER_SAMPLES_COUNT};
+
+const HUD_WIDTH: f64 = 240.0;
+const HUD_HEIGHT: f64 = 120.0;
+const LINE_HEIGHT: f64 = 16.0;
+const PADDING: f64 = 6.0;
+
+// frame time at the top of the graph, in milliseconds
+const GRAPH_MAX_FRAME_TIME: f64 = 50.0;
+
+// frame time of 60 fps
+const TARGET_FRAME_TIME: f64 = 1000.0 / 60.0;
+
+// name of window property with the latest summary, eg. `window.perfStats.fps`
+const EXPORT_PROPERTY: &str = "perfStats";
+
+pub fn init_perf_hud_drawer(state: &mut GameState) {
+    let document = web_sys::window().unwrap().document().unwrap();
+
+    let canvas = document
+        .query_selector(".perf-hud")
+        .unwrap()
+        .expect("failed to find perf hud element")
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+
+    canvas.set_width(HUD_WIDTH as u32);
+    canvas.set_height(HUD_HEIGHT as u32);
+
+    let ctx = canvas
+        .get_context("2d")
+        .unwrap()
+        .expect("failed to get 2D render context")
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .unwrap();
+
+    state.scene.perf_hud = Some(canvas);
+    state.scene.perf_hud_ctx = Some(ctx);
+}
+
+/// Draws counters of recent frames and graph of frame times. Counters are
+/// collected even when HUD is hidden, so they can be read from JS
+pub fn draw_perf_hud_loop(state: &mut GameState) {
+    export_perf_summary(state);
+
+    let scene = &state.scene;
+    let canvas = scene.perf_hud.as_ref().unwrap();
+    let ctx = scene.perf_hud_ctx.as_ref().unwrap();
+
+    if canvas.hidden() == scene.is_perf_hud_shown {
+        canvas.set_hidden(!scene.is_perf_hud_shown);
+    }
+    if !scene.is_perf_hud_shown {
+        return;
+    }
+
+    let profiler = &scene.profiler;
+    let summary = &profiler.summary;
+
+    ctx.clear_rect(0.0, 0.0, HUD_WIDTH, HUD_HEIGHT);
+    ctx.set_fill_style(&"rgba(0, 0, 0, 0.6)".into());
+    ctx.fill_rect(0.0, 0.0, HUD_WIDTH, HUD_HEIGHT);
+
+    let lines = [
+        format!("{:.0} fps", summary.fps),
+        format!(
+            "frame p50 {:.1} p95 {:.1} p99 {:.1} ms",
+            summary.frame_time_p50, summary.frame_time_p95, summary.frame_time_p99
+        ),
+        format!(
+            "ticks {:.2} draws {:.1} per frame",
+            summary.ticks_per_frame, summary.draw_calls_per_frame
+        ),
+        format!(
+            "repaints {:.0}/s upload {:.0} KB/s",
+            summary.side_repaints_per_second,
+            summary.uploaded_bytes_per_second / 1024.0
+        ),
+    ];
+
+    ctx.set_font("12px Consolas, monospace");
+    ctx.set_text_baseline("top");
+    ctx.set_fill_style(&"white".into());
+    for (i, line) in lines.iter().enumerate() {
+        ctx.fill_text(line, PADDING, PADDING + i as f64 * LINE_HEIGHT)
+            .unwrap();
+    }
+
+    // one bar per frame, the newest on the right
+    let graph_top = PADDING * 2.0 + lines.len() as f64 * LINE_HEIGHT;
+    let graph_height = HUD_HEIGHT - graph_top - PADDING;
+    let bar_width = (HUD_WIDTH - PADDING * 2.0) / PROFILER_SAMPLES_COUNT as f64;
+    let to_height = |time: f64| (time / GRAPH_MAX_FRAME_TIME).min(1.0) * graph_height;
+
+    for (i, sample) in profiler.samples.iter().rev().enumerate() {
+        let color = if sample.frame_time > TARGET_FRAME_TIME * 1.5 {
+            "orangered"
+        } else {
+            "limegreen"
+        };
+        let height = to_height(sample.frame_time);
+
+        ctx.set_fill_style(&color.into());
+        ctx.fill_rect(
+            HUD_WIDTH - PADDING - (i + 1) as f64 * bar_width,
+            graph_top + graph_height - height,
+            bar_width,
+            height,
+        );
+    }
+
+    // frames below the line are fast enough for 60 fps
+    ctx.set_fill_style(&"white".into());
+    ctx.fill_rect(
+        PADDING,
+        graph_top + graph_height - to_height(TARGET_FRAME_TIME),
+        HUD_WIDTH - PADDING * 2.0,
+        1.0,
+    );
+}
+
+// sets summary to window property, once it's updated
+fn export_perf_summary(state: &mut GameState) {
+    let profiler = &mut state.scene.profiler;
+    if profiler.is_summary_exported {
+        return;
+    }
+    profiler.is_summary_exported = true;
+
+    let window = web_sys::window().unwrap();
+    js_sys::Reflect::set(
+        &window,
+        &EXPORT_PROPERTY.into(),
+        &summary_to_js(&profiler.summary),
+    )
+    .unwrap();
+}
+
+fn summary_to_js(summary: &PerfSummary) -> JsValue {
+    let object = js_sys::Object::new();
+
+    let fields = [
+        ("fps", summary.fps),
+        ("frameTimeP50", summary.frame_time_p50),
+        ("frameTimeP95", summary.frame_time_p95),
+        ("frameTimeP99", summary.frame_time_p99),
+        ("ticksPerFrame", summary.ticks_per_frame),
+        ("drawCallsPerFrame", summary.draw_calls_per_frame),
+        ("sideRepaintsPerSecond", summary.side_repaints_per_second),
+        ("uploadedBytesPerSecond", summary.uploaded_bytes_per_second),
+    ];
+
+    for (name, value) in fields {
+        js_sys::Reflect::set(&object, &name.into(), &value.into()).unwrap();
+    }
+
+    object.into()
+}
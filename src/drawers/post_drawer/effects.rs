@@ -0,0 +1,85 @@
+use crate::models::{GameStatus, PostEffect, Theme};
+
+// time for effect to fully appear or disappear, in milliseconds
+pub const FADE_DURATION: f64 = 400.0;
+
+/// Strength effect should have in current state of the game
+pub fn get_effect_target(
+    effect: PostEffect,
+    theme: &Theme,
+    status: GameStatus,
+    is_allowed: bool,
+) -> f32 {
+    if !is_allowed || !theme.post_effects.contains(&effect) {
+        return 0.0;
+    }
+
+    let is_shown = match effect {
+        PostEffect::FailFade => status == GameStatus::Fail,
+        PostEffect::PauseBlur => status == GameStatus::Paused,
+        PostEffect::Bloom | PostEffect::Vignette | PostEffect::Crt => true,
+    };
+
+    if is_shown {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Moves amount towards target by time passed. Returns false if it's already
+/// there
+pub fn step_effect_amount(amount: &mut f32, target: f32, elapsed: f64) -> bool {
+    if *amount == target {
+        return false;
+    }
+
+    let step = (elapsed / FADE_DURATION) as f32;
+    *amount = if *amount < target {
+        (*amount + step).min(target)
+    } else {
+        (*amount - step).max(target)
+    };
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::THEMES;
+
+    use super::*;
+
+    #[test]
+    fn status_effects_are_shown_in_their_status() {
+        let theme = &THEMES[1];
+
+        let target = |effect, status| get_effect_target(effect, theme, status, true);
+        assert_eq!(target(PostEffect::FailFade, GameStatus::Fail), 1.0);
+        assert_eq!(target(PostEffect::FailFade, GameStatus::InGame), 0.0);
+        assert_eq!(target(PostEffect::PauseBlur, GameStatus::Paused), 1.0);
+        assert_eq!(target(PostEffect::Bloom, GameStatus::InGame), 1.0);
+
+        // not used by theme, or turned off by quality
+        assert_eq!(target(PostEffect::Crt, GameStatus::InGame), 0.0);
+        assert_eq!(
+            get_effect_target(PostEffect::Bloom, theme, GameStatus::InGame, false),
+            0.0
+        );
+    }
+
+    #[test]
+    fn amount_fades_towards_target() {
+        let mut amount = 0.0;
+
+        assert!(step_effect_amount(&mut amount, 1.0, FADE_DURATION / 4.0));
+        assert_eq!(amount, 0.25);
+
+        assert!(step_effect_amount(&mut amount, 1.0, FADE_DURATION));
+        assert_eq!(amount, 1.0);
+        assert!(!step_effect_amount(&mut amount, 1.0, FADE_DURATION));
+
+        assert!(step_effect_amount(&mut amount, 0.0, FADE_DURATION / 2.0));
+        assert_eq!(amount, 0.5);
+    }
+}
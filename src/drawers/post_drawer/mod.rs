@@ -0,0 +1,342 @@
+mod effects;
+
+use web_sys::WebGlRenderingContext as GL;
+
+use crate::{
+    helpers::{
+        gl_context::GlContext,
+        webgl::{get_attribute_location, get_uniform_location, init_program, init_shader},
+    },
+    models::{GameState, PostEffect, PostPass, PostProcessing, RenderTarget, POST_EFFECTS},
+};
+
+use effects::{get_effect_target, step_effect_amount, FADE_DURATION};
+
+const VERTEX_SHADER_SRC: &str = include_str!("shaders/vertex.glsl");
+const BLOOM_SHADER_SRC: &str = include_str!("shaders/bloom.glsl");
+const FAIL_FADE_SHADER_SRC: &str = include_str!("shaders/fail_fade.glsl");
+const PAUSE_BLUR_SHADER_SRC: &str = include_str!("shaders/pause_blur.glsl");
+const VIGNETTE_SHADER_SRC: &str = include_str!("shaders/vignette.glsl");
+const CRT_SHADER_SRC: &str = include_str!("shaders/crt.glsl");
+
+// single triangle, which covers the whole clip space
+const QUAD_VERTICES: [f32; 6] = [-1.0, -1.0, 3.0, -1.0, -1.0, 3.0];
+
+// webgl 1 keeps side textures in the first units
+const POST_TEXTURE_UNIT: u32 = 7;
+
+// cube and two images for passes to switch between
+const RENDER_TARGETS_COUNT: usize = 3;
+
+/// Compiles programs of all effects. Effect, which fails to compile, is
+/// reported and skipped
+pub fn init_post_drawer(state: &mut GameState) {
+    let scene = &mut state.scene;
+    let ctx = scene.ctx.as_ref().unwrap();
+    let post = &mut scene.post_processing;
+
+    // previous resources are gone with lost context
+    post.passes.clear();
+    post.targets.clear();
+    post.is_supported = true;
+
+    let vertex_shader = match init_shader(ctx, GL::VERTEX_SHADER, VERTEX_SHADER_SRC) {
+        Ok(shader) => shader,
+        Err(error) => {
+            let message = format!("post effects are disabled: {error}");
+            web_sys::console::error_1(&message.into());
+            post.is_supported = false;
+            return;
+        }
+    };
+
+    for effect in POST_EFFECTS {
+        match init_post_pass(ctx, &vertex_shader, get_fragment_shader_src(effect)) {
+            Ok(pass) => {
+                post.passes.insert(effect, pass);
+            }
+            Err(error) => {
+                let message = format!("post effect {effect:?} is disabled: {error}");
+                web_sys::console::error_1(&message.into());
+            }
+        }
+    }
+
+    post.quad_buffer = ctx.create_buffer();
+    ctx.bind_buffer(GL::ARRAY_BUFFER, post.quad_buffer.as_ref());
+    ctx.buffer_data_with_array_buffer_view(
+        GL::ARRAY_BUFFER,
+        &js_sys::Float32Array::from(&QUAD_VERTICES[..]),
+        GL::STATIC_DRAW,
+    );
+}
+
+fn get_fragment_shader_src(effect: PostEffect) -> &'static str {
+    match effect {
+        PostEffect::Bloom => BLOOM_SHADER_SRC,
+        PostEffect::FailFade => FAIL_FADE_SHADER_SRC,
+        PostEffect::PauseBlur => PAUSE_BLUR_SHADER_SRC,
+        PostEffect::Vignette => VIGNETTE_SHADER_SRC,
+        PostEffect::Crt => CRT_SHADER_SRC,
+    }
+}
+
+fn init_post_pass(
+    ctx: &GlContext,
+    vertex_shader: &web_sys::WebGlShader,
+    fragment_shader_src: &str,
+) -> Result<PostPass, String> {
+    let fragment_shader = init_shader(ctx, GL::FRAGMENT_SHADER, fragment_shader_src)?;
+    let program = init_program(ctx, [vertex_shader.clone(), fragment_shader])?;
+
+    // compiler drops uniforms, which effect doesn't use
+    let uniform = |name| get_uniform_location(ctx, &program, name).ok();
+
+    Ok(PostPass {
+        position_attribute_location: get_attribute_location(ctx, &program, "a_position")?,
+        texture_uniform_location: uniform("u_texture"),
+        resolution_uniform_location: uniform("u_resolution"),
+        amount_uniform_location: uniform("u_amount"),
+        glow_color_uniform_location: uniform("u_glow_color"),
+        program,
+    })
+}
+
+/// Fades effects in and out following theme, quality and game status. Keeps
+/// cube redrawing while they change
+pub fn update_post_effects_loop(state: &mut GameState) {
+    let scene = &mut state.scene;
+    let theme = scene.theme();
+    let is_allowed = scene.quality.preset().post_effects;
+    let post = &mut scene.post_processing;
+
+    let now = web_sys::window().unwrap().performance().unwrap().now();
+
+    // effects are at full strength from the first frame
+    let elapsed = post
+        .last_update_time
+        .map_or(FADE_DURATION, |time| now - time);
+    post.last_update_time = Some(now);
+
+    let mut is_changed = false;
+    for effect in POST_EFFECTS {
+        let target = get_effect_target(effect, &theme, state.status, is_allowed);
+        let amount = post.amounts.entry(effect).or_insert(0.0);
+        is_changed |= step_effect_amount(amount, target, elapsed);
+    }
+
+    if is_changed {
+        scene.cube.needs_redraw = true;
+    }
+}
+
+/// Makes cube draw into off-screen image, if any effect is shown. Returns
+/// false if cube should be drawn right to the canvas
+pub fn begin_post_processing(state: &mut GameState) -> bool {
+    let scene = &mut state.scene;
+    let ctx = scene.ctx.as_ref().unwrap();
+    let canvas = scene.canvas.as_ref().unwrap();
+    let post = &mut scene.post_processing;
+
+    let (width, height) = (canvas.width(), canvas.height());
+    if !post.is_supported || get_active_passes(post).is_empty() || width == 0 || height == 0 {
+        return false;
+    }
+
+    let is_resized = post
+        .targets
+        .first()
+        .is_none_or(|target| target.width != width || target.height != height);
+
+    if is_resized {
+        if let Err(error) = init_render_targets(ctx, post, width, height) {
+            let message = format!("post effects are disabled: {error}");
+            web_sys::console::error_1(&message.into());
+            post.is_supported = false;
+            return false;
+        }
+    }
+
+    ctx.bind_framebuffer(GL::FRAMEBUFFER, Some(&post.targets[0].framebuffer));
+
+    true
+}
+
+/// Runs shown effects one after another over the image of cube. The last one
+/// draws to the canvas
+pub fn end_post_processing(state: &mut GameState) {
+    let scene = &state.scene;
+    let ctx = scene.ctx.as_ref().unwrap();
+    let post = &scene.post_processing;
+
+    let passes = get_active_passes(post);
+    let glow_color = scene.theme().apple.to_gl(1.0);
+
+    // passes cover the whole screen and don't need depth
+    if let GlContext::WebGl2(ctx) = ctx {
+        ctx.bind_vertex_array(None);
+    }
+    ctx.disable(GL::DEPTH_TEST);
+    ctx.disable(GL::CULL_FACE);
+
+    ctx.active_texture(GL::TEXTURE0 + POST_TEXTURE_UNIT);
+    ctx.bind_buffer(GL::ARRAY_BUFFER, post.quad_buffer.as_ref());
+
+    let mut source = &post.targets[0];
+    for (i, (pass, amount)) in passes.iter().enumerate() {
+        let target = if i == passes.len() - 1 {
+            None
+        } else {
+            Some(&post.targets[1 + i % 2])
+        };
+
+        ctx.bind_framebuffer(GL::FRAMEBUFFER, target.map(|target| &target.framebuffer));
+        ctx.use_program(Some(&pass.program));
+        ctx.bind_texture(GL::TEXTURE_2D, Some(&source.texture));
+
+        ctx.uniform1i(
+            pass.texture_uniform_location.as_ref(),
+            POST_TEXTURE_UNIT as i32,
+        );
+        ctx.uniform2f(
+            pass.resolution_uniform_location.as_ref(),
+            source.width as f32,
+            source.height as f32,
+        );
+        ctx.uniform1f(pass.amount_uniform_location.as_ref(), *amount);
+        ctx.uniform3fv_with_f32_array(pass.glow_color_uniform_location.as_ref(), &glow_color[..3]);
+
+        let location = pass.position_attribute_location;
+        ctx.enable_vertex_attrib_array(location);
+        ctx.vertex_attrib_pointer_with_i32(location, 2, GL::FLOAT, false, 0, 0);
+        ctx.draw_arrays(GL::TRIANGLES, 0, 3);
+        ctx.disable_vertex_attrib_array(location);
+
+        if let Some(target) = target {
+            source = target;
+        }
+    }
+
+    // texture can't be sampled while cube is drawn into it next frame
+    ctx.bind_texture(GL::TEXTURE_2D, None);
+    ctx.active_texture(GL::TEXTURE0);
+
+    state.scene.stats.draw_calls += passes.len() as u32;
+}
+
+// passes of shown effects, in order, with their strength
+fn get_active_passes(post: &PostProcessing) -> Vec<(&PostPass, f32)> {
+    POST_EFFECTS
+        .iter()
+        .filter_map(|effect| {
+            let amount = post.amounts.get(effect).copied().unwrap_or(0.0);
+            if amount > 0.0 {
+                post.passes.get(effect).map(|pass| (pass, amount))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn init_render_targets(
+    ctx: &GlContext,
+    post: &mut PostProcessing,
+    width: u32,
+    height: u32,
+) -> Result<(), String> {
+    for target in post.targets.drain(..) {
+        ctx.delete_framebuffer(Some(&target.framebuffer));
+        ctx.delete_texture(Some(&target.texture));
+        ctx.delete_renderbuffer(target.depth.as_ref());
+    }
+
+    ctx.active_texture(GL::TEXTURE0 + POST_TEXTURE_UNIT);
+
+    for i in 0..RENDER_TARGETS_COUNT {
+        let target = create_render_target(ctx, width, height, i == 0);
+        post.targets.push(target?);
+    }
+
+    ctx.bind_texture(GL::TEXTURE_2D, None);
+    ctx.active_texture(GL::TEXTURE0);
+
+    Ok(())
+}
+
+fn create_render_target(
+    ctx: &GlContext,
+    width: u32,
+    height: u32,
+    with_depth: bool,
+) -> Result<RenderTarget, String> {
+    let texture = ctx.create_texture().ok_or("failed to create texture")?;
+    ctx.bind_texture(GL::TEXTURE_2D, Some(&texture));
+    ctx.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+        GL::TEXTURE_2D,
+        0,
+        GL::RGBA as i32,
+        width as i32,
+        height as i32,
+        0,
+        GL::RGBA,
+        GL::UNSIGNED_BYTE,
+        None,
+    )
+    .map_err(|_| "failed to allocate texture")?;
+
+    // size of canvas is rarely a power of two, so no mipmaps and no repeat
+    ctx.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::LINEAR as i32);
+    ctx.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, GL::LINEAR as i32);
+    ctx.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
+    ctx.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
+
+    let framebuffer = ctx
+        .create_framebuffer()
+        .ok_or("failed to create framebuffer")?;
+    ctx.bind_framebuffer(GL::FRAMEBUFFER, Some(&framebuffer));
+    ctx.framebuffer_texture_2d(
+        GL::FRAMEBUFFER,
+        GL::COLOR_ATTACHMENT0,
+        GL::TEXTURE_2D,
+        Some(&texture),
+        0,
+    );
+
+    let mut depth = None;
+    if with_depth {
+        let renderbuffer = ctx
+            .create_renderbuffer()
+            .ok_or("failed to create renderbuffer")?;
+        ctx.bind_renderbuffer(GL::RENDERBUFFER, Some(&renderbuffer));
+        ctx.renderbuffer_storage(
+            GL::RENDERBUFFER,
+            GL::DEPTH_COMPONENT16,
+            width as i32,
+            height as i32,
+        );
+        ctx.framebuffer_renderbuffer(
+            GL::FRAMEBUFFER,
+            GL::DEPTH_ATTACHMENT,
+            GL::RENDERBUFFER,
+            Some(&renderbuffer),
+        );
+        ctx.bind_renderbuffer(GL::RENDERBUFFER, None);
+        depth = Some(renderbuffer);
+    }
+
+    let status = ctx.check_framebuffer_status(GL::FRAMEBUFFER);
+    ctx.bind_framebuffer(GL::FRAMEBUFFER, None);
+
+    if status != GL::FRAMEBUFFER_COMPLETE {
+        return Err(format!("framebuffer is incomplete: {status:#x}"));
+    }
+
+    Ok(RenderTarget {
+        framebuffer,
+        texture,
+        depth,
+        width,
+        height,
+    })
+}
@@ -0,0 +1,36 @@
+precision mediump float;
+
+uniform sampler2D u_texture;
+uniform vec2 u_resolution;
+uniform float u_amount;
+
+// color of apples, which are glowing
+uniform vec3 u_glow_color;
+
+varying vec2 v_uv;
+
+// how much pixel looks like an apple. shading changes brightness of the color,
+// so only its direction is compared
+float get_glow(vec2 uv) {
+  vec4 color = texture2D(u_texture, uv);
+  vec3 rgb = color.rgb / max(color.a, 0.001);
+  float similarity = dot(normalize(rgb + 0.001), normalize(u_glow_color + 0.001));
+
+  return smoothstep(0.97, 1.0, similarity) * color.a;
+}
+
+void main() {
+  vec4 color = texture2D(u_texture, v_uv);
+
+  // two rings of samples around the pixel
+  float glow = 0.0;
+  for (int i = 0; i < 8; i++) {
+    float angle = float(i) * 0.785398;
+    vec2 direction = vec2(cos(angle), sin(angle)) / u_resolution;
+    glow += get_glow(v_uv + direction * 4.0) + get_glow(v_uv + direction * 10.0);
+  }
+  glow = glow / 16.0 * u_amount;
+
+  // colors are premultiplied, so glow around the cube makes it opaque
+  gl_FragColor = vec4(color.rgb + u_glow_color * glow, max(color.a, glow));
+}
@@ -0,0 +1,27 @@
+precision mediump float;
+
+uniform sampler2D u_texture;
+uniform float u_amount;
+
+varying vec2 v_uv;
+
+void main() {
+  // bulge the image like old screen glass
+  vec2 centered = v_uv * 2.0 - 1.0;
+  centered *= 1.0 + 0.08 * u_amount * dot(centered, centered);
+  vec2 uv = centered * 0.5 + 0.5;
+
+  if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+    gl_FragColor = vec4(0.0, 0.0, 0.0, u_amount);
+    return;
+  }
+
+  vec4 color = texture2D(u_texture, uv);
+
+  // every other row of pixels is darker
+  float scanline = 0.5 + 0.5 * sin(gl_FragCoord.y * 3.141593);
+  float darkness = 0.3 * u_amount * (1.0 - scanline);
+
+  float v = 1.0 - darkness;
+  gl_FragColor = vec4(color.rgb * v, color.a * v + darkness);
+}
@@ -0,0 +1,13 @@
+precision mediump float;
+
+uniform sampler2D u_texture;
+uniform float u_amount;
+
+varying vec2 v_uv;
+
+void main() {
+  vec4 color = texture2D(u_texture, v_uv);
+  float gray = dot(color.rgb, vec3(0.299, 0.587, 0.114));
+
+  gl_FragColor = vec4(mix(color.rgb, vec3(gray), u_amount), color.a);
+}
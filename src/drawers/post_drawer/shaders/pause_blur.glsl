@@ -0,0 +1,25 @@
+precision mediump float;
+
+uniform sampler2D u_texture;
+uniform vec2 u_resolution;
+uniform float u_amount;
+
+varying vec2 v_uv;
+
+// pixels, at the edge of the screen
+const float MAX_RADIUS = 8.0;
+
+void main() {
+  // overlay is in the center of the screen, and stays sharp
+  float distance_to_center = distance(v_uv, vec2(0.5));
+  float radius = MAX_RADIUS * u_amount * smoothstep(0.15, 0.5, distance_to_center);
+
+  vec4 color = texture2D(u_texture, v_uv);
+  for (int i = 0; i < 12; i++) {
+    float angle = float(i) * 0.523599;
+    vec2 offset = vec2(cos(angle), sin(angle)) * radius / u_resolution;
+    color += texture2D(u_texture, v_uv + offset) + texture2D(u_texture, v_uv + offset * 0.5);
+  }
+
+  gl_FragColor = color / 25.0;
+}
@@ -0,0 +1,15 @@
+precision mediump float;
+
+uniform sampler2D u_texture;
+uniform float u_amount;
+
+varying vec2 v_uv;
+
+void main() {
+  vec4 color = texture2D(u_texture, v_uv);
+  float darkness = 0.6 * u_amount * smoothstep(0.35, 0.8, distance(v_uv, vec2(0.5)));
+
+  // same as black layer over the image, so transparent parts get dark too
+  float v = 1.0 - darkness;
+  gl_FragColor = vec4(color.rgb * v, color.a * v + darkness);
+}
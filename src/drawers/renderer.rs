@@ -0,0 +1,24 @@
+use crate::models::{CubeSideType, GameState};
+
+use super::{cube_drawer::draw_cube_loop, cube_side_drawer::draw_cube_side_loop};
+
+/// Target of scene drawer. Draws images of cube sides, and then the cube with
+/// those images on it
+pub trait Renderer {
+    fn draw_cube_side(&mut self, state: &mut GameState, side_type: CubeSideType);
+    fn draw_cube(&mut self, state: &mut GameState);
+}
+
+/// Draws sides with canvas 2D and the cube with webgl. All webgl resources are
+/// kept in scene
+pub struct WebGlRenderer;
+
+impl Renderer for WebGlRenderer {
+    fn draw_cube_side(&mut self, state: &mut GameState, side_type: CubeSideType) {
+        draw_cube_side_loop(state, side_type);
+    }
+
+    fn draw_cube(&mut self, state: &mut GameState) {
+        draw_cube_loop(state);
+    }
+}
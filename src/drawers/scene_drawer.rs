@@ -1,8 +1,20 @@
-use crate::models::GameState;
+use crate::{
+    actions::perf_actions::record_frame_stats,
+    helpers::canvas::resize_canvas,
+    models::{GameState, Size},
+};
 
 use super::{
-    cube_drawer::{draw_cube_loop, init_cube_drawer},
-    cube_side_drawer::{draw_cube_side_loop, init_cube_side_drawer},
+    cube_drawer::{apply_cube_quality, init_cube_drawer},
+    cube_side_drawer::{init_cube_side_drawer, load_sprites_loop, resize_cube_side},
+    export_drawer::save_screenshot_loop,
+    hud_drawer::{draw_hud_loop, init_hud_drawer, resize_hud},
+    minimap_drawer::{draw_minimap_loop, init_minimap_drawer},
+    objects_drawer::init_objects_drawer,
+    particles_drawer::{emit_particles_loop, init_particles_drawer},
+    perf_hud_drawer::{draw_perf_hud_loop, init_perf_hud_drawer},
+    post_drawer::{init_post_drawer, update_post_effects_loop},
+    renderer::{Renderer, WebGlRenderer},
 };
 
 pub fn init_scene_drawer(state: &mut GameState, canvas: web_sys::HtmlCanvasElement) {
@@ -13,12 +25,194 @@ pub fn init_scene_drawer(state: &mut GameState, canvas: web_sys::HtmlCanvasEleme
     }
 
     init_cube_drawer(state);
+    init_objects_drawer(state);
+    init_particles_drawer(state);
+    init_post_drawer(state);
+    init_hud_drawer(state);
+    init_minimap_drawer(state);
+    init_perf_hud_drawer(state);
 }
 
 pub fn draw_scene_loop(state: &mut GameState) {
+    if state.scene.is_context_lost {
+        return;
+    }
+
+    apply_quality_loop(state);
+    load_sprites_loop(state);
+    emit_particles_loop(state);
+    update_post_effects_loop(state);
+
+    draw_minimap_loop(state);
+    draw_scene(&mut WebGlRenderer, state);
+    save_screenshot_loop(state);
+    draw_hud_loop(state);
+
+    let now = web_sys::window().unwrap().performance().unwrap().now();
+    record_frame_stats(state, now);
+    draw_perf_hud_loop(state);
+}
+
+/// Stops drawing until webgl context is restored
+pub fn lose_scene_context(state: &mut GameState) {
+    state.scene.is_context_lost = true;
+
+    // particles live in lost buffer, there is nothing to restore them from
+    state.scene.particles.particles.clear();
+}
+
+/// Creates all webgl resources again in restored context. Side canvases and
+/// geometry are kept on CPU side, so scene is the same as before the loss
+pub fn restore_scene_context(state: &mut GameState) {
+    restore_scene_drawers(
+        state,
+        &mut [
+            &mut init_cube_drawer,
+            &mut init_objects_drawer,
+            &mut init_particles_drawer,
+            &mut init_post_drawer,
+        ],
+    );
+}
+
+// drawers are passed in, so restoring can be tested without webgl
+fn restore_scene_drawers(
+    state: &mut GameState,
+    init_drawers: &mut [&mut dyn FnMut(&mut GameState)],
+) {
+    let objects_as_meshes = state.scene.cube.objects_as_meshes;
+
+    // objects drawer turns meshes on, if they are still supported
+    state.scene.cube.objects_as_meshes = false;
+    for init_drawer in init_drawers.iter_mut() {
+        init_drawer(state);
+    }
+
+    // keep objects mode chosen by player
+    let scene = &mut state.scene;
+    scene.cube.objects_as_meshes &= objects_as_meshes;
+    scene.cube.needs_redraw = true;
+    scene.cube.sides.values_mut().for_each(|side| {
+        side.needs_redraw = true;
+    });
+
+    scene.is_context_lost = false;
+}
+
+/// Fits cube canvas to the page, with resolution of current quality preset
+pub fn resize_scene(state: &mut GameState) {
+    let window = web_sys::window().unwrap();
+    let body = window.document().unwrap().body().unwrap();
+
+    let scene = &mut state.scene;
+    let canvas = scene.canvas.as_ref().unwrap();
+
+    let css_size = Size {
+        width: body.client_width() as f64,
+        height: body.client_height() as f64,
+    };
+    let pixel_ratio = window.device_pixel_ratio() * scene.quality.preset().render_scale;
+
+    resize_canvas(canvas, css_size, pixel_ratio).unwrap();
+
+    // resizing clears the canvas
+    scene.cube.needs_redraw = true;
+
+    resize_hud(state, css_size);
+}
+
+// updates canvases and textures once quality preset changes
+fn apply_quality_loop(state: &mut GameState) {
+    if !state.scene.quality.needs_apply {
+        return;
+    }
+    state.scene.quality.needs_apply = false;
+
+    for side_type in state.scene.cube.side_types {
+        resize_cube_side(state, side_type);
+    }
+
+    apply_cube_quality(state);
+    resize_scene(state);
+}
+
+/// Draws cube sides and the cube with given renderer
+pub fn draw_scene(renderer: &mut impl Renderer, state: &mut GameState) {
     for side_type in state.scene.cube.side_types {
-        draw_cube_side_loop(state, side_type);
+        renderer.draw_cube_side(state, side_type);
     }
 
-    draw_cube_loop(state);
+    renderer.draw_cube(state);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{actions::game_actions::init_game_state, models::Particle};
+
+    // restores context with fake drawers, which record that they ran
+    fn restore(state: &mut GameState, are_meshes_supported: bool) -> Vec<&'static str> {
+        let inits = RefCell::new(vec![]);
+        let inits_ref = &inits;
+        let record = |name| move |_: &mut GameState| inits_ref.borrow_mut().push(name);
+
+        restore_scene_drawers(
+            state,
+            &mut [
+                &mut record("cube"),
+                &mut |state: &mut GameState| {
+                    inits.borrow_mut().push("objects");
+                    state.scene.cube.objects_as_meshes = are_meshes_supported;
+                },
+                &mut record("particles"),
+                &mut record("post"),
+            ],
+        );
+
+        inits.into_inner()
+    }
+
+    #[test]
+    fn restores_lost_context() {
+        let mut state = GameState::default();
+        init_game_state(&mut state, 42);
+        state.scene.cube.objects_as_meshes = true;
+        state.scene.particles.particles.push(Particle {
+            origin: [0.0; 3],
+            velocity: [0.0; 3],
+            acceleration: [0.0; 3],
+            color: [1.0; 4],
+            size: 1.0,
+            start_time: 0.0,
+            lifetime: 1000.0,
+        });
+
+        lose_scene_context(&mut state);
+        assert!(state.scene.is_context_lost);
+        assert!(state.scene.particles.particles.is_empty());
+
+        state.scene.cube.needs_redraw = false;
+        state
+            .scene
+            .cube
+            .sides
+            .values_mut()
+            .for_each(|side| side.needs_redraw = false);
+
+        let inits = restore(&mut state, true);
+        assert_eq!(inits, ["cube", "objects", "particles", "post"]);
+
+        let cube = &state.scene.cube;
+        assert!(!state.scene.is_context_lost);
+        assert!(cube.objects_as_meshes);
+        assert!(cube.needs_redraw);
+        assert!(cube.sides.values().all(|side| side.needs_redraw));
+
+        // meshes are dropped only if restored context can't draw them
+        lose_scene_context(&mut state);
+        restore(&mut state, false);
+        assert!(!state.scene.cube.objects_as_meshes);
+    }
 }
@@ -0,0 +1,150 @@
+use crate::models::{Color, CubePosition, CubeSideType, GameState, SpriteType};
+
+const GHOST_ALPHA: f32 = 0.3;
+
+// shapes of objects with shape coding on, so they can be told apart without
+// relying on color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellShape {
+    Square,
+    Striped,
+    Ringed,
+    Circle,
+    Cross,
+}
+
+/// How object cell is painted. Surfaces without sprites or shape coding use
+/// color and alpha only
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectCell {
+    pub color: Color,
+    pub alpha: f32,
+    pub sprite: Option<SpriteType>,
+    pub shape: CellShape,
+}
+
+/// Something cube side is painted on: side canvas in browser, or RGBA image
+/// in software renderer and exports. Rects are `(x, y, width, height)` with y
+/// going down
+pub trait SideSurface {
+    fn fill_background(&mut self, color: Color);
+
+    /// Draws horizontal or vertical line
+    fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: Color);
+
+    fn paint_cell(&mut self, rect: (f64, f64, f64, f64), cell: &ObjectCell);
+}
+
+/// Paints background, grid and, if `with_objects` is set, objects of the side
+/// on a surface of given size. Canvas and software renderer both paint sides
+/// here, so they don't drift apart
+pub fn paint_side(
+    state: &GameState,
+    side_type: CubeSideType,
+    surface: &mut impl SideSurface,
+    (width, height): (f64, f64),
+    with_objects: bool,
+) {
+    let theme = state.scene.theme();
+    let grid = &state.scene.cube.grid;
+
+    surface.fill_background(theme.background);
+
+    // draw grid
+    let cell_width = width / grid.cols_count as f64;
+    let cell_height = height / grid.rows_count as f64;
+
+    for i in 1..grid.cols_count {
+        let x = i as f64 * cell_width;
+        surface.draw_line(
+            (x, 0.0),
+            (x, height),
+            theme.grid_line_width,
+            theme.grid_line,
+        );
+    }
+    for i in 1..grid.rows_count {
+        let y = i as f64 * cell_height;
+        surface.draw_line((0.0, y), (width, y), theme.grid_line_width, theme.grid_line);
+    }
+
+    if !with_objects {
+        return;
+    }
+
+    paint_objects(state, |pos, cell| {
+        if pos.side == side_type {
+            let x = pos.col as f64 * cell_width;
+            let y = height - (pos.row + 1) as f64 * cell_height;
+            surface.paint_cell((x, y, cell_width, cell_height), cell);
+        }
+    });
+}
+
+/// Calls `paint_cell` for every object cell, in the order cells are painted on
+/// sides, so later cells cover earlier ones
+pub fn paint_objects(state: &GameState, mut paint_cell: impl FnMut(&CubePosition, &ObjectCell)) {
+    let theme = state.scene.theme();
+
+    let body = ObjectCell {
+        color: theme.snake_body,
+        alpha: 1.0,
+        sprite: Some(SpriteType::SnakeBody),
+        shape: CellShape::Striped,
+    };
+
+    if let Some(ghost) = &state.ghost {
+        let ghost_body = ObjectCell {
+            alpha: GHOST_ALPHA,
+            ..body
+        };
+        for part in &ghost.state.snake.parts {
+            paint_cell(part, &ghost_body);
+        }
+    }
+
+    // head last so it stays on top of crashed body
+    for part in state.snake.parts.iter().skip(1) {
+        paint_cell(part, &body);
+    }
+    if let Some(head) = state.snake.parts.front() {
+        let head_cell = ObjectCell {
+            color: theme.snake_head,
+            alpha: 1.0,
+            sprite: Some(SpriteType::SnakeHead),
+            shape: CellShape::Square,
+        };
+        paint_cell(head, &head_cell);
+    }
+
+    // sprites are for player's snake only, so rivals are told apart by color
+    let rival_cell = ObjectCell {
+        color: theme.rival,
+        alpha: 1.0,
+        sprite: None,
+        shape: CellShape::Ringed,
+    };
+    for part in state.rivals.iter().flat_map(|rival| &rival.parts) {
+        paint_cell(part, &rival_cell);
+    }
+
+    let apple_cell = ObjectCell {
+        color: theme.apple,
+        alpha: 1.0,
+        sprite: Some(SpriteType::Apple),
+        shape: CellShape::Circle,
+    };
+    for apple in &state.apples {
+        paint_cell(apple, &apple_cell);
+    }
+
+    let stone_cell = ObjectCell {
+        color: theme.stone,
+        alpha: 1.0,
+        sprite: Some(SpriteType::Stone),
+        shape: CellShape::Cross,
+    };
+    for stone in &state.stones {
+        paint_cell(stone, &stone_cell);
+    }
+}
@@ -0,0 +1,273 @@
+mod rasterizer;
+mod side_image;
+
+pub use side_image::paint_side_image;
+
+use std::collections::HashMap;
+
+use num_traits::FromPrimitive;
+
+use crate::{
+    helpers::graphics_math::{self as gmath, Vec3},
+    models::{CubeSideType, GameState},
+};
+
+use self::rasterizer::{rasterize_triangle, ScreenVertex};
+
+use super::{
+    cube_drawer::{
+        geometry::{create_cube_mesh, CUBE_VERTEX_SIZE},
+        get_cube_view,
+    },
+    renderer::Renderer,
+};
+
+/// Renders the scene on CPU into RGBA pixels, without browser. Follows webgl
+/// renderer closely enough to catch regressions in golden image tests, but not
+/// pixel to pixel: no sprites, overlay, objects as meshes or particles
+pub struct SoftwareRenderer {
+    pub width: usize,
+    pub height: usize,
+
+    // RGBA, rows from top to bottom
+    pub pixels: Vec<u8>,
+
+    side_size: usize,
+    sides: HashMap<CubeSideType, Vec<u8>>,
+    depth: Vec<f32>,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: usize, height: usize, side_size: usize) -> Self {
+        SoftwareRenderer {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+            side_size,
+            sides: HashMap::new(),
+            depth: vec![1.0; width * height],
+        }
+    }
+
+    // nearest texel, like texture with NEAREST filter and clamped edges
+    fn sample_side(&self, side_type: CubeSideType, u: f32, v: f32) -> [u8; 4] {
+        // nothing to sample from empty side
+        if self.side_size == 0 {
+            return [0; 4];
+        }
+
+        let image = &self.sides[&side_type];
+        let max = self.side_size - 1;
+
+        let col = ((u * self.side_size as f32) as usize).min(max);
+        let row = ((v * self.side_size as f32) as usize).min(max);
+        let offset = (row * self.side_size + col) * 4;
+
+        image[offset..offset + 4].try_into().unwrap()
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn draw_cube_side(&mut self, state: &mut GameState, side_type: CubeSideType) {
+        let image = paint_side_image(state, side_type, self.side_size);
+        self.sides.insert(side_type, image);
+    }
+
+    fn draw_cube(&mut self, state: &mut GameState) {
+        self.pixels.fill(0);
+        self.depth.fill(1.0);
+
+        let cube = &state.scene.cube;
+        let view = get_cube_view(
+            &cube.current_rotation,
+            self.width as f32 / self.height as f32,
+        );
+        let normal_matrix = gmath::normal_matrix(view.world_matrix);
+
+        let light = &cube.light;
+        let to_light = gmath::normalize(light.direction).map(|c| -c);
+
+        let mesh = create_cube_mesh(&cube.shape, &cube.grid);
+        let mut vertices = Vec::with_capacity(mesh.vertex_coords.len() / CUBE_VERTEX_SIZE);
+
+        for (i, coords) in mesh.vertex_coords.chunks(CUBE_VERTEX_SIZE).enumerate() {
+            let position: Vec3 = [coords[1], coords[2], coords[3]];
+
+            let [x, y, z, w] = gmath::transform_point(view.matrix, position);
+            let world = gmath::transform_point(view.world_matrix, position);
+            let normal = gmath::transform_point(normal_matrix, [coords[4], coords[5], coords[6]]);
+
+            vertices.push(ScreenVertex {
+                x: (x / w + 1.0) / 2.0 * self.width as f32,
+                y: (1.0 - y / w) / 2.0 * self.height as f32,
+                z: z / w,
+                inv_w: 1.0 / w,
+                u: mesh.texture_coords[i * 2],
+                v: mesh.texture_coords[i * 2 + 1],
+                world: [world[0], world[1], world[2]],
+                normal: [normal[0], normal[1], normal[2]],
+            });
+        }
+
+        for triangle in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| &vertices[triangle[i] as usize]);
+
+            // cube is in front of camera, so no clipping is done
+            if a.inv_w <= 0.0 || b.inv_w <= 0.0 || c.inv_w <= 0.0 {
+                continue;
+            }
+
+            // cull back faces. counter-clockwise is front, but y is flipped on screen
+            let area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+            if area >= 0.0 {
+                continue;
+            }
+
+            let side_index = mesh.vertex_coords[triangle[0] as usize * CUBE_VERTEX_SIZE];
+            let side_type = CubeSideType::from_f32(side_index).unwrap();
+
+            let mut fragments = vec![];
+            rasterize_triangle([a, b, c], self.width, self.height, &mut self.depth, |f| {
+                fragments.push(f)
+            });
+
+            for fragment in fragments {
+                let texel = self.sample_side(side_type, fragment.u, fragment.v);
+
+                // same as apply_light in cube shaders
+                let normal = gmath::normalize(fragment.normal);
+                let diffuse = gmath::dot(normal, to_light).max(0.0);
+                let to_camera =
+                    gmath::normalize(gmath::subtract_vectors(view.camera_pos, fragment.world));
+                let half_vector = gmath::normalize([
+                    to_light[0] + to_camera[0],
+                    to_light[1] + to_camera[1],
+                    to_light[2] + to_camera[2],
+                ]);
+                let specular = if diffuse > 0.0 {
+                    light.specular
+                        * gmath::dot(normal, half_vector)
+                            .max(0.0)
+                            .powf(light.shininess)
+                } else {
+                    0.0
+                };
+                let brightness = light.ambient + (1.0 - light.ambient) * diffuse;
+
+                let offset = (fragment.y * self.width + fragment.x) * 4;
+                let pixel = &mut self.pixels[offset..offset + 3];
+                for (channel, value) in pixel.iter_mut().zip(texel) {
+                    let color = value as f32 / 255.0 * brightness + specular;
+                    *channel = (color.clamp(0.0, 1.0) * 255.0).round() as u8;
+                }
+                self.pixels[offset + 3] = texel[3];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::BufWriter, path::PathBuf};
+
+    use super::SoftwareRenderer;
+    use crate::{
+        actions::game_actions::init_game_state,
+        drawers::scene_drawer::draw_scene,
+        models::{Degrees, GameState, Quaternion, X_AXIS, Y_AXIS},
+    };
+
+    // channels may differ a bit between platforms because of float rounding
+    const TOLERANCE: u8 = 2;
+
+    fn render(rotation: Quaternion) -> SoftwareRenderer {
+        let mut state = GameState::default();
+        init_game_state(&mut state, 42);
+        state.scene.cube.current_rotation = rotation;
+
+        let mut renderer = SoftwareRenderer::new(96, 96, 64);
+        draw_scene(&mut renderer, &mut state);
+        renderer
+    }
+
+    fn golden_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/drawers/software_renderer/golden")
+            .join(format!("{}.png", name))
+    }
+
+    fn write_png(path: &PathBuf, renderer: &SoftwareRenderer) {
+        let file = File::create(path).unwrap();
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(file),
+            renderer.width as u32,
+            renderer.height as u32,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&renderer.pixels).unwrap();
+    }
+
+    fn read_png(path: &PathBuf) -> Vec<u8> {
+        let decoder = png::Decoder::new(File::open(path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        pixels.truncate(info.buffer_size());
+        pixels
+    }
+
+    // run with UPDATE_GOLDEN=1 to accept changes in rendering
+    fn assert_golden(name: &str, renderer: &SoftwareRenderer) {
+        let path = golden_path(name);
+
+        if std::env::var("UPDATE_GOLDEN").is_ok() {
+            write_png(&path, renderer);
+            return;
+        }
+
+        let expected = read_png(&path);
+        let is_matching = expected.len() == renderer.pixels.len()
+            && expected
+                .iter()
+                .zip(&renderer.pixels)
+                .all(|(a, b)| a.abs_diff(*b) <= TOLERANCE);
+
+        if !is_matching {
+            let actual_path = std::env::temp_dir().join(format!("{}.actual.png", name));
+            write_png(&actual_path, renderer);
+            panic!(
+                "rendered image differs from {:?}, see {:?}",
+                path, actual_path
+            );
+        }
+    }
+
+    #[test]
+    fn renders_front_view() {
+        let renderer = render(Quaternion::default());
+        assert_golden("front", &renderer);
+    }
+
+    #[test]
+    fn renders_rotated_view() {
+        let renderer = render(
+            Quaternion::from_axis_angle(&X_AXIS, Degrees(-30.0).into())
+                * Quaternion::from_axis_angle(&Y_AXIS, Degrees(40.0).into()),
+        );
+        assert_golden("rotated", &renderer);
+    }
+
+    #[test]
+    fn renders_with_empty_sides() {
+        let mut state = GameState::default();
+        init_game_state(&mut state, 42);
+
+        let mut renderer = SoftwareRenderer::new(16, 16, 0);
+        draw_scene(&mut renderer, &mut state);
+
+        // cube is there, but has nothing to show
+        assert!(renderer.pixels.chunks(4).all(|pixel| pixel[3] == 0));
+    }
+}
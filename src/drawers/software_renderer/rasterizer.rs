@@ -0,0 +1,95 @@
+use crate::helpers::graphics_math::Vec3;
+
+/// Triangle vertex after projection to screen
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenVertex {
+    // pixels, from top left corner
+    pub x: f32,
+    pub y: f32,
+
+    // depth in normalized device coordinates (-1..1)
+    pub z: f32,
+
+    // 1 / w of clip coordinates, for perspective correct interpolation
+    pub inv_w: f32,
+
+    // texture coordinates
+    pub u: f32,
+    pub v: f32,
+
+    // position and normal in world space, for lighting
+    pub world: Vec3,
+    pub normal: Vec3,
+}
+
+/// Interpolated values at pixel of triangle
+pub struct Fragment {
+    pub x: usize,
+    pub y: usize,
+    pub u: f32,
+    pub v: f32,
+    pub world: Vec3,
+    pub normal: Vec3,
+}
+
+/// Calls `on_fragment` for every pixel which center is inside triangle and
+/// which passes depth test, updating depth buffer
+pub fn rasterize_triangle(
+    [a, b, c]: [&ScreenVertex; 3],
+    width: usize,
+    height: usize,
+    depth: &mut [f32],
+    mut on_fragment: impl FnMut(Fragment),
+) {
+    let area = edge(a, b, c.x, c.y);
+    if area == 0.0 {
+        return;
+    }
+
+    let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as usize;
+    let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as usize;
+    let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.0) as usize).min(width);
+    let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.0) as usize).min(height);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let px = x as f32 + 0.5;
+            let py = y as f32 + 0.5;
+
+            // barycentric weights, all positive inside triangle of any winding
+            let wa = edge(b, c, px, py) / area;
+            let wb = edge(c, a, px, py) / area;
+            let wc = edge(a, b, px, py) / area;
+
+            if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                continue;
+            }
+
+            let z = wa * a.z + wb * b.z + wc * c.z;
+            let depth_value = &mut depth[y * width + x];
+            if z >= *depth_value {
+                continue;
+            }
+            *depth_value = z;
+
+            // attributes are linear in world space, not in screen space
+            let inv_w = wa * a.inv_w + wb * b.inv_w + wc * c.inv_w;
+            let interpolate = |va: f32, vb: f32, vc: f32| {
+                (wa * va * a.inv_w + wb * vb * b.inv_w + wc * vc * c.inv_w) / inv_w
+            };
+
+            on_fragment(Fragment {
+                x,
+                y,
+                u: interpolate(a.u, b.u, c.u),
+                v: interpolate(a.v, b.v, c.v),
+                world: std::array::from_fn(|i| interpolate(a.world[i], b.world[i], c.world[i])),
+                normal: std::array::from_fn(|i| interpolate(a.normal[i], b.normal[i], c.normal[i])),
+            });
+        }
+    }
+}
+
+fn edge(a: &ScreenVertex, b: &ScreenVertex, px: f32, py: f32) -> f32 {
+    (b.x - a.x) * (py - a.y) - (b.y - a.y) * (px - a.x)
+}
@@ -0,0 +1,81 @@
+use crate::{
+    drawers::side_painter::{paint_side, ObjectCell, SideSurface},
+    models::{Color, CubeSideType, GameState},
+};
+
+/// Paints cube side into RGBA image of given size, the same way side canvas is
+/// painted, except that sprites and shape coding are left out. Objects are
+/// always painted, since there are no object meshes without browser
+pub fn paint_side_image(state: &GameState, side_type: CubeSideType, size: usize) -> Vec<u8> {
+    let mut image = SideImage {
+        pixels: vec![0; size * size * 4],
+        size,
+    };
+
+    paint_side(
+        state,
+        side_type,
+        &mut image,
+        (size as f64, size as f64),
+        true,
+    );
+
+    image.pixels
+}
+
+struct SideImage {
+    pixels: Vec<u8>,
+    size: usize,
+}
+
+impl SideImage {
+    // fills pixels which centers are inside the rect
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color, alpha: f32) {
+        let to_range = |from: f32, len: f32| {
+            let start = (from - 0.5).ceil().max(0.0) as usize;
+            let end = ((from + len - 0.5).ceil().max(0.0) as usize).min(self.size);
+            start..end
+        };
+
+        for row in to_range(y, height) {
+            for col in to_range(x, width) {
+                let offset = (row * self.size + col) * 4;
+                let pixel = &mut self.pixels[offset..offset + 3];
+
+                let Color(r, g, b) = Color(pixel[0], pixel[1], pixel[2]).blend(color, alpha);
+                pixel.copy_from_slice(&[r, g, b]);
+            }
+        }
+    }
+}
+
+impl SideSurface for SideImage {
+    fn fill_background(&mut self, color: Color) {
+        self.pixels = [color.0, color.1, color.2, 255].repeat(self.size * self.size);
+    }
+
+    fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: Color) {
+        let half = width / 2.0;
+        let (x, y) = (from.0.min(to.0), from.1.min(to.1));
+
+        // lines are axis aligned, so they are thin rects
+        let (x, y, w, h) = if from.0 == to.0 {
+            (x - half, y, width, (to.1 - from.1).abs())
+        } else {
+            (x, y - half, (to.0 - from.0).abs(), width)
+        };
+
+        self.fill_rect(x as f32, y as f32, w as f32, h as f32, color, 1.0);
+    }
+
+    fn paint_cell(&mut self, (x, y, width, height): (f64, f64, f64, f64), cell: &ObjectCell) {
+        self.fill_rect(
+            x as f32,
+            y as f32,
+            width as f32,
+            height as f32,
+            cell.color,
+            cell.alpha,
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use num_traits::FromPrimitive;
+
+use crate::{
+    actions::{
+        game_actions::plant_objects,
+        snake_actions::{move_snakes, set_snake_direction},
+    },
+    drawers::{scene_drawer::draw_scene, software_renderer::SoftwareRenderer},
+    models::{Direction, GameRules, GameState, GameStatus},
+};
+
+use super::{encode_observation, Observation, ObservationConfig, DIRECTIONS_COUNT};
+
+pub const ACTIONS_COUNT: usize = DIRECTIONS_COUNT;
+
+// size of cube side images for render, in pixels
+const RENDER_SIDE_SIZE: usize = 128;
+
+/// Rewards given to agent per step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rewards {
+    pub apple: f64,
+    pub death: f64,
+
+    // given on every step, usually negative to make agent hurry up
+    pub step: f64,
+}
+
+impl Default for Rewards {
+    fn default() -> Self {
+        Rewards {
+            apple: 1.0,
+            death: -1.0,
+            step: -0.01,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnvConfig {
+    pub rules: GameRules,
+    pub rewards: Rewards,
+    pub observation: ObservationConfig,
+
+    // episode is cut after this number of steps, so agent which learned to
+    // loop forever does not stall training
+    pub max_steps: Option<u32>,
+}
+
+/// Extra details about step which are not part of observation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Info {
+    pub tick: u32,
+    pub apples_eaten: usize,
+    pub status: GameStatus,
+
+    // episode was cut by max steps limit, not ended by game rules
+    pub is_truncated: bool,
+}
+
+/// Single-player game as reinforcement learning environment in style of gym.
+/// Runs the same simulation as the browser game, but steps it on demand
+pub struct Env {
+    pub config: EnvConfig,
+    pub state: GameState,
+}
+
+impl Env {
+    /// Fails if objects of the rules don't fit on the board, since planting
+    /// them would never end
+    pub fn new(config: EnvConfig) -> Result<Self, String> {
+        config.rules.validate()?;
+
+        Ok(Env {
+            config,
+            state: GameState::default(),
+        })
+    }
+
+    pub fn reset(&mut self, seed: u32) -> Observation {
+        self.state = GameState {
+            seed,
+            rules: self.config.rules.clone(),
+            status: GameStatus::InGame,
+            ..GameState::default()
+        };
+
+        plant_objects(&mut self.state);
+
+        self.observe()
+    }
+
+    /// Turns snake and moves it one cell. Turning back is ignored as in game
+    pub fn step(&mut self, action: Direction) -> (Observation, f64, bool, Info) {
+        if self.state.status != GameStatus::InGame || self.is_truncated() {
+            return (self.observe(), 0.0, true, self.info());
+        }
+
+        let state = &mut self.state;
+        let rewards = &self.config.rewards;
+
+        let apples_count = state.apples.len();
+
+        set_snake_direction(&mut state.snake, action);
+        move_snakes(state);
+
+        // nothing renders effects here
+        state.events.clear();
+
+        let mut reward = rewards.step;
+        reward += (apples_count - state.apples.len()) as f64 * rewards.apple;
+
+        if state.snake.is_crashed {
+            reward += rewards.death;
+            state.status = GameStatus::Fail;
+        } else if state.apples.is_empty() {
+            state.status = GameStatus::Win;
+        }
+
+        let done = state.status != GameStatus::InGame || self.is_truncated();
+
+        (self.observe(), reward, done, self.info())
+    }
+
+    /// Same as step, but action is index in [0, ACTIONS_COUNT)
+    pub fn step_index(&mut self, action: usize) -> (Observation, f64, bool, Info) {
+        let direction = Direction::from_usize(action)
+            .unwrap_or_else(|| panic!("invalid action index: {}", action));
+
+        self.step(direction)
+    }
+
+    pub fn observe(&self) -> Observation {
+        encode_observation(&self.state, &self.config.observation)
+    }
+
+    /// Renders current frame into RGBA pixels, rows from top to bottom
+    pub fn render(&mut self, width: usize, height: usize) -> Vec<u8> {
+        let mut renderer = SoftwareRenderer::new(width, height, RENDER_SIDE_SIZE);
+        draw_scene(&mut renderer, &mut self.state);
+        renderer.pixels
+    }
+
+    fn info(&self) -> Info {
+        Info {
+            tick: self.state.tick,
+            apples_eaten: self.state.rules.apples_count - self.state.apples.len(),
+            status: self.state.status,
+            is_truncated: self.is_truncated(),
+        }
+    }
+
+    fn is_truncated(&self) -> bool {
+        self.state.status == GameStatus::InGame
+            && matches!(self.config.max_steps, Some(max_steps) if self.state.tick >= max_steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        env::ObservationChannel,
+        helpers::cube::get_next_cube_position_and_direction,
+        models::{CubePosition, CubeSideType},
+    };
+
+    fn create_env() -> Env {
+        Env::new(EnvConfig {
+            rules: GameRules {
+                grid_size: 4,
+                apples_count: 3,
+                stones_count: 2,
+            },
+            ..EnvConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn rules_that_cannot_be_planted() {
+        let config = |grid_size, apples_count| EnvConfig {
+            rules: GameRules {
+                grid_size,
+                apples_count,
+                stones_count: 0,
+            },
+            ..EnvConfig::default()
+        };
+
+        assert!(Env::new(config(1, 1)).is_err());
+        assert!(Env::new(config(2, 100)).is_err());
+        assert!(Env::new(config(2, 1)).is_ok());
+    }
+
+    #[test]
+    fn reset_observation() {
+        let mut env = create_env();
+        let obs = env.reset(1);
+
+        assert_eq!(obs.shape(), [3, 6, 4, 4]);
+        assert_eq!(obs.grids.len(), 3 * 6 * 4 * 4);
+
+        // one snake cell, then apples and stones
+        let count = |channel: usize| {
+            let size = 6 * 4 * 4;
+            obs.grids[channel * size..(channel + 1) * size]
+                .iter()
+                .filter(|cell| **cell == 1.0)
+                .count()
+        };
+        assert_eq!(count(0), 1);
+        assert_eq!(count(1), 3);
+        assert_eq!(count(2), 2);
+
+        let head = CubePosition {
+            side: CubeSideType::Front,
+            row: 0,
+            col: 0,
+        };
+        assert_eq!(obs.head, head);
+        assert_eq!(obs.get(0, &head), 1.0);
+        assert_eq!(obs.direction, Direction::Right);
+
+        assert_eq!(obs.to_features().len(), obs.grids.len() + 6 + 2 + 4);
+    }
+
+    #[test]
+    fn same_seed_same_episode() {
+        let mut a = create_env();
+        let mut b = create_env();
+
+        assert_eq!(a.reset(7), b.reset(7));
+
+        for i in 0..50 {
+            let action = [0, 0, 3, 3, 1, 2][i % 6];
+            assert_eq!(a.step_index(action), b.step_index(action));
+        }
+    }
+
+    #[test]
+    fn rewards() {
+        let mut env = create_env();
+        env.config.rewards = Rewards {
+            apple: 10.0,
+            death: -100.0,
+            step: -1.0,
+        };
+
+        // puts given objects on the cell in front of the head
+        let reset_with_next_cell = |env: &mut Env, apples: usize, stones: usize| {
+            env.reset(3);
+
+            let state = &mut env.state;
+            let head = state.snake.parts[0];
+            let (next, _) = get_next_cube_position_and_direction(
+                &head,
+                state.snake.direction,
+                &state.scene.cube.grid,
+            );
+            let far = CubePosition {
+                side: CubeSideType::Back,
+                ..next
+            };
+
+            state.apples = [next, far].into_iter().take(apples).collect();
+            state.stones = [next].into_iter().take(stones).collect();
+            state.rules.apples_count = apples;
+        };
+
+        // eating apple
+        reset_with_next_cell(&mut env, 2, 0);
+        let direction = env.state.snake.direction;
+        let (_, reward, done, info) = env.step(direction);
+        assert_eq!(reward, 9.0);
+        assert!(!done);
+        assert_eq!(info.apples_eaten, 1);
+
+        // eating the last apple wins
+        reset_with_next_cell(&mut env, 1, 0);
+        let (_, reward, done, info) = env.step(direction);
+        assert_eq!(reward, 9.0);
+        assert!(done);
+        assert_eq!(info.status, GameStatus::Win);
+
+        // crashing into stone
+        reset_with_next_cell(&mut env, 0, 1);
+        env.state.apples.insert(CubePosition {
+            side: CubeSideType::Back,
+            row: 0,
+            col: 0,
+        });
+        env.state.rules.apples_count = 1;
+        let (_, reward, done, info) = env.step(direction);
+        assert_eq!(reward, -101.0);
+        assert!(done);
+        assert_eq!(info.status, GameStatus::Fail);
+
+        // stepping finished episode changes nothing
+        let (_, reward, done, _) = env.step(Direction::Up);
+        assert_eq!(reward, 0.0);
+        assert!(done);
+    }
+
+    #[test]
+    fn truncation() {
+        let mut env = create_env();
+        env.config.max_steps = Some(2);
+        env.config.observation.channels = vec![ObservationChannel::SnakeHead];
+        env.reset(1);
+
+        // nothing on the way, and the only apple is out of reach
+        env.state.stones.clear();
+        env.state.apples = [CubePosition {
+            side: CubeSideType::Back,
+            row: 0,
+            col: 0,
+        }]
+        .into_iter()
+        .collect();
+        env.state.rules.apples_count = 1;
+
+        let (_, _, done, info) = env.step(Direction::Right);
+        assert!(!done && !info.is_truncated);
+
+        let (obs, _, done, info) = env.step(Direction::Right);
+        assert_eq!(obs.shape(), [1, 6, 4, 4]);
+        assert!(done);
+        assert!(info.is_truncated);
+        assert_eq!(info.status, GameStatus::InGame);
+    }
+}
@@ -0,0 +1,5 @@
+mod environment;
+mod observation;
+
+pub use environment::*;
+pub use observation::*;
@@ -0,0 +1,118 @@
+use crate::models::{CubePosition, Direction, GameState};
+
+pub const SIDES_COUNT: usize = 6;
+pub const DIRECTIONS_COUNT: usize = 4;
+
+/// Kind of objects marked in occupancy grid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObservationChannel {
+    // whole snake including head
+    Snake,
+    SnakeHead,
+    SnakeBody,
+    Apples,
+    Stones,
+}
+
+/// Which occupancy grids go into observation, one grid stack per channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObservationConfig {
+    pub channels: Vec<ObservationChannel>,
+}
+
+impl Default for ObservationConfig {
+    fn default() -> Self {
+        ObservationConfig {
+            channels: vec![
+                ObservationChannel::Snake,
+                ObservationChannel::Apples,
+                ObservationChannel::Stones,
+            ],
+        }
+    }
+}
+
+/// What agent sees of the game
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    // occupancy grids of shape [channels][sides][rows][cols], 1.0 where object
+    // of the channel is in the cell and 0.0 elsewhere. sides are indexed by
+    // CubeSideType values, row 0 is the bottom one as on cube side textures
+    pub grids: Vec<f32>,
+    pub channels_count: usize,
+    pub grid_size: usize,
+
+    pub head: CubePosition,
+    pub direction: Direction,
+}
+
+impl Observation {
+    pub fn shape(&self) -> [usize; 4] {
+        [
+            self.channels_count,
+            SIDES_COUNT,
+            self.grid_size,
+            self.grid_size,
+        ]
+    }
+
+    pub fn get(&self, channel: usize, pos: &CubePosition) -> f32 {
+        self.grids[get_cell_index(channel, pos, self.grid_size)]
+    }
+
+    /// Flat feature vector: grids, then one-hot head side, head row and col
+    /// scaled to [0, 1], then one-hot direction
+    pub fn to_features(&self) -> Vec<f32> {
+        let mut features = self.grids.clone();
+
+        let mut side = [0.0; SIDES_COUNT];
+        side[self.head.side as usize] = 1.0;
+        features.extend_from_slice(&side);
+
+        let max_index = (self.grid_size - 1).max(1) as f32;
+        features.push(self.head.row as f32 / max_index);
+        features.push(self.head.col as f32 / max_index);
+
+        let mut direction = [0.0; DIRECTIONS_COUNT];
+        direction[self.direction as usize] = 1.0;
+        features.extend_from_slice(&direction);
+
+        features
+    }
+}
+
+pub fn encode_observation(state: &GameState, config: &ObservationConfig) -> Observation {
+    let grid_size = state.rules.grid_size as usize;
+    let channels_count = config.channels.len();
+
+    let mut grids = vec![0.0; channels_count * SIDES_COUNT * grid_size * grid_size];
+
+    for (channel, channel_type) in config.channels.iter().enumerate() {
+        let parts = &state.snake.parts;
+
+        let positions: Box<dyn Iterator<Item = &CubePosition>> = match channel_type {
+            ObservationChannel::Snake => Box::new(parts.iter()),
+            ObservationChannel::SnakeHead => Box::new(parts.iter().take(1)),
+            ObservationChannel::SnakeBody => Box::new(parts.iter().skip(1)),
+            ObservationChannel::Apples => Box::new(state.apples.iter()),
+            ObservationChannel::Stones => Box::new(state.stones.iter()),
+        };
+
+        for pos in positions {
+            grids[get_cell_index(channel, pos, grid_size)] = 1.0;
+        }
+    }
+
+    Observation {
+        grids,
+        channels_count,
+        grid_size,
+        head: *state.snake.parts.front().unwrap(),
+        direction: state.snake.direction,
+    }
+}
+
+fn get_cell_index(channel: usize, pos: &CubePosition, grid_size: usize) -> usize {
+    let side = channel * SIDES_COUNT + pos.side as usize;
+    (side * grid_size + pos.row as usize) * grid_size + pos.col as usize
+}
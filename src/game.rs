@@ -3,14 +3,29 @@ use std::{cell::RefCell, ops::Deref, rc::Rc};
 use wasm_bindgen::{prelude::Closure, JsCast};
 
 use crate::{
-    actions::{control_actions, game_actions},
-    drawers::scene_drawer,
+    actions::{
+        control_actions, game_actions, network_actions, palette_actions, quality_actions,
+        replay_actions, spectator_actions,
+    },
+    drawers::{
+        export_drawer::{save_net_png, save_net_svg},
+        scene_drawer,
+    },
     helpers::{
-        canvas::resize_canvas,
-        dom::{subscribe_to_event, unsubscribe_from_event},
+        dom::{
+            download_file, read_dropped_file, subscribe_to_event, subscribe_to_target_event,
+            unsubscribe_from_event, unsubscribe_from_target_event,
+        },
         raf::start_request_animation_frame_loop,
+        replay_file::{decode_replay, encode_replay, REPLAY_FILE_EXTENSION},
+    },
+    models::{GameState, GameStatus},
+    network::{
+        client::NetworkClient,
+        protocol::ServerMessage,
+        snapshot::{SnapshotMessage, SnapshotPublisher, SnapshotReceiver},
+        snapshot_transport::SnapshotTransport,
     },
-    models::{GameState, Size},
 };
 
 #[derive(Default)]
@@ -26,6 +41,19 @@ pub struct Game {
     on_mousedown: Option<Closure<dyn FnMut(web_sys::Event)>>,
     on_mouseup: Option<Closure<dyn FnMut(web_sys::Event)>>,
     on_mousemove: Option<Closure<dyn FnMut(web_sys::Event)>>,
+    on_dragover: Option<Closure<dyn FnMut(web_sys::Event)>>,
+    on_drop: Option<Closure<dyn FnMut(web_sys::Event)>>,
+    on_context_lost: Option<Closure<dyn FnMut(web_sys::Event)>>,
+    on_context_restored: Option<Closure<dyn FnMut(web_sys::Event)>>,
+
+    // lets L key drop and restore webgl context, to check recovery
+    lose_context_ext: Option<web_sys::WebglLoseContext>,
+
+    network_client: Option<NetworkClient>,
+
+    snapshot_transport: Option<SnapshotTransport>,
+    snapshot_publisher: Option<SnapshotPublisher>,
+    snapshot_receiver: SnapshotReceiver,
 }
 
 // 1. create wrapper-type, since game only used through pointer.
@@ -64,11 +92,32 @@ impl GameRc {
 
         let game = GameRc(Rc::new(RefCell::new(Game::default())));
 
-        game_actions::init_game_state(&mut game.borrow_mut().state);
+        // fixed seed gives the same board on every restart, so player can race
+        // against the best run on it, eg. http://localhost:8080/?seed=42
+        let search = window.location().search().unwrap();
+        let params = web_sys::UrlSearchParams::new_with_str(&search).unwrap();
+        let fixed_seed = params.get("seed").and_then(|seed| seed.parse::<u32>().ok());
+
+        let seed = fixed_seed.unwrap_or((js_sys::Math::random() * u32::MAX as f64) as u32);
+        game.borrow_mut().state.is_seed_fixed = fixed_seed.is_some();
+        game_actions::init_game_state(&mut game.borrow_mut().state, seed);
+        quality_actions::load_quality(&mut game.borrow_mut().state);
+        palette_actions::load_palette(&mut game.borrow_mut().state);
+        control_actions::load_control_mapping(&mut game.borrow_mut().state);
         scene_drawer::init_scene_drawer(&mut game.borrow_mut().state, canvas);
 
+        // context loss can be simulated with ?lose_context param
+        if params.has("lose_context") {
+            let mut game = game.borrow_mut();
+            let ctx = game.state.scene.ctx.as_ref().unwrap();
+            let ext = ctx.get_extension("WEBGL_lose_context").ok().flatten();
+            game.lose_context_ext = ext.map(|ext| ext.unchecked_into());
+        }
+
         game.on_resize();
         game.subscribe();
+        game.connect();
+        game.connect_snapshots();
 
         // start game loop
         start_request_animation_frame_loop(Box::new(move || {
@@ -78,8 +127,10 @@ impl GameRc {
     }
 
     pub fn loop_(&self) {
+        quality_actions::adapt_quality_loop(&mut self.borrow_mut().state);
         game_actions::update_game_state_loop(&mut self.borrow_mut().state);
         scene_drawer::draw_scene_loop(&mut self.borrow_mut().state);
+        self.publish_snapshot();
     }
 
     fn subscribe(&self) {
@@ -119,6 +170,43 @@ impl GameRc {
             }),
             &mut self.borrow_mut().on_mousemove,
         );
+        subscribe_to_event(
+            "dragover",
+            // allow dropping files on the page
+            Box::new(|event| event.prevent_default()),
+            &mut self.borrow_mut().on_dragover,
+        );
+        let game = self.clone();
+        subscribe_to_event(
+            "drop",
+            Box::new(move |event| {
+                event.prevent_default();
+                let event = event.dyn_into::<web_sys::DragEvent>().unwrap();
+                game.on_drop(event);
+            }),
+            &mut self.borrow_mut().on_drop,
+        );
+
+        // context events are fired on canvas and don't bubble
+        let canvas = self.borrow().state.scene.canvas.clone().unwrap();
+        let game = self.clone();
+        subscribe_to_target_event(
+            &canvas,
+            "webglcontextlost",
+            Box::new(move |event| {
+                // tells browser that context is going to be restored
+                event.prevent_default();
+                game.on_context_lost();
+            }),
+            &mut self.borrow_mut().on_context_lost,
+        );
+        let game = self.clone();
+        subscribe_to_target_event(
+            &canvas,
+            "webglcontextrestored",
+            Box::new(move |_| game.on_context_restored()),
+            &mut self.borrow_mut().on_context_restored,
+        );
     }
 
     #[allow(dead_code)]
@@ -128,25 +216,238 @@ impl GameRc {
         unsubscribe_from_event("mousedown", &mut self.borrow_mut().on_mousedown);
         unsubscribe_from_event("mouseup", &mut self.borrow_mut().on_mouseup);
         unsubscribe_from_event("mousemove", &mut self.borrow_mut().on_mousemove);
+        unsubscribe_from_event("dragover", &mut self.borrow_mut().on_dragover);
+        unsubscribe_from_event("drop", &mut self.borrow_mut().on_drop);
+
+        let canvas = self.borrow().state.scene.canvas.clone().unwrap();
+        unsubscribe_from_target_event(
+            &canvas,
+            "webglcontextlost",
+            &mut self.borrow_mut().on_context_lost,
+        );
+        unsubscribe_from_target_event(
+            &canvas,
+            "webglcontextrestored",
+            &mut self.borrow_mut().on_context_restored,
+        );
     }
 
-    fn on_resize(&self) {
+    // joins network game if server address is passed in page url, eg.
+    // http://localhost:8080/?server=ws://localhost:9001&name=alice
+    fn connect(&self) {
         let window = web_sys::window().unwrap();
-        let body = window.document().unwrap().body().unwrap();
+        let search = window.location().search().unwrap();
+        let params = web_sys::UrlSearchParams::new_with_str(&search).unwrap();
 
-        let state = &self.borrow().state;
-        let canvas = state.scene.canvas.as_ref().unwrap();
+        let url = match params.get("server") {
+            Some(url) => url,
+            None => return,
+        };
+        let name = params.get("name").unwrap_or_else(|| "player".to_string());
+
+        network_actions::join_network_game(&mut self.borrow_mut().state, &name);
+
+        let game = self.clone();
+        let on_open = Box::new(move || game.flush_network_outbox());
+        let game = self.clone();
+        let on_message = Box::new(move |bytes: Vec<u8>| game.on_server_message(&bytes));
+        let on_close = Box::new(|| web_sys::console::warn_1(&"disconnected from server".into()));
+
+        let client = NetworkClient::connect(&url, on_open, on_message, on_close)
+            .expect("failed to connect to server");
+
+        self.borrow_mut().network_client = Some(client);
+    }
+
+    fn on_server_message(&self, bytes: &[u8]) {
+        let res = ServerMessage::decode(bytes).and_then(|msg| {
+            network_actions::handle_server_message(&mut self.borrow_mut().state, msg)
+        });
+
+        if let Err(err) = res {
+            web_sys::console::error_1(&err.into());
+        }
+
+        self.flush_network_outbox();
+    }
+
+    fn flush_network_outbox(&self) {
+        let game = &mut *self.borrow_mut();
+
+        let session = game.state.network.as_mut();
+        let client = game.network_client.as_ref();
+
+        if let (Some(session), Some(client)) = (session, client) {
+            for msg in session.outbox.drain(..) {
+                client.send(msg).expect("failed to send message to server");
+            }
+        }
+    }
+
+    // publishes game to spectators or spectates another game, if requested in
+    // page url. address is either "tab" for other tabs of the same browser, or
+    // relay server url, eg. ?publish=tab or ?spectate=ws://localhost:9001
+    fn connect_snapshots(&self) {
+        let window = web_sys::window().unwrap();
+        let search = window.location().search().unwrap();
+        let params = web_sys::UrlSearchParams::new_with_str(&search).unwrap();
+
+        if let Some(address) = params.get("publish") {
+            let game = self.clone();
+            let on_spectator_joined = Box::new(move || game.on_spectator_joined());
 
-        let css_size = Size {
-            width: body.client_width() as f64,
-            height: body.client_height() as f64,
+            let transport = SnapshotTransport::publisher(&address, on_spectator_joined)
+                .expect("failed to open snapshot publisher");
+
+            let mut game = self.borrow_mut();
+            game.snapshot_transport = Some(transport);
+            game.snapshot_publisher = Some(SnapshotPublisher::default());
+        } else if let Some(address) = params.get("spectate") {
+            let game = self.clone();
+            let on_snapshot = Box::new(move |bytes: Vec<u8>| game.on_snapshot(&bytes));
+
+            let transport = SnapshotTransport::spectator(&address, on_snapshot)
+                .expect("failed to open snapshot spectator");
+
+            let mut game = self.borrow_mut();
+            game.snapshot_transport = Some(transport);
+            spectator_actions::start_spectating(&mut game.state);
+        }
+    }
+
+    fn publish_snapshot(&self) {
+        let game = &mut *self.borrow_mut();
+
+        let publisher = game.snapshot_publisher.as_mut();
+        let transport = game.snapshot_transport.as_ref();
+
+        if let (Some(publisher), Some(transport)) = (publisher, transport) {
+            if let Some(msg) = publisher.publish(&game.state) {
+                transport
+                    .publish(msg.encode())
+                    .expect("failed to publish snapshot");
+            }
+        }
+    }
+
+    fn on_spectator_joined(&self) {
+        if let Some(publisher) = self.borrow_mut().snapshot_publisher.as_mut() {
+            publisher.request_full_snapshot();
+        }
+    }
+
+    fn on_snapshot(&self, bytes: &[u8]) {
+        let msg = match SnapshotMessage::decode(bytes) {
+            Ok(msg) => msg,
+            Err(err) => return web_sys::console::error_1(&err.into()),
         };
 
-        resize_canvas(canvas, css_size, window.device_pixel_ratio()).unwrap();
+        let game = &mut *self.borrow_mut();
+
+        if let Some(snapshot) = game.snapshot_receiver.receive(msg) {
+            if let Err(err) = spectator_actions::apply_snapshot(&mut game.state, snapshot) {
+                web_sys::console::error_1(&err.into());
+            }
+        }
+    }
+
+    fn on_resize(&self) {
+        scene_drawer::resize_scene(&mut self.borrow_mut().state);
     }
 
     fn on_keydown(&self, event: web_sys::KeyboardEvent) {
+        if event.code() == "KeyR" {
+            return self.download_replay();
+        }
+
+        if event.code() == "KeyN" {
+            return self.download_net(event.shift_key());
+        }
+
+        if event.code() == "KeyL" {
+            return self.toggle_context_loss();
+        }
+
         control_actions::on_keydown(&mut self.borrow_mut().state, &event.code());
+        self.flush_network_outbox();
+    }
+
+    // saves replay of the game that has just ended
+    fn download_replay(&self) {
+        let state = &self.borrow().state;
+
+        let is_game_over = matches!(state.status, GameStatus::Win | GameStatus::Fail);
+        if !is_game_over || state.network.is_some() || state.playback.is_some() {
+            return;
+        }
+
+        let file_name = format!("snake-3d-{}.{}", state.seed, REPLAY_FILE_EXTENSION);
+        let bytes = encode_replay(&state.replay);
+
+        download_file(&file_name, &bytes, "application/octet-stream")
+            .expect("failed to download replay");
+    }
+
+    fn on_context_lost(&self) {
+        let state = &mut self.borrow_mut().state;
+
+        game_actions::pause_on_context_loss(state);
+        scene_drawer::lose_scene_context(state);
+    }
+
+    fn on_context_restored(&self) {
+        let state = &mut self.borrow_mut().state;
+
+        scene_drawer::restore_scene_context(state);
+        game_actions::resume_after_context_restore(state);
+    }
+
+    // drops or restores webgl context, if it's allowed with url param
+    fn toggle_context_loss(&self) {
+        // context events can fire right from these calls, so game must not be
+        // borrowed here
+        let (ext, is_context_lost) = {
+            let game = self.borrow();
+            (
+                game.lose_context_ext.clone(),
+                game.state.scene.is_context_lost,
+            )
+        };
+
+        match ext {
+            Some(ext) if is_context_lost => ext.restore_context(),
+            Some(ext) => ext.lose_context(),
+            None => {}
+        }
+    }
+
+    // saves unfolded cube as PNG, or as SVG with shift
+    fn download_net(&self, is_svg: bool) {
+        let state = &self.borrow().state;
+
+        let result = if is_svg {
+            save_net_svg(state)
+        } else {
+            save_net_png(state)
+        };
+
+        result.expect("failed to download cube net");
+    }
+
+    // starts playback of replay file dropped on the page
+    fn on_drop(&self, event: web_sys::DragEvent) {
+        if self.borrow().state.network.is_some() || self.borrow().state.is_spectator {
+            return;
+        }
+
+        let game = self.clone();
+        read_dropped_file(
+            &event,
+            Box::new(move |bytes| match decode_replay(&bytes) {
+                Ok(replay) => replay_actions::start_playback(&mut game.borrow_mut().state, replay),
+                Err(err) => web_sys::console::error_1(&err.into()),
+            }),
+        );
     }
 
     fn on_mousedown(&self) {
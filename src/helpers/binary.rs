@@ -0,0 +1,205 @@
+use crate::models::{CubePosition, CubeSideType, Direction};
+use num_traits::FromPrimitive;
+
+// compact little-endian binary encoding, shared by network protocol and other
+// formats that go over the wire or into files. hand-written instead of pulling
+// serialization library, since messages are few and simple
+
+#[derive(Default)]
+pub struct BinaryWriter {
+    pub bytes: Vec<u8>,
+}
+
+impl BinaryWriter {
+    pub fn new() -> Self {
+        BinaryWriter::default()
+    }
+
+    pub fn write_u8(&mut self, v: u8) {
+        self.bytes.push(v);
+    }
+
+    pub fn write_u16(&mut self, v: u16) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, v: u32) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, v: u64) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    /// Writes LEB128 variable-length integer: 7 bits per byte, high bit tells
+    /// whether more bytes follow. small numbers take a single byte
+    pub fn write_varint(&mut self, mut v: u64) {
+        loop {
+            let byte = (v & 0x7f) as u8;
+            v >>= 7;
+
+            if v == 0 {
+                self.write_u8(byte);
+                return;
+            }
+
+            self.write_u8(byte | 0x80);
+        }
+    }
+
+    pub fn write_str(&mut self, v: &str) {
+        self.write_u16(v.len() as u16);
+        self.bytes.extend_from_slice(v.as_bytes());
+    }
+
+    pub fn write_direction(&mut self, v: Direction) {
+        self.write_u8(v as u8);
+    }
+
+    pub fn write_position(&mut self, v: &CubePosition) {
+        self.write_u8(v.side as u8);
+        self.write_u16(v.row as u16);
+        self.write_u16(v.col as u16);
+    }
+
+    pub fn write_bytes_with_len(&mut self, v: &[u8]) {
+        self.write_u32(v.len() as u32);
+        self.bytes.extend_from_slice(v);
+    }
+}
+
+pub struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BinaryReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        BinaryReader { bytes, offset: 0 }
+    }
+
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self.offset + count;
+        if end > self.bytes.len() {
+            return Err(format!("unexpected end of data at offset {}", self.offset));
+        }
+
+        let res = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(res)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, String> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap()))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    pub fn read_varint(&mut self) -> Result<u64, String> {
+        let mut v: u64 = 0;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            v |= ((byte & 0x7f) as u64) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+
+        Err("varint is too long".to_string())
+    }
+
+    pub fn read_str(&mut self) -> Result<String, String> {
+        let len = self.read_u16()? as usize;
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|e| format!("invalid string: {e}"))
+    }
+
+    pub fn read_direction(&mut self) -> Result<Direction, String> {
+        let v = self.read_u8()?;
+        FromPrimitive::from_u8(v).ok_or_else(|| format!("invalid direction: {v}"))
+    }
+
+    pub fn read_position(&mut self) -> Result<CubePosition, String> {
+        let side = self.read_u8()?;
+        let side: CubeSideType =
+            FromPrimitive::from_u8(side).ok_or_else(|| format!("invalid cube side: {side}"))?;
+
+        Ok(CubePosition {
+            side,
+            row: self.read_u16()? as i32,
+            col: self.read_u16()? as i32,
+        })
+    }
+
+    pub fn read_bytes_with_len(&mut self) -> Result<Vec<u8>, String> {
+        let len = self.read_u32()? as usize;
+        Ok(self.read_bytes(len)?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let pos = CubePosition {
+            side: CubeSideType::Left,
+            row: 3,
+            col: 15,
+        };
+
+        let mut w = BinaryWriter::new();
+        w.write_u8(1);
+        w.write_u16(2);
+        w.write_u32(3);
+        w.write_u64(4);
+        w.write_str("snake");
+        w.write_direction(Direction::Left);
+        w.write_position(&pos);
+        w.write_bytes_with_len(&[5, 6]);
+
+        let mut r = BinaryReader::new(&w.bytes);
+        assert_eq!(r.read_u8().unwrap(), 1);
+        assert_eq!(r.read_u16().unwrap(), 2);
+        assert_eq!(r.read_u32().unwrap(), 3);
+        assert_eq!(r.read_u64().unwrap(), 4);
+        assert_eq!(r.read_str().unwrap(), "snake");
+        assert!(r.read_direction().unwrap() == Direction::Left);
+        assert!(r.read_position().unwrap() == pos);
+        assert_eq!(r.read_bytes_with_len().unwrap(), vec![5, 6]);
+    }
+
+    #[test]
+    fn varint() {
+        for v in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut w = BinaryWriter::new();
+            w.write_varint(v);
+
+            let mut r = BinaryReader::new(&w.bytes);
+            assert_eq!(r.read_varint().unwrap(), v);
+        }
+
+        let mut w = BinaryWriter::new();
+        w.write_varint(127);
+        assert_eq!(w.bytes.len(), 1);
+    }
+
+    #[test]
+    fn unexpected_end() {
+        let mut r = BinaryReader::new(&[1, 2]);
+        assert!(r.read_u32().is_err());
+    }
+}
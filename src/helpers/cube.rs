@@ -1,24 +1,31 @@
 use num_traits::FromPrimitive;
 
-use crate::models::{
-    Cube, CubePosition, CubeSideType, Degrees, Direction, Grid, ModelRotation, Point3D, Radians,
-};
+use crate::models::{Cube, CubePosition, CubeSideType, Direction, Grid, Point3D, Quaternion};
 
 use crate::drawers::cube_drawer::geometry;
 
-use super::graphics_math::get_angle_between_vectors;
+use super::{
+    graphics_math::{cross, dot, normalize, subtract_vectors, Vec3},
+    random::Random,
+};
 
 pub fn get_position_3d_for_cube_position(pos: &CubePosition, grid: &Grid) -> Point3D {
     let vert_ratio = (pos.row as f64 + 0.5) / grid.rows_count as f64;
     let horiz_ratio = (pos.col as f64 + 0.5) / grid.cols_count as f64;
 
-    let ranges = &geometry::CUBE_SIDE_COORDS_RANGE[&pos.side];
+    get_side_point(pos.side, horiz_ratio, vert_ratio)
+}
+
+/// Gets point on cube side, where ratios go from 0 to 1 along columns and rows.
+/// Cube mesh is built from the same points, so cells always match geometry
+pub fn get_side_point(side: CubeSideType, horiz_ratio: f64, vert_ratio: f64) -> Point3D {
+    let ranges = &geometry::CUBE_SIDE_COORDS_RANGE[&side];
 
     let dx = ranges.x.1 - ranges.x.0;
     let dy = ranges.y.1 - ranges.y.0;
     let dz = ranges.z.1 - ranges.z.0;
 
-    match pos.side {
+    match side {
         CubeSideType::Front | CubeSideType::Back => Point3D {
             x: ranges.x.0 + dx * horiz_ratio,
             y: ranges.y.0 + dy * vert_ratio,
@@ -37,23 +44,93 @@ pub fn get_position_3d_for_cube_position(pos: &CubePosition, grid: &Grid) -> Poi
     }
 }
 
-pub fn get_cube_rotation_for_position(pos: &CubePosition, grid: &Grid) -> ModelRotation {
-    let pos_3d = get_position_3d_for_cube_position(pos, grid);
+/// Gets point between centers of two neighbour cells, where progress is 0 at
+/// `from` cell and 1 at `to` cell. Cells on different sides are connected
+/// through their common edge, so the point always stays on cube surface.
+/// Also returns side the point is on
+pub fn get_interpolated_position_3d(
+    from: &CubePosition,
+    to: &CubePosition,
+    grid: &Grid,
+    progress: f64,
+) -> (Point3D, CubeSideType) {
+    let from_3d = get_position_3d_for_cube_position(from, grid);
+    let to_3d = get_position_3d_for_cube_position(to, grid);
+
+    if from.side == to.side {
+        return (lerp_points(&from_3d, &to_3d, progress), to.side);
+    }
+
+    let [_, _, from_normal] = get_cube_side_axes(from.side);
+    let [_, _, to_normal] = get_cube_side_axes(to.side);
 
-    // angle around X axis
-    let x_vector = Point3D { y: 0.0, ..pos_3d };
-    let x_angle = Radians(pos_3d.y.signum() * *get_angle_between_vectors(&pos_3d, &x_vector));
+    // only neighbour sides have common edge
+    if dot(from_normal, to_normal) != 0.0 {
+        return (to_3d, to.side);
+    }
+
+    // move from cell center straight to the plane of other side
+    let to_normal = Point3D {
+        x: to_normal[0] as f64,
+        y: to_normal[1] as f64,
+        z: to_normal[2] as f64,
+    };
+    let distance_to_edge = 0.5 - dot_points(&from_3d, &to_normal);
+    let edge_3d = Point3D {
+        x: from_3d.x + to_normal.x * distance_to_edge,
+        y: from_3d.y + to_normal.y * distance_to_edge,
+        z: from_3d.z + to_normal.z * distance_to_edge,
+    };
 
-    // angle around Y axis
-    let y_vector = Point3D { x: 0.0, ..pos_3d };
-    let mut y_angle = Radians(-pos_3d.x.signum() * *get_angle_between_vectors(&pos_3d, &y_vector));
-    if pos_3d.z < 0.0 {
-        y_angle = Radians(std::f64::consts::PI - *y_angle);
+    let from_len = get_distance(&from_3d, &edge_3d);
+    let to_len = get_distance(&edge_3d, &to_3d);
+    let len = progress * (from_len + to_len);
+
+    if len < from_len {
+        (lerp_points(&from_3d, &edge_3d, len / from_len), from.side)
+    } else {
+        let to_progress = (len - from_len) / to_len;
+        (lerp_points(&edge_3d, &to_3d, to_progress), to.side)
     }
+}
+
+/// Gets cube rotation which turns given point on cube surface to the camera,
+/// with given heading along the surface pointing up on screen
+pub fn get_cube_rotation_for_point(pos_3d: &Point3D, heading: &Point3D) -> Quaternion {
+    let forward = normalize_point(pos_3d);
+
+    // heading lies on the side, so it only leans towards the camera a bit
+    let lean = dot_points(heading, &forward);
+    let up = normalize_point(&Point3D {
+        x: heading.x - forward.x * lean,
+        y: heading.y - forward.y * lean,
+        z: heading.z - forward.z * lean,
+    });
+
+    let right = Point3D {
+        x: up.y * forward.z - up.z * forward.y,
+        y: up.z * forward.x - up.x * forward.z,
+        z: up.x * forward.y - up.y * forward.x,
+    };
+
+    Quaternion::from_axes(&right, &up, &forward)
+}
+
+/// Gets unit vector of moving in given direction on cube side
+pub fn get_direction_vector(side: CubeSideType, direction: Direction) -> Point3D {
+    let [axis_x, axis_y, _] = get_cube_side_axes(side);
 
-    ModelRotation {
-        x: Degrees::from(x_angle).normalize().round(),
-        y: Degrees::from(y_angle).normalize().round(),
+    let (axis, sign) = match direction {
+        Direction::Up => (axis_y, 1.0),
+        Direction::Down => (axis_y, -1.0),
+        Direction::Right => (axis_x, 1.0),
+        Direction::Left => (axis_x, -1.0),
+    };
+
+    Point3D {
+        x: axis[0] as f64 * sign,
+        y: axis[1] as f64 * sign,
+        z: axis[2] as f64 * sign,
     }
 }
 
@@ -222,10 +299,184 @@ pub fn get_next_cube_position_and_direction(
     (next_pos, next_direction)
 }
 
-pub fn get_random_cube_position(cube: &Cube) -> CubePosition {
+pub fn get_random_cube_position(cube: &Cube, random: &mut Random) -> CubePosition {
     CubePosition {
-        side: FromPrimitive::from_f64(js_sys::Math::random() * cube.sides.len() as f64).unwrap(),
-        row: (js_sys::Math::random() * cube.grid.rows_count as f64).floor() as i32,
-        col: (js_sys::Math::random() * cube.grid.cols_count as f64).floor() as i32,
+        side: FromPrimitive::from_i32(random.next_i32(cube.sides.len() as i32)).unwrap(),
+        row: random.next_i32(cube.grid.rows_count),
+        col: random.next_i32(cube.grid.cols_count),
+    }
+}
+
+/// Gets unit vectors along which columns and rows of cube side grow, and the
+/// outward normal of that side
+pub fn get_cube_side_axes(side: CubeSideType) -> [Vec3; 3] {
+    let grid = Grid {
+        rows_count: 2,
+        cols_count: 2,
+    };
+
+    let get_pos = |row, col| {
+        let pos = get_position_3d_for_cube_position(&CubePosition { side, row, col }, &grid);
+        [pos.x as f32, pos.y as f32, pos.z as f32]
+    };
+
+    let origin = get_pos(0, 0);
+    let axis_x = normalize(subtract_vectors(get_pos(0, 1), origin));
+    let axis_y = normalize(subtract_vectors(get_pos(1, 0), origin));
+
+    [axis_x, axis_y, cross(axis_x, axis_y)]
+}
+
+/// Marks cube position as changed, so object on it is drawn again
+pub fn mark_cube_position_changed(cube: &mut Cube, pos: &CubePosition) {
+    if cube.objects_as_meshes {
+        // objects are not on side textures, so only meshes need redraw
+        cube.needs_redraw = true;
+    } else {
+        let side = cube.sides.get_mut(&pos.side).unwrap();
+        side.dirty_cells.insert((pos.row, pos.col));
+    }
+}
+
+fn lerp_points(a: &Point3D, b: &Point3D, t: f64) -> Point3D {
+    Point3D {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+        z: a.z + (b.z - a.z) * t,
+    }
+}
+
+fn dot_points(a: &Point3D, b: &Point3D) -> f64 {
+    a.x * b.x + a.y * b.y + a.z * b.z
+}
+
+fn normalize_point(p: &Point3D) -> Point3D {
+    let len = dot_points(p, p).sqrt();
+
+    Point3D {
+        x: p.x / len,
+        y: p.y / len,
+        z: p.z / len,
+    }
+}
+
+fn get_distance(a: &Point3D, b: &Point3D) -> f64 {
+    let d = Point3D {
+        x: b.x - a.x,
+        y: b.y - a.y,
+        z: b.z - a.z,
+    };
+
+    dot_points(&d, &d).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn side_axes_point_outward() {
+        let grid = Grid {
+            rows_count: 4,
+            cols_count: 4,
+        };
+
+        for side in Cube::default().side_types {
+            let [axis_x, axis_y, normal] = get_cube_side_axes(side);
+
+            let center = get_position_3d_for_cube_position(
+                &CubePosition {
+                    side,
+                    row: 2,
+                    col: 2,
+                },
+                &grid,
+            );
+
+            assert_eq!(axis_x.iter().map(|v| v.abs()).sum::<f32>(), 1.0);
+            assert_eq!(axis_y.iter().map(|v| v.abs()).sum::<f32>(), 1.0);
+
+            // center of side is half cube size away from cube center
+            let outward = normal[0] as f64 * center.x
+                + normal[1] as f64 * center.y
+                + normal[2] as f64 * center.z;
+            assert_eq!(outward, 0.5);
+        }
+    }
+
+    #[test]
+    fn interpolated_position_bends_over_edge() {
+        let grid = Grid {
+            rows_count: 4,
+            cols_count: 4,
+        };
+
+        // rightmost cell of front side and leftmost cell of right side
+        let from = CubePosition {
+            side: CubeSideType::Front,
+            row: 1,
+            col: 3,
+        };
+        let (to, _) = get_next_cube_position_and_direction(&from, Direction::Right, &grid);
+        assert_eq!(to.side, CubeSideType::Right);
+
+        let (start, start_side) = get_interpolated_position_3d(&from, &to, &grid, 0.0);
+        assert_eq!(start, get_position_3d_for_cube_position(&from, &grid));
+        assert_eq!(start_side, CubeSideType::Front);
+
+        // halfway point is on the edge, so it lies on both sides
+        let (middle, _) = get_interpolated_position_3d(&from, &to, &grid, 0.5);
+        assert!((middle.x - 0.5).abs() < 1e-9);
+        assert!((middle.z - 0.5).abs() < 1e-9);
+
+        // never cuts through the cube
+        for i in 0..=10 {
+            let (point, side) = get_interpolated_position_3d(&from, &to, &grid, i as f64 / 10.0);
+            let [_, _, normal] = get_cube_side_axes(side);
+            let outward = normal[0] as f64 * point.x
+                + normal[1] as f64 * point.y
+                + normal[2] as f64 * point.z;
+            assert!((outward - 0.5).abs() < 1e-9);
+        }
+
+        let (end, end_side) = get_interpolated_position_3d(&from, &to, &grid, 1.0);
+        assert_eq!(end, get_position_3d_for_cube_position(&to, &grid));
+        assert_eq!(end_side, CubeSideType::Right);
+    }
+
+    #[test]
+    fn follow_rotation_turns_heading_up() {
+        let grid = Grid {
+            rows_count: 4,
+            cols_count: 4,
+        };
+        let directions = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ];
+
+        for side in Cube::default().side_types {
+            for direction in directions {
+                let pos = CubePosition {
+                    side,
+                    row: 3,
+                    col: 1,
+                };
+                let pos_3d = get_position_3d_for_cube_position(&pos, &grid);
+                let heading = get_direction_vector(side, direction);
+                let rotation = get_cube_rotation_for_point(&pos_3d, &heading);
+
+                // point faces the camera
+                let point = rotation.rotate_point(&pos_3d);
+                assert!(point.x.abs() < 1e-6 && point.y.abs() < 1e-6 && point.z > 0.0);
+
+                // and moving from it goes straight up on screen
+                let screen_heading = rotation.rotate_point(&heading);
+                assert!(screen_heading.x.abs() < 1e-6);
+                assert!(screen_heading.y > 0.0);
+            }
+        }
     }
 }
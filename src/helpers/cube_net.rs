@@ -0,0 +1,171 @@
+use num_traits::FromPrimitive;
+
+use crate::models::{CubePosition, CubeSideType, Grid, Point2D, Size};
+
+use super::{
+    cube::get_cube_side_axes,
+    graphics_math::{dot, Vec3},
+};
+
+/// Size of unfolded cube net, in sides
+pub const NET_COLS: usize = 4;
+pub const NET_ROWS: usize = 3;
+
+/// Cube side unfolded into the net
+#[derive(Debug, Clone, Copy)]
+pub struct NetSide {
+    pub side: CubeSideType,
+
+    // slot of side in the net, in sides from top left
+    pub col: usize,
+    pub row: usize,
+
+    // directions in 3D space, which point right and up on the net after
+    // side is unfolded
+    pub right: Vec3,
+    pub up: Vec3,
+}
+
+/// Unfolds cube into a cross around given side, so it stays in the middle of
+/// the net upright, with its neighbours around and the opposite side at the
+/// right end
+pub fn get_cube_net(center: CubeSideType) -> [NetSide; 6] {
+    let [x, y, normal] = get_cube_side_axes(center);
+    let neg = |v: Vec3| v.map(|c| -c);
+
+    let slot = |side_normal: Vec3, col, row, right, up| NetSide {
+        side: get_side_with_normal(side_normal),
+        col,
+        row,
+        right,
+        up,
+    };
+
+    // neighbour sides are folded over the common edge, so going over that
+    // edge on the net goes into the cube
+    [
+        slot(normal, 1, 1, x, y),
+        slot(y, 1, 0, x, neg(normal)),
+        slot(neg(y), 1, 2, x, normal),
+        slot(neg(x), 0, 1, normal, y),
+        slot(x, 2, 1, neg(normal), y),
+        slot(neg(normal), 3, 1, neg(x), y),
+    ]
+}
+
+/// Gets bounds of cell in the net, in sides from top left of the net
+pub fn get_net_cell_rect(net_side: &NetSide, pos: &CubePosition, grid: &Grid) -> (Point2D, Size) {
+    let [axis_x, axis_y, _] = get_cube_side_axes(net_side.side);
+
+    // corner of the cell relative to side center, projected onto the net
+    let project = |col: i32, row: i32| {
+        let u = col as f32 / grid.cols_count as f32 - 0.5;
+        let v = row as f32 / grid.rows_count as f32 - 0.5;
+        let offset: Vec3 = std::array::from_fn(|i| u * axis_x[i] + v * axis_y[i]);
+
+        (
+            net_side.col as f64 + 0.5 + dot(offset, net_side.right) as f64,
+            net_side.row as f64 + 0.5 - dot(offset, net_side.up) as f64,
+        )
+    };
+
+    let (x0, y0) = project(pos.col, pos.row);
+    let (x1, y1) = project(pos.col + 1, pos.row + 1);
+
+    (
+        Point2D {
+            x: x0.min(x1),
+            y: y0.min(y1),
+        },
+        Size {
+            width: (x1 - x0).abs(),
+            height: (y1 - y0).abs(),
+        },
+    )
+}
+
+fn get_side_with_normal(normal: Vec3) -> CubeSideType {
+    (0..6)
+        .filter_map(CubeSideType::from_usize)
+        .find(|side| dot(get_cube_side_axes(*side)[2], normal) > 0.5)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{helpers::cube::get_next_cube_position_and_direction, models::Direction};
+
+    #[test]
+    fn front_net_keeps_sides_upright() {
+        let net = get_cube_net(CubeSideType::Front);
+
+        let slots: Vec<_> = net.iter().map(|s| (s.side, s.col, s.row)).collect();
+        assert_eq!(
+            slots,
+            [
+                (CubeSideType::Front, 1, 1),
+                (CubeSideType::Up, 1, 0),
+                (CubeSideType::Down, 1, 2),
+                (CubeSideType::Left, 0, 1),
+                (CubeSideType::Right, 2, 1),
+                (CubeSideType::Back, 3, 1),
+            ]
+        );
+
+        // side images are drawn as they are in this layout
+        for net_side in net {
+            let [axis_x, axis_y, _] = get_cube_side_axes(net_side.side);
+            assert_eq!(net_side.right, axis_x);
+            assert_eq!(net_side.up, axis_y);
+        }
+    }
+
+    #[test]
+    fn neighbour_cells_touch_on_net() {
+        let grid = Grid {
+            rows_count: 4,
+            cols_count: 4,
+        };
+
+        // moving off center side in any direction lands on the touching cell
+        for center in (0..6).filter_map(CubeSideType::from_usize) {
+            let net = get_cube_net(center);
+            let find = |side| net.iter().find(|s| s.side == side).unwrap();
+
+            let cases = [
+                (Direction::Up, 1, 3, 0.0, -1.0),
+                (Direction::Down, 1, 0, 0.0, 1.0),
+                (Direction::Left, 0, 1, -1.0, 0.0),
+                (Direction::Right, 3, 1, 1.0, 0.0),
+            ];
+
+            for (direction, col, row, dx, dy) in cases {
+                let from = CubePosition {
+                    side: center,
+                    row,
+                    col,
+                };
+                let (to, _) = get_next_cube_position_and_direction(&from, direction, &grid);
+
+                let (from_pos, from_size) = get_net_cell_rect(find(from.side), &from, &grid);
+                let (to_pos, _) = get_net_cell_rect(find(to.side), &to, &grid);
+
+                let expected_x = from_pos.x + dx * from_size.width;
+                let expected_y = from_pos.y + dy * from_size.height;
+                assert!(
+                    (to_pos.x - expected_x).abs() < 1e-6,
+                    "{:?} {:?}",
+                    center,
+                    direction
+                );
+                assert!(
+                    (to_pos.y - expected_y).abs() < 1e-6,
+                    "{:?} {:?}",
+                    center,
+                    direction
+                );
+            }
+        }
+    }
+}
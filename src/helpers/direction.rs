@@ -1,4 +1,13 @@
-use crate::models::Direction;
+use crate::models::{CubeSideType, Direction, Point3D, Quaternion};
+
+use super::cube::get_direction_vector;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
 
 pub fn get_opposite_direction(d: Direction) -> Direction {
     match d {
@@ -8,3 +17,133 @@ pub fn get_opposite_direction(d: Direction) -> Direction {
         Direction::Right => Direction::Left,
     }
 }
+
+/// Gets direction on cube side, which looks the closest to given arrow on
+/// screen, when cube is turned with given rotation
+pub fn get_screen_relative_direction(
+    side: CubeSideType,
+    rotation: &Quaternion,
+    arrow: Direction,
+) -> Direction {
+    let (arrow_x, arrow_y) = get_screen_vector(arrow);
+
+    // rotated side axes are projected on screen by dropping depth
+    let get_match = |direction: Direction| {
+        let Point3D { x, y, .. } = rotation.rotate_point(&get_direction_vector(side, direction));
+        x * arrow_x + y * arrow_y
+    };
+
+    DIRECTIONS
+        .into_iter()
+        .max_by(|a, b| get_match(*a).total_cmp(&get_match(*b)))
+        .unwrap()
+}
+
+fn get_screen_vector(arrow: Direction) -> (f64, f64) {
+    match arrow {
+        Direction::Up => (0.0, 1.0),
+        Direction::Down => (0.0, -1.0),
+        Direction::Left => (-1.0, 0.0),
+        Direction::Right => (1.0, 0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        helpers::cube::{
+            get_cube_rotation_for_point, get_cube_side_axes, get_position_3d_for_cube_position,
+        },
+        models::{CubePosition, Degrees, Grid, X_AXIS, Y_AXIS},
+    };
+
+    use super::*;
+
+    const SIDES: [CubeSideType; 6] = [
+        CubeSideType::Front,
+        CubeSideType::Right,
+        CubeSideType::Back,
+        CubeSideType::Left,
+        CubeSideType::Up,
+        CubeSideType::Down,
+    ];
+
+    #[test]
+    fn arrows_follow_screen_for_any_rotation() {
+        let z_axis = Point3D {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        };
+
+        // angles are off round ones, so no arrow is exactly between directions
+        for x in (-173..180).step_by(15) {
+            for y in (-176..180).step_by(15) {
+                for z in (0..360).step_by(40) {
+                    let rotation = Quaternion::from_axis_angle(&z_axis, Degrees(z as f64).into())
+                        * Quaternion::from_axis_angle(&X_AXIS, Degrees(x as f64).into())
+                        * Quaternion::from_axis_angle(&Y_AXIS, Degrees(y as f64).into());
+
+                    for side in SIDES {
+                        let [_, _, normal] = get_cube_side_axes(side);
+                        let normal = rotation.rotate_point(&Point3D {
+                            x: normal[0] as f64,
+                            y: normal[1] as f64,
+                            z: normal[2] as f64,
+                        });
+
+                        // side seen edge-on has no clear screen directions
+                        if normal.z.abs() < 0.1 {
+                            continue;
+                        }
+
+                        for arrow in DIRECTIONS {
+                            let direction = get_screen_relative_direction(side, &rotation, arrow);
+
+                            // snake goes towards arrow on screen
+                            let moved =
+                                rotation.rotate_point(&get_direction_vector(side, direction));
+                            let (arrow_x, arrow_y) = get_screen_vector(arrow);
+                            assert!(moved.x * arrow_x + moved.y * arrow_y > 0.0);
+
+                            // and opposite arrow turns it back
+                            let opposite = get_screen_relative_direction(
+                                side,
+                                &rotation,
+                                get_opposite_direction(arrow),
+                            );
+                            assert_eq!(opposite, get_opposite_direction(direction));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn up_arrow_keeps_heading_with_following_camera() {
+        let grid = Grid {
+            rows_count: 8,
+            cols_count: 8,
+        };
+
+        for side in SIDES {
+            for direction in DIRECTIONS {
+                let pos = CubePosition {
+                    side,
+                    row: 6,
+                    col: 1,
+                };
+                let pos_3d = get_position_3d_for_cube_position(&pos, &grid);
+                let heading = get_direction_vector(side, direction);
+                let rotation = get_cube_rotation_for_point(&pos_3d, &heading);
+
+                let ahead = get_screen_relative_direction(side, &rotation, Direction::Up);
+                assert_eq!(ahead, direction);
+
+                let back = get_screen_relative_direction(side, &rotation, Direction::Down);
+                assert_eq!(back, get_opposite_direction(direction));
+            }
+        }
+    }
+}
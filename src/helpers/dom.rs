@@ -1,16 +1,33 @@
-use wasm_bindgen::{prelude::Closure, JsCast};
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
 
 pub fn subscribe_to_event(
     event_type: &str,
     handler: Box<dyn FnMut(web_sys::Event)>,
     closure: &mut Option<Closure<dyn FnMut(web_sys::Event)>>,
+) {
+    subscribe_to_target_event(&web_sys::window().unwrap(), event_type, handler, closure);
+}
+
+pub fn unsubscribe_from_event(
+    event_type: &str,
+    closure: &mut Option<Closure<dyn FnMut(web_sys::Event)>>,
+) {
+    unsubscribe_from_target_event(&web_sys::window().unwrap(), event_type, closure);
+}
+
+/// Same as subscribe to event, but for events which don't reach window, like
+/// webgl context events fired on canvas
+pub fn subscribe_to_target_event(
+    target: &web_sys::EventTarget,
+    event_type: &str,
+    handler: Box<dyn FnMut(web_sys::Event)>,
+    closure: &mut Option<Closure<dyn FnMut(web_sys::Event)>>,
 ) {
     // wrap rust's closure into js closure, which can be called from js side
     let c = Closure::wrap(handler);
 
     // register created closure as a handler for DOM event
-    web_sys::window()
-        .unwrap()
+    target
         .add_event_listener_with_callback(event_type, c.as_ref().unchecked_ref())
         .unwrap();
 
@@ -18,12 +35,12 @@ pub fn subscribe_to_event(
     *closure = Some(c);
 }
 
-pub fn unsubscribe_from_event(
+pub fn unsubscribe_from_target_event(
+    target: &web_sys::EventTarget,
     event_type: &str,
     closure: &mut Option<Closure<dyn FnMut(web_sys::Event)>>,
 ) {
-    web_sys::window()
-        .unwrap()
+    target
         .remove_event_listener_with_callback(
             event_type,
             closure
@@ -37,3 +54,51 @@ pub fn unsubscribe_from_event(
     // drop closure to free up resources (eg. pointer to game state struct)
     *closure = None;
 }
+
+/// Makes browser save bytes to a file in downloads folder
+pub fn download_file(file_name: &str, bytes: &[u8], mime_type: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_(mime_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+    download_url(file_name, &url)?;
+
+    web_sys::Url::revoke_object_url(&url)
+}
+
+/// Makes browser save file from url (eg. data url of canvas) to downloads folder
+pub fn download_url(file_name: &str, url: &str) -> Result<(), JsValue> {
+    let document = web_sys::window().unwrap().document().unwrap();
+
+    let anchor = document
+        .create_element("a")?
+        .dyn_into::<web_sys::HtmlAnchorElement>()?;
+    anchor.set_href(url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    Ok(())
+}
+
+/// Reads first file dropped onto the page, `on_read` called with its content
+pub fn read_dropped_file(event: &web_sys::DragEvent, on_read: Box<dyn FnOnce(Vec<u8>)>) {
+    let file = match event
+        .data_transfer()
+        .and_then(|data| data.files())
+        .and_then(|files| files.get(0))
+    {
+        Some(file) => file,
+        None => return,
+    };
+
+    let on_loaded = Closure::once(move |buffer: JsValue| {
+        on_read(js_sys::Uint8Array::new(&buffer).to_vec());
+    });
+
+    let _ = file.array_buffer().then(&on_loaded);
+
+    // files are dropped rarely, so leaking small closure is fine here
+    on_loaded.forget();
+}
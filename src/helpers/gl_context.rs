@@ -0,0 +1,230 @@
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    AngleInstancedArrays, HtmlCanvasElement, ImageData, WebGl2RenderingContext, WebGlBuffer,
+    WebGlContextAttributes, WebGlFramebuffer, WebGlProgram, WebGlRenderbuffer,
+    WebGlRenderingContext, WebGlShader, WebGlTexture, WebGlUniformLocation,
+};
+
+/// WebGL context of either version. Most of the API is the same for both
+/// versions, and such calls are simply passed to the actual context. code
+/// which needs version specific features matches on the variant
+pub enum GlContext {
+    WebGl1 {
+        ctx: WebGlRenderingContext,
+
+        // instancing is an extension in webgl 1
+        instancing: Option<AngleInstancedArrays>,
+    },
+    WebGl2(WebGl2RenderingContext),
+}
+
+/// Creates webgl 2 context if it is allowed and supported, or webgl 1 otherwise
+pub fn create_gl_context(
+    canvas: &HtmlCanvasElement,
+    allow_webgl2: bool,
+    antialias: bool,
+) -> Result<GlContext, String> {
+    let mut options = WebGlContextAttributes::new();
+    options.antialias(antialias);
+
+    if allow_webgl2 {
+        if let Ok(Some(ctx)) = canvas.get_context_with_context_options("webgl2", &options) {
+            return Ok(GlContext::WebGl2(ctx.unchecked_into()));
+        }
+    }
+
+    let ctx = canvas
+        .get_context_with_context_options("webgl", &options)
+        .map_err(|_| "failed to get webgl context")?
+        .ok_or("webgl is not supported")?
+        .unchecked_into::<WebGlRenderingContext>();
+
+    let instancing = match ctx.get_extension("ANGLE_instanced_arrays") {
+        Ok(Some(ext)) => Some(ext.unchecked_into()),
+        _ => None,
+    };
+
+    Ok(GlContext::WebGl1 { ctx, instancing })
+}
+
+// generates methods which call the same method on context of any version
+macro_rules! delegate_to_context {
+    ($(pub fn $name:ident(&self $(, $arg:ident: $arg_type:ty)*) $(-> $ret:ty)?;)*) => {
+        impl GlContext {
+            $(
+                // some webgl methods take many arguments, and are kept as is
+                #[allow(clippy::too_many_arguments)]
+                pub fn $name(&self $(, $arg: $arg_type)*) $(-> $ret)? {
+                    match self {
+                        GlContext::WebGl1 { ctx, .. } => ctx.$name($($arg),*),
+                        GlContext::WebGl2(ctx) => ctx.$name($($arg),*),
+                    }
+                }
+            )*
+        }
+    };
+}
+
+delegate_to_context! {
+    pub fn active_texture(&self, texture: u32);
+    pub fn attach_shader(&self, program: &WebGlProgram, shader: &WebGlShader);
+    pub fn bind_buffer(&self, target: u32, buffer: Option<&WebGlBuffer>);
+    pub fn bind_framebuffer(&self, target: u32, framebuffer: Option<&WebGlFramebuffer>);
+    pub fn bind_renderbuffer(&self, target: u32, renderbuffer: Option<&WebGlRenderbuffer>);
+    pub fn bind_texture(&self, target: u32, texture: Option<&WebGlTexture>);
+    pub fn blend_func(&self, sfactor: u32, dfactor: u32);
+    pub fn buffer_data_with_array_buffer_view(&self, target: u32, data: &js_sys::Object, usage: u32);
+    pub fn check_framebuffer_status(&self, target: u32) -> u32;
+    pub fn clear(&self, mask: u32);
+    pub fn compile_shader(&self, shader: &WebGlShader);
+    pub fn create_buffer(&self) -> Option<WebGlBuffer>;
+    pub fn create_framebuffer(&self) -> Option<WebGlFramebuffer>;
+    pub fn create_program(&self) -> Option<WebGlProgram>;
+    pub fn create_renderbuffer(&self) -> Option<WebGlRenderbuffer>;
+    pub fn create_shader(&self, shader_type: u32) -> Option<WebGlShader>;
+    pub fn create_texture(&self) -> Option<WebGlTexture>;
+    pub fn delete_framebuffer(&self, framebuffer: Option<&WebGlFramebuffer>);
+    pub fn delete_renderbuffer(&self, renderbuffer: Option<&WebGlRenderbuffer>);
+    pub fn delete_texture(&self, texture: Option<&WebGlTexture>);
+    pub fn depth_mask(&self, flag: bool);
+    pub fn disable(&self, cap: u32);
+    pub fn disable_vertex_attrib_array(&self, index: u32);
+    pub fn draw_arrays(&self, mode: u32, first: i32, count: i32);
+    pub fn draw_elements_with_i32(&self, mode: u32, count: i32, type_: u32, offset: i32);
+    pub fn enable(&self, cap: u32);
+    pub fn enable_vertex_attrib_array(&self, index: u32);
+    pub fn framebuffer_renderbuffer(&self, target: u32, attachment: u32, renderbuffertarget: u32, renderbuffer: Option<&WebGlRenderbuffer>);
+    pub fn framebuffer_texture_2d(&self, target: u32, attachment: u32, textarget: u32, texture: Option<&WebGlTexture>, level: i32);
+    pub fn generate_mipmap(&self, target: u32);
+    pub fn get_attrib_location(&self, program: &WebGlProgram, name: &str) -> i32;
+    pub fn get_extension(&self, name: &str) -> Result<Option<js_sys::Object>, JsValue>;
+    pub fn get_parameter(&self, pname: u32) -> Result<JsValue, JsValue>;
+    pub fn get_program_info_log(&self, program: &WebGlProgram) -> Option<String>;
+    pub fn get_program_parameter(&self, program: &WebGlProgram, pname: u32) -> JsValue;
+    pub fn get_shader_info_log(&self, shader: &WebGlShader) -> Option<String>;
+    pub fn get_shader_parameter(&self, shader: &WebGlShader, pname: u32) -> JsValue;
+    pub fn get_uniform_location(&self, program: &WebGlProgram, name: &str) -> Option<WebGlUniformLocation>;
+    pub fn link_program(&self, program: &WebGlProgram);
+    pub fn renderbuffer_storage(&self, target: u32, internalformat: u32, width: i32, height: i32);
+    pub fn shader_source(&self, shader: &WebGlShader, source: &str);
+    pub fn tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(&self, target: u32, level: i32, internalformat: i32, width: i32, height: i32, border: i32, format: u32, type_: u32, pixels: Option<&[u8]>) -> Result<(), JsValue>;
+    pub fn tex_parameterf(&self, target: u32, pname: u32, param: f32);
+    pub fn tex_parameteri(&self, target: u32, pname: u32, param: i32);
+    pub fn uniform1f(&self, location: Option<&WebGlUniformLocation>, x: f32);
+    pub fn uniform1i(&self, location: Option<&WebGlUniformLocation>, x: i32);
+    pub fn uniform2f(&self, location: Option<&WebGlUniformLocation>, x: f32, y: f32);
+    pub fn uniform3fv_with_f32_array(&self, location: Option<&WebGlUniformLocation>, data: &[f32]);
+    pub fn uniform_matrix4fv_with_f32_array(&self, location: Option<&WebGlUniformLocation>, transpose: bool, data: &[f32]);
+    pub fn use_program(&self, program: Option<&WebGlProgram>);
+    pub fn vertex_attrib_pointer_with_i32(&self, index: u32, size: i32, type_: u32, normalized: bool, stride: i32, offset: i32);
+    pub fn viewport(&self, x: i32, y: i32, width: i32, height: i32);
+}
+
+impl GlContext {
+    pub fn is_webgl2(&self) -> bool {
+        matches!(self, GlContext::WebGl2(_))
+    }
+
+    // canvas overloads are named differently in webgl 1 and 2 bindings
+
+    pub fn tex_image_2d_with_canvas(
+        &self,
+        target: u32,
+        level: i32,
+        internalformat: i32,
+        format: u32,
+        type_: u32,
+        canvas: &HtmlCanvasElement,
+    ) -> Result<(), JsValue> {
+        match self {
+            GlContext::WebGl1 { ctx, .. } => ctx.tex_image_2d_with_u32_and_u32_and_canvas(
+                target,
+                level,
+                internalformat,
+                format,
+                type_,
+                canvas,
+            ),
+            GlContext::WebGl2(ctx) => ctx.tex_image_2d_with_u32_and_u32_and_html_canvas_element(
+                target,
+                level,
+                internalformat,
+                format,
+                type_,
+                canvas,
+            ),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn tex_sub_image_2d_with_canvas(
+        &self,
+        target: u32,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        format: u32,
+        type_: u32,
+        canvas: &HtmlCanvasElement,
+    ) -> Result<(), JsValue> {
+        match self {
+            GlContext::WebGl1 { ctx, .. } => ctx.tex_sub_image_2d_with_u32_and_u32_and_canvas(
+                target, level, xoffset, yoffset, format, type_, canvas,
+            ),
+            GlContext::WebGl2(ctx) => ctx
+                .tex_sub_image_2d_with_u32_and_u32_and_html_canvas_element(
+                    target, level, xoffset, yoffset, format, type_, canvas,
+                ),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn tex_sub_image_2d_with_image_data(
+        &self,
+        target: u32,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        format: u32,
+        type_: u32,
+        image_data: &ImageData,
+    ) -> Result<(), JsValue> {
+        match self {
+            GlContext::WebGl1 { ctx, .. } => ctx.tex_sub_image_2d_with_u32_and_u32_and_image_data(
+                target, level, xoffset, yoffset, format, type_, image_data,
+            ),
+            GlContext::WebGl2(ctx) => ctx.tex_sub_image_2d_with_u32_and_u32_and_image_data(
+                target, level, xoffset, yoffset, format, type_, image_data,
+            ),
+        }
+    }
+
+    pub fn supports_instancing(&self) -> bool {
+        match self {
+            GlContext::WebGl1 { instancing, .. } => instancing.is_some(),
+            GlContext::WebGl2(_) => true,
+        }
+    }
+
+    pub fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        match self {
+            GlContext::WebGl1 { instancing, .. } => instancing
+                .as_ref()
+                .expect("instancing is not supported")
+                .vertex_attrib_divisor_angle(index, divisor),
+            GlContext::WebGl2(ctx) => ctx.vertex_attrib_divisor(index, divisor),
+        }
+    }
+
+    pub fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, instances_count: i32) {
+        match self {
+            GlContext::WebGl1 { instancing, .. } => instancing
+                .as_ref()
+                .expect("instancing is not supported")
+                .draw_arrays_instanced_angle(mode, first, count, instances_count),
+            GlContext::WebGl2(ctx) => {
+                ctx.draw_arrays_instanced(mode, first, count, instances_count)
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::models::{Point3D, Radians};
+use crate::models::Radians;
 
 // using f32 instead of f64 because webgl api uniform_matrix4fv_with_f32_array
 // receives uniform array of f32 only
@@ -95,6 +95,11 @@ pub fn subtract_vectors(a: Vec3, b: Vec3) -> Vec3 {
     res
 }
 
+/// Computes the dot product of 2 vectors
+pub fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
 /// Computes the cross product of 2 vectors
 pub fn cross(a: Vec3, b: Vec3) -> Vec3 {
     let mut res: Vec3 = [0.0; 3];
@@ -238,89 +243,37 @@ pub fn inverse(m: Matrix4) -> Matrix4 {
     res
 }
 
-/// Multiplies by an x rotation matrix.
-///
-/// This is the optimized version of `multiply(m, x_rotate(angle))`
-pub fn x_rotate(m: Matrix4, angle: Radians) -> Matrix4 {
+pub fn transpose(m: Matrix4) -> Matrix4 {
     let mut res: Matrix4 = [0.0; 16];
 
-    let m10 = m[4];
-    let m11 = m[5];
-    let m12 = m[6];
-    let m13 = m[7];
-    let m20 = m[8];
-    let m21 = m[9];
-    let m22 = m[10];
-    let m23 = m[11];
-    let c = angle.cos() as f32;
-    let s = angle.sin() as f32;
-
-    res[4] = c * m10 + s * m20;
-    res[5] = c * m11 + s * m21;
-    res[6] = c * m12 + s * m22;
-    res[7] = c * m13 + s * m23;
-    res[8] = c * m20 - s * m10;
-    res[9] = c * m21 - s * m11;
-    res[10] = c * m22 - s * m12;
-    res[11] = c * m23 - s * m13;
-
-    if m != res {
-        res[0] = m[0];
-        res[1] = m[1];
-        res[2] = m[2];
-        res[3] = m[3];
-        res[12] = m[12];
-        res[13] = m[13];
-        res[14] = m[14];
-        res[15] = m[15];
+    for row in 0..4 {
+        for col in 0..4 {
+            res[col * 4 + row] = m[row * 4 + col];
+        }
     }
 
     res
 }
 
-/// Multiplies by an y rotation matrix.
-///
-/// This is the optimized version of `multiply(m, y_rotate(angle))`
-pub fn y_rotate(m: Matrix4, angle: Radians) -> Matrix4 {
-    let mut res: Matrix4 = [0.0; 16];
+/// Transforms point by 4-by-4 matrix, returning homogeneous coordinates
+pub fn transform_point(m: Matrix4, v: Vec3) -> [f32; 4] {
+    let mut res = [0.0; 4];
 
-    #[allow(clippy::erasing_op, clippy::identity_op)]
-    {
-        let m00 = m[0 * 4 + 0];
-        let m01 = m[0 * 4 + 1];
-        let m02 = m[0 * 4 + 2];
-        let m03 = m[0 * 4 + 3];
-        let m20 = m[2 * 4 + 0];
-        let m21 = m[2 * 4 + 1];
-        let m22 = m[2 * 4 + 2];
-        let m23 = m[2 * 4 + 3];
-        let c = angle.cos() as f32;
-        let s = angle.sin() as f32;
-
-        res[0] = c * m00 - s * m20;
-        res[1] = c * m01 - s * m21;
-        res[2] = c * m02 - s * m22;
-        res[3] = c * m03 - s * m23;
-        res[8] = c * m20 + s * m00;
-        res[9] = c * m21 + s * m01;
-        res[10] = c * m22 + s * m02;
-        res[11] = c * m23 + s * m03;
-
-        if m != res {
-            res[4] = m[4];
-            res[5] = m[5];
-            res[6] = m[6];
-            res[7] = m[7];
-            res[12] = m[12];
-            res[13] = m[13];
-            res[14] = m[14];
-            res[15] = m[15];
-        }
+    for (row, value) in res.iter_mut().enumerate() {
+        *value = m[row] * v[0] + m[4 + row] * v[1] + m[8 + row] * v[2] + m[12 + row];
     }
 
     res
 }
 
+/// Computes matrix for transforming normals of a model with given world matrix.
+/// Normals can't be transformed by world matrix itself, since they would stop
+/// being perpendicular to surface under non-uniform scaling, so inverse
+/// transpose is used instead. Only upper left 3-by-3 part is meaningful
+pub fn normal_matrix(world_matrix: Matrix4) -> Matrix4 {
+    transpose(inverse(world_matrix))
+}
+
 /// Computes a 4-by-4 perspective transformation matrix given the angular height
 /// of the frustum, the aspect ratio, and the near and far clipping planes.  The
 /// arguments define a frustum extending in the negative z direction.  The given
@@ -363,14 +316,21 @@ pub fn perspective(field_of_view: Radians, aspect: f32, near: f32, far: f32) ->
     res
 }
 
-pub fn get_angle_between_vectors(a: &Point3D, b: &Point3D) -> Radians {
-    let dot_product = a.x * b.x + a.y * b.y + a.z * b.z;
+#[cfg(test)]
+mod tests {
+    use crate::models::{Quaternion, X_AXIS, Y_AXIS};
 
-    let a_len = (a.x * a.x + a.y * a.y + a.z * a.z).sqrt();
-    let b_len = (b.x * b.x + b.y * b.y + b.z * b.z).sqrt();
-    let len_mult = a_len * b_len;
+    use super::*;
 
-    let angle_cos = dot_product / len_mult;
+    #[test]
+    fn normal_matrix_of_rotation_is_rotation() {
+        let rotation = Quaternion::from_axis_angle(&X_AXIS, Radians(0.3))
+            * Quaternion::from_axis_angle(&Y_AXIS, Radians(-1.2));
+        let world_matrix = rotation.to_matrix();
+        let normal_matrix = normal_matrix(world_matrix);
 
-    Radians(angle_cos.acos())
+        for (a, b) in normal_matrix.iter().zip(world_matrix.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
 }
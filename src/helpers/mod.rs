@@ -1,8 +1,15 @@
+pub mod binary;
 pub mod canvas;
 pub mod cube;
+pub mod cube_net;
 pub mod direction;
 pub mod dom;
+pub mod gl_context;
 pub mod graphics_math;
 pub mod raf;
+pub mod random;
 pub mod ranges;
+pub mod replay_file;
+pub mod state_hash;
+pub mod storage;
 pub mod webgl;
@@ -0,0 +1,83 @@
+/// Seedable pseudo-random number generator (xorshift32).
+///
+/// Game simulation should be fully reproducible from a seed, so that every
+/// client of network game ends up with the same apples and stones, which is
+/// not possible with `Math.random()` provided by the browser
+#[derive(Clone)]
+pub struct Random {
+    state: u32,
+}
+
+impl Random {
+    pub fn new(seed: u32) -> Self {
+        // scramble seed so close seeds do not produce similar sequences, and
+        // avoid zero state, which xorshift can never leave
+        let mut state = seed.wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+        if state == 0 {
+            state = 0x2545_F491;
+        }
+
+        Random { state }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Returns random number in range [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        self.next_u32() as f64 / (u32::MAX as f64 + 1.0)
+    }
+
+    /// Returns random integer in range [0, max)
+    pub fn next_i32(&mut self, max: i32) -> i32 {
+        (self.next_f64() * max as f64).floor() as i32
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Random::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Random::new(42);
+        let mut b = Random::new(42);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+
+    #[test]
+    fn different_seeds_different_sequences() {
+        let mut a = Random::new(1);
+        let mut b = Random::new(2);
+
+        assert_ne!(a.next_u32(), b.next_u32());
+    }
+
+    #[test]
+    fn range() {
+        let mut r = Random::new(7);
+
+        for _ in 0..1000 {
+            let f = r.next_f64();
+            assert!((0.0..1.0).contains(&f));
+
+            let i = r.next_i32(16);
+            assert!((0..16).contains(&i));
+        }
+    }
+}
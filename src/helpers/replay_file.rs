@@ -0,0 +1,212 @@
+use num_traits::FromPrimitive;
+
+use crate::models::{GameRules, Replay, ReplayInput};
+
+use super::binary::{BinaryReader, BinaryWriter};
+
+// replay file layout (little-endian):
+//   u8[4]  magic "S3DR"
+//   u8     version
+//   u32    seed
+//   u16    grid size
+//   u16    apples count
+//   u16    stones count
+//   varint ticks count
+//   varint inputs count
+//   varint input[inputs count] - tick delta from previous input * 4 + direction
+const REPLAY_MAGIC: &[u8; 4] = b"S3DR";
+const REPLAY_VERSION: u8 = 1;
+
+pub const REPLAY_FILE_EXTENSION: &str = "s3dr";
+
+pub fn encode_replay(replay: &Replay) -> Vec<u8> {
+    let mut w = BinaryWriter::new();
+
+    w.bytes.extend_from_slice(REPLAY_MAGIC);
+    w.write_u8(REPLAY_VERSION);
+    w.write_u32(replay.seed);
+    w.write_u16(replay.rules.grid_size as u16);
+    w.write_u16(replay.rules.apples_count as u16);
+    w.write_u16(replay.rules.stones_count as u16);
+    w.write_varint(replay.ticks_count as u64);
+    w.write_varint(replay.inputs.len() as u64);
+
+    // inputs are usually few ticks apart, so most of them take single byte
+    let mut prev_tick = 0;
+    for input in &replay.inputs {
+        let tick_delta = (input.tick - prev_tick) as u64;
+        w.write_varint((tick_delta << 2) | input.direction as u64);
+        prev_tick = input.tick;
+    }
+
+    w.bytes
+}
+
+pub fn decode_replay(bytes: &[u8]) -> Result<Replay, String> {
+    let mut r = BinaryReader::new(bytes);
+
+    let magic = [r.read_u8()?, r.read_u8()?, r.read_u8()?, r.read_u8()?];
+    if &magic != REPLAY_MAGIC {
+        return Err("not a replay file".to_string());
+    }
+
+    let version = r.read_u8()?;
+    if version != REPLAY_VERSION {
+        return Err(format!("unsupported replay version: {version}"));
+    }
+
+    let seed = r.read_u32()?;
+    let rules = GameRules {
+        grid_size: r.read_u16()? as i32,
+        apples_count: r.read_u16()? as usize,
+        stones_count: r.read_u16()? as usize,
+    };
+    rules.validate()?;
+
+    let ticks_count = u32::try_from(r.read_varint()?).map_err(|_| "too many ticks")?;
+    let inputs_count = r.read_varint()?;
+
+    // inputs can't be after the end, which also keeps crafted files from
+    // overflowing tick
+    let mut inputs = Vec::new();
+    let mut tick: u32 = 0;
+    for _ in 0..inputs_count {
+        let v = r.read_varint()?;
+        tick = u32::try_from(v >> 2)
+            .ok()
+            .and_then(|delta| tick.checked_add(delta))
+            .filter(|tick| *tick <= ticks_count)
+            .ok_or("input is after the end of replay")?;
+
+        inputs.push(ReplayInput {
+            tick,
+            direction: FromPrimitive::from_u64(v & 0b11).unwrap(),
+        });
+    }
+
+    Ok(Replay {
+        seed,
+        rules,
+        inputs,
+        ticks_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Direction;
+
+    #[test]
+    fn round_trip() {
+        let replay = Replay {
+            seed: 12345,
+            rules: GameRules::default(),
+            inputs: vec![
+                ReplayInput {
+                    tick: 3,
+                    direction: Direction::Up,
+                },
+                ReplayInput {
+                    tick: 3,
+                    direction: Direction::Left,
+                },
+                ReplayInput {
+                    tick: 1000,
+                    direction: Direction::Down,
+                },
+            ],
+            ticks_count: 2000,
+        };
+
+        assert_eq!(decode_replay(&encode_replay(&replay)).unwrap(), replay);
+    }
+
+    #[test]
+    fn compact_inputs() {
+        let replay = Replay {
+            inputs: (0..100)
+                .map(|i| ReplayInput {
+                    tick: i * 10,
+                    direction: Direction::Right,
+                })
+                .collect(),
+            ..Replay::default()
+        };
+
+        let header_size = 4 + 1 + 4 + 2 * 3 + 1 + 1;
+        assert_eq!(encode_replay(&replay).len(), header_size + 100);
+    }
+
+    #[test]
+    fn inputs_after_the_end() {
+        // header of default replay with given ticks count and raw inputs
+        let encode = |ticks_count: u64, inputs: &[u64]| {
+            let mut bytes = encode_replay(&Replay::default());
+            bytes.truncate(bytes.len() - 2);
+
+            let mut w = BinaryWriter::new();
+            w.write_varint(ticks_count);
+            w.write_varint(inputs.len() as u64);
+            inputs.iter().for_each(|input| w.write_varint(*input));
+
+            bytes.extend(w.bytes);
+            bytes
+        };
+
+        assert!(decode_replay(&encode(10, &[10 << 2])).is_ok());
+        assert!(decode_replay(&encode(10, &[11 << 2])).is_err());
+
+        // deltas which overflow tick
+        let max_delta = (u32::MAX as u64) << 2;
+        assert!(decode_replay(&encode(u32::MAX as u64, &[max_delta, max_delta])).is_err());
+        assert!(decode_replay(&encode(10, &[u64::MAX])).is_err());
+    }
+
+    #[test]
+    fn wrong_magic() {
+        assert!(decode_replay(b"ABCD").is_err());
+    }
+
+    #[test]
+    fn rules_that_cannot_be_planted() {
+        let decode_rules = |rules: GameRules| {
+            decode_replay(&encode_replay(&Replay {
+                rules,
+                ..Replay::default()
+            }))
+        };
+
+        assert!(decode_rules(GameRules {
+            grid_size: 0,
+            ..GameRules::default()
+        })
+        .is_err());
+        assert!(decode_rules(GameRules {
+            grid_size: 1000,
+            ..GameRules::default()
+        })
+        .is_err());
+
+        // 6 sides of 2x2 cells
+        assert!(decode_rules(GameRules {
+            grid_size: 2,
+            apples_count: 20,
+            stones_count: 0,
+        })
+        .is_err());
+        assert!(decode_rules(GameRules {
+            grid_size: 2,
+            apples_count: 1,
+            stones_count: 20,
+        })
+        .is_err());
+
+        assert!(decode_rules(GameRules {
+            grid_size: 2,
+            apples_count: 5,
+            stones_count: 5,
+        })
+        .is_ok());
+    }
+}
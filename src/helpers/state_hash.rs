@@ -0,0 +1,60 @@
+use crate::models::{CubePosition, GameState, Snake};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// FNV-1a hash. not cryptographic, but cheap and stable across platforms, which
+// is all we need to detect that simulations of network clients diverged
+struct Hasher(u64);
+
+impl Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u32(&mut self, v: u32) {
+        self.write(&v.to_le_bytes());
+    }
+
+    fn write_position(&mut self, pos: &CubePosition) {
+        self.write(&[pos.side as u8]);
+        self.write_u32(pos.row as u32);
+        self.write_u32(pos.col as u32);
+    }
+}
+
+/// Computes hash of simulation state (everything that affects game rules, but
+/// nothing that only affects rendering), which should be equal for all clients
+/// on the same tick
+pub fn get_state_hash(state: &GameState) -> u64 {
+    let mut h = Hasher(FNV_OFFSET_BASIS);
+
+    h.write_u32(state.tick);
+
+    // local snake goes first on each client, so order by player id instead
+    let mut snakes: Vec<&Snake> = std::iter::once(&state.snake)
+        .chain(state.rivals.iter())
+        .collect();
+    snakes.sort_by_key(|snake| snake.player_id);
+
+    for snake in snakes {
+        h.write_u32(snake.player_id);
+        h.write(&[snake.direction as u8, snake.is_crashed as u8]);
+        h.write_u32(snake.parts.len() as u32);
+        snake.parts.iter().for_each(|part| h.write_position(part));
+    }
+
+    // hash sets iterate in random order, so sort them first
+    for positions in [&state.apples, &state.stones] {
+        let mut positions: Vec<&CubePosition> = positions.iter().collect();
+        positions.sort();
+
+        h.write_u32(positions.len() as u32);
+        positions.iter().for_each(|pos| h.write_position(pos));
+    }
+
+    h.0
+}
@@ -0,0 +1,54 @@
+// browser local storage keeps strings only, so binary values are saved as hex
+
+pub fn get_storage_item(key: &str) -> Option<String> {
+    let storage = web_sys::window().unwrap().local_storage().ok()??;
+    storage.get_item(key).ok()?
+}
+
+pub fn set_storage_item(key: &str, value: &str) {
+    // storage can be disabled by user, losing saved values is not critical
+    if let Ok(Some(storage)) = web_sys::window().unwrap().local_storage() {
+        let _ = storage.set_item(key, value);
+    }
+}
+
+pub fn get_storage_bytes(key: &str) -> Option<Vec<u8>> {
+    decode_hex(&get_storage_item(key)?)
+}
+
+pub fn set_storage_bytes(key: &str, bytes: &[u8]) {
+    set_storage_item(key, &encode_hex(bytes));
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match std::str::from_utf8(pair) {
+            Ok(pair) if pair.len() == 2 => u8::from_str_radix(pair, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trip() {
+        let bytes = vec![0, 1, 15, 16, 127, 255];
+
+        assert_eq!(encode_hex(&bytes), "00010f107fff");
+        assert_eq!(decode_hex(&encode_hex(&bytes)), Some(bytes));
+    }
+
+    #[test]
+    fn invalid_hex() {
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+    }
+}
@@ -1,7 +1,9 @@
 use web_sys::WebGlRenderingContext;
 
+use super::gl_context::GlContext;
+
 pub fn init_shader(
-    ctx: &web_sys::WebGlRenderingContext,
+    ctx: &GlContext,
     shader_type: u32,
     shader_src: &str,
 ) -> Result<web_sys::WebGlShader, String> {
@@ -23,7 +25,7 @@ pub fn init_shader(
 }
 
 pub fn init_program(
-    ctx: &web_sys::WebGlRenderingContext,
+    ctx: &GlContext,
     shaders: [web_sys::WebGlShader; 2],
 ) -> Result<web_sys::WebGlProgram, String> {
     let program = ctx
@@ -47,7 +49,7 @@ pub fn init_program(
 }
 
 pub fn get_attribute_location(
-    ctx: &web_sys::WebGlRenderingContext,
+    ctx: &GlContext,
     program: &web_sys::WebGlProgram,
     name: &str,
 ) -> Result<u32, String> {
@@ -59,10 +61,10 @@ pub fn get_attribute_location(
 }
 
 pub fn get_uniform_location(
-    ctx: &web_sys::WebGlRenderingContext,
+    ctx: &GlContext,
     program: &web_sys::WebGlProgram,
     name: &str,
 ) -> Result<web_sys::WebGlUniformLocation, String> {
     ctx.get_uniform_location(program, name)
-        .ok_or_else(|| panic!("failed to get uniform location: {name}"))
+        .ok_or_else(|| format!("failed to get uniform location: {name}"))
 }
@@ -1,8 +1,10 @@
-mod actions;
+pub mod actions;
 mod drawers;
+pub mod env;
 mod game;
 mod helpers;
-mod models;
+pub mod models;
+pub mod network;
 
 use game::GameRc;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
@@ -0,0 +1,28 @@
+/// RGB color, usable both in canvas 2D styles and in shaders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    pub fn to_css(self) -> String {
+        format!("rgb({}, {}, {})", self.0, self.1, self.2)
+    }
+
+    pub fn to_gl(self, alpha: f32) -> [f32; 4] {
+        [
+            self.0 as f32 / 255.0,
+            self.1 as f32 / 255.0,
+            self.2 as f32 / 255.0,
+            alpha,
+        ]
+    }
+
+    /// Color of `other` painted over this one with given opacity
+    pub fn blend(self, other: Color, alpha: f32) -> Color {
+        let mix = |a: u8, b: u8| (a as f32 * (1.0 - alpha) + b as f32 * alpha).round() as u8;
+        Color(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+}
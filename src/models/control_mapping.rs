@@ -0,0 +1,19 @@
+/// How arrow keys are turned into snake directions
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ControlMapping {
+    // arrows are directions of rows and columns of the side with snake head
+    #[default]
+    FaceLocal,
+
+    // arrows point where snake goes on screen, however cube is rotated
+    ScreenRelative,
+}
+
+impl ControlMapping {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlMapping::FaceLocal => "face",
+            ControlMapping::ScreenRelative => "screen",
+        }
+    }
+}
@@ -1,20 +1,54 @@
 use std::collections::HashMap;
 
-use super::{CameraMode, CubeSide, CubeSideType, Grid, ModelRotation, Point2D};
-
-const GRID_SIZE: i32 = 16;
+use super::{
+    CameraMode, CubeShape, CubeSide, CubeSideType, Grid, Light, LightUniformLocations, Point2D,
+    Quaternion, DEFAULT_GRID_SIZE,
+};
 
 pub struct Cube {
     pub program: Option<web_sys::WebGlProgram>,
     pub matrix_uniform_location: Option<web_sys::WebGlUniformLocation>,
+    pub world_matrix_uniform_location: Option<web_sys::WebGlUniformLocation>,
+    pub normal_matrix_uniform_location: Option<web_sys::WebGlUniformLocation>,
+    pub light_uniform_locations: LightUniformLocations,
     pub textures: Vec<web_sys::WebGlTexture>,
-    pub current_rotation: ModelRotation,
-    pub target_rotation: ModelRotation,
+
+    // side texture filtering, set from quality preset. anisotropy is clamped
+    // to max supported, and is none without the extension
+    pub mipmaps: bool,
+    pub anisotropy: Option<f32>,
+    pub max_anisotropy: Option<f32>,
+
+    // webgl 2 only
+    pub texture_array: Option<web_sys::WebGlTexture>,
+    pub vertex_array: Option<web_sys::WebGlVertexArrayObject>,
+
+    pub vertex_coords_buffer: Option<web_sys::WebGlBuffer>,
+    pub texture_coords_buffer: Option<web_sys::WebGlBuffer>,
+    pub index_buffer: Option<web_sys::WebGlBuffer>,
+    pub indices_count: i32,
+    pub vertex_coord_attr_location: u32,
+    pub vertex_side_attr_location: u32,
+    pub vertex_normal_attr_location: u32,
+    pub texture_coord_attr_location: u32,
+    pub light: Light,
+    pub current_rotation: Quaternion,
+    pub target_rotation: Quaternion,
     pub camera_mode: CameraMode,
     pub mouse_is_dragging: bool,
     pub mouse_pos: Option<Point2D>,
     pub needs_redraw: bool,
     pub grid: Grid,
+    pub shape: CubeShape,
+
+    // grid which uploaded cube mesh was built for, since edges depend on cell
+    // size
+    pub mesh_grid: Option<Grid>,
+
+    // objects are drawn as 3D meshes on top of the cube, and side textures only
+    // have grid on them. otherwise objects are painted on side textures
+    pub objects_as_meshes: bool,
+
     pub sides: HashMap<CubeSideType, CubeSide>,
 
     // side types can also be retreived from keys of sides map, but save them
@@ -45,17 +79,37 @@ impl Default for Cube {
         Cube {
             program: None,
             matrix_uniform_location: None,
+            world_matrix_uniform_location: None,
+            normal_matrix_uniform_location: None,
+            light_uniform_locations: LightUniformLocations::default(),
             textures: Vec::new(),
-            current_rotation: ModelRotation::default(),
-            target_rotation: ModelRotation::default(),
+            mipmaps: false,
+            anisotropy: None,
+            max_anisotropy: None,
+            texture_array: None,
+            vertex_array: None,
+            vertex_coords_buffer: None,
+            texture_coords_buffer: None,
+            index_buffer: None,
+            indices_count: 0,
+            vertex_coord_attr_location: 0,
+            vertex_side_attr_location: 0,
+            vertex_normal_attr_location: 0,
+            texture_coord_attr_location: 0,
+            light: Light::default(),
+            current_rotation: Quaternion::default(),
+            target_rotation: Quaternion::default(),
             camera_mode: CameraMode::Overview,
             mouse_is_dragging: false,
             mouse_pos: None,
             needs_redraw: true,
             grid: Grid {
-                rows_count: GRID_SIZE,
-                cols_count: GRID_SIZE,
+                rows_count: DEFAULT_GRID_SIZE,
+                cols_count: DEFAULT_GRID_SIZE,
             },
+            shape: CubeShape::default(),
+            mesh_grid: None,
+            objects_as_meshes: false,
             sides,
             side_types,
         }
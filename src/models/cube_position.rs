@@ -1,6 +1,6 @@
 use super::CubeSideType;
 
-#[derive(Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub struct CubePosition {
     pub side: CubeSideType,
     pub row: i32,
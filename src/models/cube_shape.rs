@@ -0,0 +1,38 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeShape {
+    Sharp,
+
+    // quarter of a circle
+    Rounded,
+
+    // flat cut at 45 degrees
+    Beveled,
+}
+
+/// Description of cube mesh. Sides are split into grid of quads, which is
+/// denser on shaped edges, so they are lit smoothly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubeShape {
+    // quads along flat part of a side
+    pub subdivisions: u32,
+
+    pub edge_shape: EdgeShape,
+
+    // cube units taken by edge from each side. it's cut down to less than half
+    // of a cell on dense grids, so cell centers stay on flat part
+    pub edge_size: f64,
+
+    // quads along edge part of a side
+    pub edge_segments: u32,
+}
+
+impl Default for CubeShape {
+    fn default() -> Self {
+        CubeShape {
+            subdivisions: 4,
+            edge_shape: EdgeShape::Rounded,
+            edge_size: 0.03,
+            edge_segments: 3,
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use super::CubeSideType;
+use std::collections::HashSet;
+
+use super::{CubeSideType, Rect};
 
 pub struct CubeSide {
     pub canvas: Option<web_sys::HtmlCanvasElement>,
@@ -8,6 +10,13 @@ pub struct CubeSide {
 
     pub needs_redraw: bool,
     pub needs_update_on_cube: bool,
+
+    // cells (row, col) changed since last draw. only these cells are repainted
+    // if side doesn't need full redraw
+    pub dirty_cells: HashSet<(i32, i32)>,
+
+    // regions of side canvas repainted since last update on cube
+    pub dirty_rects: Vec<Rect>,
 }
 
 impl Default for CubeSide {
@@ -18,6 +27,8 @@ impl Default for CubeSide {
             side_type: CubeSideType::Front,
             needs_redraw: true,
             needs_update_on_cube: true,
+            dirty_cells: HashSet::new(),
+            dirty_rects: Vec::new(),
         }
     }
 }
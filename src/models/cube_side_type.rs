@@ -1,6 +1,6 @@
 use num_derive::FromPrimitive;
 
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, FromPrimitive)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, FromPrimitive)]
 pub enum CubeSideType {
     Front = 0,
     Back = 1,
@@ -0,0 +1,13 @@
+use crate::helpers::graphics_math::{Matrix4, Vec3};
+
+/// Transformations of the cube for one frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubeView {
+    // from cube space to clip space
+    pub matrix: Matrix4,
+
+    // cube rotation only, without camera and projection, for lighting
+    pub world_matrix: Matrix4,
+
+    pub camera_pos: Vec3,
+}
@@ -1,4 +1,6 @@
-#[derive(Clone, Copy, PartialEq)]
+use num_derive::FromPrimitive;
+
+#[derive(Debug, Clone, Copy, PartialEq, FromPrimitive)]
 pub enum Direction {
     Up,
     Down,
@@ -0,0 +1,11 @@
+use super::CubePosition;
+
+/// Something that happened in simulation, for effects which are not part of
+/// game rules (eg. particles). Events are collected in game state until
+/// someone takes them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameEvent {
+    AppleEaten(CubePosition),
+    StoneHit(CubePosition),
+    Win,
+}
@@ -0,0 +1,45 @@
+pub const DEFAULT_GRID_SIZE: i32 = 16;
+pub const MAX_GRID_SIZE: i32 = 64;
+
+// players start on different cube sides, with single part each
+const MAX_SNAKE_PARTS_COUNT: usize = 6;
+
+/// Rules that define the board. Together with seed they fully determine initial
+/// layout of the game, so clients of network game only need to share them
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRules {
+    pub grid_size: i32,
+    pub apples_count: usize,
+    pub stones_count: usize,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules {
+            grid_size: DEFAULT_GRID_SIZE,
+            apples_count: 10,
+            stones_count: 10,
+        }
+    }
+}
+
+impl GameRules {
+    /// Checks that board can be planted with these rules. Objects are planted
+    /// on free cells only, so they must fit with room to spare
+    pub fn validate(&self) -> Result<(), String> {
+        if self.grid_size < 1 || self.grid_size > MAX_GRID_SIZE {
+            return Err(format!("invalid grid size: {}", self.grid_size));
+        }
+
+        let cells_count = 6 * (self.grid_size * self.grid_size) as usize;
+        let objects_count = self.apples_count + self.stones_count + MAX_SNAKE_PARTS_COUNT;
+        if objects_count >= cells_count {
+            return Err(format!(
+                "{} apples and {} stones don't fit on grid of size {}",
+                self.apples_count, self.stones_count, self.grid_size
+            ));
+        }
+
+        Ok(())
+    }
+}
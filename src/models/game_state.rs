@@ -1,13 +1,59 @@
 use std::collections::HashSet;
 
-use super::{CubePosition, GameStatus, Scene, Snake};
+use crate::helpers::random::Random;
+
+use super::{
+    ControlMapping, CubePosition, GameEvent, GameRules, GameStatus, Ghost, NetworkSession,
+    Playback, Replay, Scene, Snake,
+};
 
 pub struct GameState {
     pub scene: Scene,
     pub snake: Snake,
+
+    // snakes of other players in network game
+    pub rivals: Vec<Snake>,
+
     pub apples: HashSet<CubePosition>,
     pub stones: HashSet<CubePosition>,
     pub status: GameStatus,
+
+    pub rules: GameRules,
+    pub seed: u32,
+    pub random: Random,
+
+    // restart with the same board instead of new random one
+    pub is_seed_fixed: bool,
+
+    // number of snake moves since game start
+    pub tick: u32,
+
+    // fraction of time passed into current tick (0..1). snakes are drawn
+    // between previous and current cells by it, while rules stay cell-based
+    pub move_progress: f64,
+
+    pub network: Option<NetworkSession>,
+
+    // how arrow keys are mapped to snake directions
+    pub control_mapping: ControlMapping,
+
+    // spectator only renders snapshots received from playing instance
+    pub is_spectator: bool,
+
+    // record of current game
+    pub replay: Replay,
+
+    // set when playing recorded game instead of live one
+    pub playback: Option<Playback>,
+
+    // best run for current board to race against
+    pub ghost: Option<Ghost>,
+
+    // events since they were taken last time
+    pub events: Vec<GameEvent>,
+
+    // game was paused by webgl context loss, and is resumed on restore
+    pub is_paused_on_context_loss: bool,
 }
 
 impl Default for GameState {
@@ -15,9 +61,24 @@ impl Default for GameState {
         GameState {
             scene: Scene::default(),
             snake: Snake::default(),
+            rivals: Vec::new(),
             apples: HashSet::new(),
             stones: HashSet::new(),
             status: GameStatus::Welcome,
+            rules: GameRules::default(),
+            seed: 0,
+            random: Random::default(),
+            is_seed_fixed: false,
+            tick: 0,
+            move_progress: 1.0,
+            network: None,
+            control_mapping: ControlMapping::default(),
+            is_spectator: false,
+            replay: Replay::default(),
+            playback: None,
+            ghost: None,
+            events: Vec::new(),
+            is_paused_on_context_loss: false,
         }
     }
 }
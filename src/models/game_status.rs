@@ -1,4 +1,6 @@
-#[derive(PartialEq)]
+use num_derive::FromPrimitive;
+
+#[derive(Debug, Clone, Copy, PartialEq, FromPrimitive)]
 pub enum GameStatus {
     Welcome,
     InGame,
@@ -0,0 +1,16 @@
+use super::{GameState, Replay};
+
+/// Best run for current seed and rules, played back alongside the live game
+pub struct Ghost {
+    pub replay: Replay,
+
+    // ghost runs its own simulation, so it never collides with live snake
+    pub state: Box<GameState>,
+
+    // index of the next input to apply
+    pub next_input: usize,
+
+    // result of the best run by its end
+    pub best_apples_eaten: usize,
+    pub best_ticks_count: u32,
+}
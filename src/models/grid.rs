@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Grid {
     pub rows_count: i32,
     pub cols_count: i32,
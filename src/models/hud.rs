@@ -0,0 +1,39 @@
+use super::Theme;
+
+/// Panel in the middle of the screen, shown while game is not running
+#[derive(Debug, Clone, PartialEq)]
+pub struct HudPanel {
+    pub title: &'static str,
+    pub controls_hint: &'static str,
+    pub start_hint: &'static str,
+}
+
+/// Everything painted on HUD, including theme it is painted with. Canvas is
+/// repainted only when this changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct HudContent {
+    pub score: String,
+    pub timer: String,
+    pub message: String,
+    pub panel: Option<HudPanel>,
+    pub theme: Theme,
+}
+
+/// Screen-space layer over the cube for text, so it's not painted into side
+/// textures
+#[derive(Default)]
+pub struct Hud {
+    pub canvas: Option<web_sys::HtmlCanvasElement>,
+    pub ctx: Option<web_sys::CanvasRenderingContext2d>,
+
+    // device pixels per css pixel. HUD is laid out in css pixels, and painted
+    // in device ones to stay crisp
+    pub pixel_ratio: f64,
+
+    // content painted last time, not set when canvas is cleared
+    pub content: Option<HudContent>,
+
+    // milliseconds of current game spent in play, pauses are not counted
+    pub play_time: f64,
+    pub last_time: Option<f64>,
+}
@@ -0,0 +1,35 @@
+/// Directional light for the cube. it falls from the same direction onto every
+/// point, like sunlight. cube rotates under it, so faces turned to the camera
+/// are lit and orientation of the cube is readable
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    // direction light travels in, in world space
+    pub direction: [f32; 3],
+
+    // brightness of faces turned away from light (0..1)
+    pub ambient: f32,
+
+    // strength of specular highlights, zero turns them off
+    pub specular: f32,
+    pub shininess: f32,
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Light {
+            direction: [-0.4, -0.6, -1.0],
+            ambient: 0.45,
+            specular: 0.25,
+            shininess: 24.0,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LightUniformLocations {
+    pub direction: Option<web_sys::WebGlUniformLocation>,
+    pub ambient: Option<web_sys::WebGlUniformLocation>,
+    pub specular: Option<web_sys::WebGlUniformLocation>,
+    pub shininess: Option<web_sys::WebGlUniformLocation>,
+    pub camera_pos: Option<web_sys::WebGlUniformLocation>,
+}
@@ -1,37 +1,79 @@
 mod camera_mode;
+mod color;
+mod control_mapping;
 mod cube;
 mod cube_position;
+mod cube_shape;
 mod cube_side;
 mod cube_side_type;
+mod cube_view;
 mod degrees;
 mod direction;
+mod game_event;
+mod game_rules;
 mod game_state;
 mod game_status;
+mod ghost;
 mod grid;
-mod model_rotation;
+mod hud;
+mod light;
+mod network_session;
+mod object_meshes;
+mod palette;
+mod particles;
+mod playback;
 mod point_2d;
 mod point_3d;
+mod post_processing;
+mod profiler;
+mod quality;
+mod quaternion;
 mod radians;
 mod range;
+mod rect;
+mod render_stats;
+mod replay;
 mod scene;
 mod size;
 mod snake;
+mod theme;
 
 pub use camera_mode::*;
+pub use color::*;
+pub use control_mapping::*;
 pub use cube::*;
 pub use cube_position::*;
+pub use cube_shape::*;
 pub use cube_side::*;
 pub use cube_side_type::*;
+pub use cube_view::*;
 pub use degrees::*;
 pub use direction::*;
+pub use game_event::*;
+pub use game_rules::*;
 pub use game_state::*;
 pub use game_status::*;
+pub use ghost::*;
 pub use grid::*;
-pub use model_rotation::*;
+pub use hud::*;
+pub use light::*;
+pub use network_session::*;
+pub use object_meshes::*;
+pub use palette::*;
+pub use particles::*;
+pub use playback::*;
 pub use point_2d::*;
 pub use point_3d::*;
+pub use post_processing::*;
+pub use profiler::*;
+pub use quality::*;
+pub use quaternion::*;
 pub use radians::*;
 pub use range::*;
+pub use rect::*;
+pub use render_stats::*;
+pub use replay::*;
 pub use scene::*;
 pub use size::*;
 pub use snake::*;
+pub use theme::*;
@@ -1,7 +0,0 @@
-use super::Degrees;
-
-#[derive(Default, Clone, Copy, PartialEq)]
-pub struct ModelRotation {
-    pub x: Degrees,
-    pub y: Degrees,
-}
@@ -0,0 +1,21 @@
+use crate::network::protocol::ClientMessage;
+
+pub type PlayerId = u32;
+
+#[derive(Default)]
+pub struct NetworkSession {
+    // assigned by server after join
+    pub player_id: Option<PlayerId>,
+    pub is_started: bool,
+
+    // game started with other players, so it's won once all of them are out.
+    // game of a single player goes on until apples are eaten
+    pub had_rivals: bool,
+
+    // tick at which state hashes of clients diverged. simulation stops after
+    // that since there is no way to recover in lockstep model
+    pub desync_tick: Option<u32>,
+
+    // messages queued to be sent to server
+    pub outbox: Vec<ClientMessage>,
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectMeshType {
+    SnakePart,
+    SnakeHead,
+    SnakeEyes,
+    Apple,
+    Stone,
+}
+
+/// Range of mesh vertices in shared vertex buffer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshRange {
+    pub first: i32,
+    pub count: i32,
+}
+
+#[derive(Default)]
+pub struct ObjectAttributeLocations {
+    // per vertex
+    pub position: u32,
+    pub normal: u32,
+
+    // per instance
+    pub offset: u32,
+    pub axis_x: u32,
+    pub axis_y: u32,
+    pub axis_z: u32,
+    pub color: u32,
+}
+
+/// WebGL resources for drawing snakes, apples and stones as instanced meshes
+#[derive(Default)]
+pub struct ObjectMeshes {
+    // not set if browser doesn't support instancing
+    pub program: Option<web_sys::WebGlProgram>,
+
+    pub matrix_uniform_location: Option<web_sys::WebGlUniformLocation>,
+    pub attribute_locations: ObjectAttributeLocations,
+
+    // vertices of all meshes one after another
+    pub vertex_buffer: Option<web_sys::WebGlBuffer>,
+    pub mesh_ranges: HashMap<ObjectMeshType, MeshRange>,
+
+    // rebuilt each time cube is redrawn
+    pub instance_buffer: Option<web_sys::WebGlBuffer>,
+    pub tube_buffer: Option<web_sys::WebGlBuffer>,
+}
@@ -0,0 +1,133 @@
+use super::{Color, Theme};
+
+/// Colors which replace theme ones, so objects can be told apart with color
+/// vision deficiency. Sprites have their own colors, so they are not used
+/// with palette
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaletteColors {
+    pub background: Color,
+    pub grid_line: Color,
+
+    pub snake_head: Color,
+    pub snake_body: Color,
+    pub rival: Color,
+    pub apple: Color,
+    pub stone: Color,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Palette {
+    pub name: &'static str,
+
+    // theme colors are kept if not set
+    pub colors: Option<PaletteColors>,
+}
+
+impl Palette {
+    /// Theme with colors of this palette
+    pub fn apply(&self, theme: &Theme) -> Theme {
+        let Some(colors) = self.colors else {
+            return theme.clone();
+        };
+
+        Theme {
+            background: colors.background,
+            grid_line: colors.grid_line,
+            snake_head: colors.snake_head,
+            snake_body: colors.snake_body,
+            rival: colors.rival,
+            apple: colors.apple,
+            stone: colors.stone,
+            sprites: None,
+            ..theme.clone()
+        }
+    }
+}
+
+// colors are picked so objects differ along the axis each deficiency keeps:
+// blue against orange for red-green ones, red against teal for blue-yellow one
+pub const PALETTES: [Palette; 5] = [
+    Palette {
+        name: "theme",
+        colors: None,
+    },
+    Palette {
+        name: "deuteranopia",
+        colors: Some(PaletteColors {
+            background: Color(255, 255, 255),
+            grid_line: Color(120, 120, 120),
+            snake_head: Color(0, 73, 146),
+            snake_body: Color(0, 114, 178),
+            rival: Color(86, 180, 233),
+            apple: Color(230, 159, 0),
+            stone: Color(0, 0, 0),
+        }),
+    },
+    Palette {
+        name: "protanopia",
+        colors: Some(PaletteColors {
+            background: Color(255, 255, 255),
+            grid_line: Color(120, 120, 120),
+            snake_head: Color(0, 68, 136),
+            snake_body: Color(0, 119, 187),
+            rival: Color(136, 204, 238),
+            apple: Color(221, 170, 51),
+            stone: Color(51, 34, 0),
+        }),
+    },
+    Palette {
+        name: "tritanopia",
+        colors: Some(PaletteColors {
+            background: Color(255, 255, 255),
+            grid_line: Color(120, 120, 120),
+            snake_head: Color(170, 20, 20),
+            snake_body: Color(220, 50, 32),
+            rival: Color(255, 140, 170),
+            apple: Color(0, 150, 140),
+            stone: Color(40, 40, 40),
+        }),
+    },
+    Palette {
+        name: "high contrast",
+        colors: Some(PaletteColors {
+            background: Color(0, 0, 0),
+            grid_line: Color(90, 90, 90),
+            snake_head: Color(255, 255, 255),
+            snake_body: Color(255, 230, 0),
+            rival: Color(0, 200, 255),
+            apple: Color(255, 0, 255),
+            stone: Color(150, 150, 150),
+        }),
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::models::THEMES;
+
+    use super::*;
+
+    #[test]
+    fn palette_recolors_theme() {
+        let pixel = &THEMES[2];
+        assert_eq!(&PALETTES[0].apply(pixel), pixel);
+
+        for palette in &PALETTES[1..] {
+            let theme = palette.apply(pixel);
+            assert_eq!(theme.sprites, None);
+            assert_eq!(theme.title_font, pixel.title_font);
+
+            // every object has its own color
+            let colors = [
+                theme.background,
+                theme.snake_body,
+                theme.rival,
+                theme.apple,
+                theme.stone,
+            ];
+            for (i, color) in colors.iter().enumerate() {
+                assert!(!colors[i + 1..].contains(color), "{}", palette.name);
+            }
+        }
+    }
+}
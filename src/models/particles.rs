@@ -0,0 +1,71 @@
+use crate::helpers::random::Random;
+
+use super::{Radians, Range};
+
+/// Settings of a burst of particles
+pub struct ParticleEmitter {
+    pub count: usize,
+
+    // milliseconds
+    pub lifetime: f64,
+
+    // cube units per second
+    pub speed: Range,
+
+    // max angle between particle velocity and emitter direction
+    pub spread: Radians,
+
+    // acceleration against emitter direction, cube units per second squared
+    pub gravity: f32,
+
+    pub color: [f32; 4],
+
+    // pixels, at the distance of the camera to cube center
+    pub size: f32,
+}
+
+/// Single particle. It moves by its initial state only, so its position is
+/// computed on GPU and particle data is uploaded only once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub origin: [f32; 3],
+    pub velocity: [f32; 3],
+    pub acceleration: [f32; 3],
+    pub color: [f32; 4],
+    pub size: f32,
+
+    // milliseconds, from performance.now()
+    pub start_time: f64,
+    pub lifetime: f64,
+}
+
+#[derive(Default)]
+pub struct ParticleAttributeLocations {
+    pub origin: u32,
+    pub velocity: u32,
+    pub acceleration: u32,
+    pub color: u32,
+    pub size: u32,
+    pub time: u32,
+}
+
+/// WebGL resources and live particles of particle system
+#[derive(Default)]
+pub struct Particles {
+    pub program: Option<web_sys::WebGlProgram>,
+    pub matrix_uniform_location: Option<web_sys::WebGlUniformLocation>,
+    pub time_uniform_location: Option<web_sys::WebGlUniformLocation>,
+    pub attribute_locations: ParticleAttributeLocations,
+
+    // rebuilt only when particles are emitted or expire
+    pub buffer: Option<web_sys::WebGlBuffer>,
+
+    // time of last buffer upload. times in buffer are relative to it, since
+    // f32 is not precise enough for absolute performance.now() values
+    pub buffer_time: f64,
+
+    pub particles: Vec<Particle>,
+
+    // separate from game random, so effects don't change simulation
+    pub random: Random,
+}
@@ -0,0 +1,24 @@
+use super::Replay;
+
+pub const PLAYBACK_SPEEDS: [f64; 4] = [0.5, 1.0, 2.0, 4.0];
+
+pub struct Playback {
+    pub replay: Replay,
+
+    // index of the next input to apply
+    pub next_input: usize,
+
+    pub speed: f64,
+    pub is_paused: bool,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Playback {
+            replay,
+            next_input: 0,
+            speed: 1.0,
+            is_paused: false,
+        }
+    }
+}
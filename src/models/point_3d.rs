@@ -1,5 +1,18 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Point3D {
     pub x: f64,
     pub y: f64,
     pub z: f64,
 }
+
+pub const X_AXIS: Point3D = Point3D {
+    x: 1.0,
+    y: 0.0,
+    z: 0.0,
+};
+
+pub const Y_AXIS: Point3D = Point3D {
+    x: 0.0,
+    y: 1.0,
+    z: 0.0,
+};
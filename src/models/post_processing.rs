@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+/// Full-screen effect applied to the rendered cube
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PostEffect {
+    // glow around apples
+    Bloom,
+
+    // grayscale fade while game is failed
+    FailFade,
+
+    // blur around pause overlay
+    PauseBlur,
+
+    // darkened corners
+    Vignette,
+
+    // curved screen with scanlines
+    Crt,
+}
+
+/// All effects in order of passes
+pub const POST_EFFECTS: [PostEffect; 5] = [
+    PostEffect::Bloom,
+    PostEffect::FailFade,
+    PostEffect::PauseBlur,
+    PostEffect::Vignette,
+    PostEffect::Crt,
+];
+
+/// Program of a single pass. It reads previous image as a texture
+pub struct PostPass {
+    pub program: web_sys::WebGlProgram,
+    pub position_attribute_location: u32,
+    pub texture_uniform_location: Option<web_sys::WebGlUniformLocation>,
+    pub resolution_uniform_location: Option<web_sys::WebGlUniformLocation>,
+    pub amount_uniform_location: Option<web_sys::WebGlUniformLocation>,
+    pub glow_color_uniform_location: Option<web_sys::WebGlUniformLocation>,
+}
+
+/// Off-screen image which passes draw into
+pub struct RenderTarget {
+    pub framebuffer: web_sys::WebGlFramebuffer,
+    pub texture: web_sys::WebGlTexture,
+
+    // only the target of cube itself needs depth
+    pub depth: Option<web_sys::WebGlRenderbuffer>,
+
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Default)]
+pub struct PostProcessing {
+    // effects, which programs failed to compile, are missing here
+    pub passes: HashMap<PostEffect, PostPass>,
+
+    // single triangle covering the whole screen
+    pub quad_buffer: Option<web_sys::WebGlBuffer>,
+
+    // cube is drawn into the first one, passes then switch between the others
+    pub targets: Vec<RenderTarget>,
+
+    // current strength of effects, from 0 to 1. they fade in and out, instead
+    // of switching at once
+    pub amounts: HashMap<PostEffect, f32>,
+    pub last_update_time: Option<f64>,
+
+    // false if targets can't be used by GPU, then cube is drawn as is
+    pub is_supported: bool,
+}
@@ -0,0 +1,49 @@
+use std::collections::VecDeque;
+
+/// Frames kept by profiler, about two seconds at 60 fps
+pub const PROFILER_SAMPLES_COUNT: usize = 120;
+
+/// Counters of a single frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameSample {
+    // milliseconds since previous frame
+    pub frame_time: f64,
+
+    pub ticks: u32,
+    pub draw_calls: u32,
+    pub side_repaints: u32,
+    pub uploaded_bytes: usize,
+}
+
+/// Digest of recent frames, shown in performance HUD
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PerfSummary {
+    pub fps: f64,
+
+    // milliseconds
+    pub frame_time_p50: f64,
+    pub frame_time_p95: f64,
+    pub frame_time_p99: f64,
+
+    pub ticks_per_frame: f64,
+    pub draw_calls_per_frame: f64,
+    pub side_repaints_per_second: f64,
+    pub uploaded_bytes_per_second: f64,
+}
+
+#[derive(Default)]
+pub struct Profiler {
+    // recent frames, the oldest first
+    pub samples: VecDeque<FrameSample>,
+    pub last_frame_time: Option<f64>,
+
+    // samples recorded since start, including ones which are dropped already
+    pub samples_count: usize,
+
+    // updated a few times per second, so numbers can be read
+    pub summary: PerfSummary,
+    pub last_summary_time: f64,
+
+    // summary is also given to JS, once after each update
+    pub is_summary_exported: bool,
+}
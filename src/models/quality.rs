@@ -0,0 +1,98 @@
+/// Graphics settings which trade look for speed
+#[derive(Debug, PartialEq)]
+pub struct QualityPreset {
+    pub name: &'static str,
+
+    // size of side canvases and textures. power of two, since webgl 1 can
+    // build mipmaps only for such textures
+    pub side_texture_size: u32,
+
+    // part of device pixel ratio used for the cube canvas
+    pub render_scale: f64,
+
+    pub mipmaps: bool,
+
+    // 1 turns anisotropic filtering off. clamped to what GPU supports
+    pub anisotropy: f32,
+
+    // webgl context can't change it once created, so it's applied on page load
+    pub antialias: bool,
+
+    // full-screen effects of theme
+    pub post_effects: bool,
+}
+
+/// Built-in presets from the lowest to the highest
+pub const QUALITY_PRESETS: [QualityPreset; 3] = [
+    QualityPreset {
+        name: "low",
+        side_texture_size: 256,
+        render_scale: 0.5,
+        mipmaps: false,
+        anisotropy: 1.0,
+        antialias: false,
+        post_effects: false,
+    },
+    QualityPreset {
+        name: "medium",
+        side_texture_size: 512,
+        render_scale: 0.75,
+        mipmaps: true,
+        anisotropy: 4.0,
+        antialias: true,
+        post_effects: true,
+    },
+    QualityPreset {
+        name: "high",
+        side_texture_size: 1024,
+        render_scale: 1.0,
+        mipmaps: true,
+        anisotropy: 16.0,
+        antialias: true,
+        post_effects: true,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualitySetting {
+    // starts from the highest preset and steps down while frames are too slow
+    Auto,
+
+    // index in presets
+    Fixed(usize),
+}
+
+pub struct Quality {
+    pub setting: QualitySetting,
+
+    // index of preset in use
+    pub preset_index: usize,
+
+    // preset is changed, but canvases and textures are not updated yet
+    pub needs_apply: bool,
+
+    // count of profiler samples already used by auto setting, so every
+    // decision is made on frames which came after the previous one
+    pub measured_samples_count: usize,
+
+    // shortest typical frame in ms, which is how often display refreshes
+    pub refresh_interval: Option<f64>,
+}
+
+impl Quality {
+    pub fn preset(&self) -> &'static QualityPreset {
+        &QUALITY_PRESETS[self.preset_index]
+    }
+}
+
+impl Default for Quality {
+    fn default() -> Self {
+        Quality {
+            setting: QualitySetting::Auto,
+            preset_index: QUALITY_PRESETS.len() - 1,
+            needs_apply: false,
+            measured_samples_count: 0,
+            refresh_interval: None,
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use std::ops::Mul;
+
+use crate::helpers::graphics_math::Matrix4;
+
+use super::{Point3D, Radians};
+
+// below this dot product quaternions are close enough to lerp between them
+const SLERP_DOT_THRESHOLD: f64 = 0.9995;
+
+/// Rotation of the cube model. Unlike angles around fixed axes, it has no
+/// preferred axis, so any orientation is reached the same smooth way
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion {
+            w: 1.0,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        }
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    /// Rotation by `rhs` first, then by `self`, same as with matrices
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        Quaternion {
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        }
+    }
+}
+
+impl Quaternion {
+    /// Rotation around given unit axis, counterclockwise when the axis points
+    /// to the viewer
+    pub fn from_axis_angle(axis: &Point3D, angle: Radians) -> Self {
+        let (sin, cos) = (*angle / 2.0).sin_cos();
+
+        Quaternion {
+            w: cos,
+            x: axis.x * sin,
+            y: axis.y * sin,
+            z: axis.z * sin,
+        }
+    }
+
+    /// Rotation which turns given orthonormal right handed axes to X, Y and Z
+    pub fn from_axes(x_axis: &Point3D, y_axis: &Point3D, z_axis: &Point3D) -> Self {
+        // rows of rotation matrix
+        let m = [
+            [x_axis.x, x_axis.y, x_axis.z],
+            [y_axis.x, y_axis.y, y_axis.z],
+            [z_axis.x, z_axis.y, z_axis.z],
+        ];
+        let trace = m[0][0] + m[1][1] + m[2][2];
+
+        // pick the largest component to divide by, so it stays precise
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quaternion {
+                w: s / 4.0,
+                x: (m[2][1] - m[1][2]) / s,
+                y: (m[0][2] - m[2][0]) / s,
+                z: (m[1][0] - m[0][1]) / s,
+            }
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Quaternion {
+                w: (m[2][1] - m[1][2]) / s,
+                x: s / 4.0,
+                y: (m[0][1] + m[1][0]) / s,
+                z: (m[0][2] + m[2][0]) / s,
+            }
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Quaternion {
+                w: (m[0][2] - m[2][0]) / s,
+                x: (m[0][1] + m[1][0]) / s,
+                y: s / 4.0,
+                z: (m[1][2] + m[2][1]) / s,
+            }
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Quaternion {
+                w: (m[1][0] - m[0][1]) / s,
+                x: (m[0][2] + m[2][0]) / s,
+                y: (m[1][2] + m[2][1]) / s,
+                z: s / 4.0,
+            }
+        };
+
+        q.normalize()
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn normalize(&self) -> Self {
+        let len = self.dot(self).sqrt();
+
+        Quaternion {
+            w: self.w / len,
+            x: self.x / len,
+            y: self.y / len,
+            z: self.z / len,
+        }
+    }
+
+    /// Angle of the rotation which turns this orientation to the other one
+    pub fn angle_to(&self, other: &Quaternion) -> Radians {
+        Radians(2.0 * self.dot(other).abs().min(1.0).acos())
+    }
+
+    /// Interpolates between orientations along the shortest arc, where `t`
+    /// goes from 0 (this one) to 1 (the other one)
+    pub fn slerp(&self, other: &Quaternion, t: f64) -> Self {
+        let mut dot = self.dot(other);
+        let mut other = *other;
+
+        // q and -q are the same rotation, take the closer one
+        if dot < 0.0 {
+            dot = -dot;
+            other = Quaternion {
+                w: -other.w,
+                x: -other.x,
+                y: -other.y,
+                z: -other.z,
+            };
+        }
+
+        let (a, b) = if dot > SLERP_DOT_THRESHOLD {
+            (1.0 - t, t)
+        } else {
+            let angle = dot.acos();
+            let sin = angle.sin();
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+
+        Quaternion {
+            w: self.w * a + other.w * b,
+            x: self.x * a + other.x * b,
+            y: self.y * a + other.y * b,
+            z: self.z * a + other.z * b,
+        }
+        .normalize()
+    }
+
+    pub fn rotate_point(&self, p: &Point3D) -> Point3D {
+        let m = self.to_matrix();
+        let (x, y, z) = (p.x as f32, p.y as f32, p.z as f32);
+
+        Point3D {
+            x: (m[0] * x + m[4] * y + m[8] * z) as f64,
+            y: (m[1] * x + m[5] * y + m[9] * z) as f64,
+            z: (m[2] * x + m[6] * y + m[10] * z) as f64,
+        }
+    }
+
+    /// Rotation matrix in column-major order, as the rest of graphics math
+    pub fn to_matrix(&self) -> Matrix4 {
+        let Quaternion { w, x, y, z } = *self;
+
+        [
+            (1.0 - 2.0 * (y * y + z * z)) as f32,
+            (2.0 * (x * y + w * z)) as f32,
+            (2.0 * (x * z - w * y)) as f32,
+            0.0,
+            (2.0 * (x * y - w * z)) as f32,
+            (1.0 - 2.0 * (x * x + z * z)) as f32,
+            (2.0 * (y * z + w * x)) as f32,
+            0.0,
+            (2.0 * (x * z + w * y)) as f32,
+            (2.0 * (y * z - w * x)) as f32,
+            (1.0 - 2.0 * (x * x + y * y)) as f32,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        helpers::graphics_math::multiply,
+        models::{X_AXIS, Y_AXIS},
+    };
+
+    use super::*;
+
+    fn assert_matrices_eq(a: Matrix4, b: Matrix4) {
+        for (a, b) in a.iter().zip(&b) {
+            assert!((a - b).abs() < 1e-5, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[rustfmt::skip]
+    fn rotation_matrices(x_angle: Radians, y_angle: Radians) -> (Matrix4, Matrix4) {
+        let (sx, cx) = (x_angle.sin() as f32, x_angle.cos() as f32);
+        let (sy, cy) = (y_angle.sin() as f32, y_angle.cos() as f32);
+
+        let x_matrix = [
+            1.0, 0.0, 0.0, 0.0,
+            0.0, cx,  sx,  0.0,
+            0.0, -sx, cx,  0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ];
+        let y_matrix = [
+            cy,  0.0, -sy, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            sy,  0.0, cy,  0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ];
+
+        (x_matrix, y_matrix)
+    }
+
+    #[test]
+    fn matrix_matches_rotations() {
+        let (x_angle, y_angle) = (Radians(-0.5), Radians(0.7));
+        let q = Quaternion::from_axis_angle(&X_AXIS, x_angle)
+            * Quaternion::from_axis_angle(&Y_AXIS, y_angle);
+
+        let (x_matrix, y_matrix) = rotation_matrices(x_angle, y_angle);
+        let expected = multiply(x_matrix, y_matrix);
+        assert_matrices_eq(q.to_matrix(), expected);
+
+        // and back from axes which are rows of that matrix
+        let m = q.to_matrix();
+        let row = |i: usize| Point3D {
+            x: m[i] as f64,
+            y: m[i + 4] as f64,
+            z: m[i + 8] as f64,
+        };
+        let restored = Quaternion::from_axes(&row(0), &row(1), &row(2));
+        assert!(restored.angle_to(&q).0 < 1e-3);
+    }
+
+    #[test]
+    fn slerp_takes_shortest_arc() {
+        let from = Quaternion::default();
+        let to = Quaternion::from_axis_angle(&Y_AXIS, Radians(3.0));
+
+        assert_eq!(from.slerp(&to, 0.0), from);
+        assert!(from.slerp(&to, 1.0).angle_to(&to).0 < 1e-6);
+
+        let half = from.slerp(&to, 0.5);
+        assert!((half.angle_to(&from).0 - 1.5).abs() < 1e-6);
+
+        // negated quaternion is the same rotation, and must not go the long way
+        let negated = Quaternion {
+            w: -to.w,
+            x: -to.x,
+            y: -to.y,
+            z: -to.z,
+        };
+        assert!(from.slerp(&negated, 0.5).angle_to(&half).0 < 1e-6);
+    }
+}
@@ -0,0 +1,15 @@
+/// Counters of work done in current frame
+#[derive(Default)]
+pub struct RenderStats {
+    // bytes of side textures uploaded to GPU
+    pub uploaded_bytes: usize,
+
+    // webgl draw calls of cube, objects, particles and effects
+    pub draw_calls: u32,
+
+    // full or partial repaints of side canvases
+    pub side_repaints: u32,
+
+    // simulation ticks the game moved by
+    pub ticks: u32,
+}
@@ -0,0 +1,20 @@
+use super::{Direction, GameRules};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayInput {
+    // input is applied after this number of snake moves
+    pub tick: u32,
+    pub direction: Direction,
+}
+
+/// Record of single-player game. Simulation is deterministic, so seed, rules
+/// and inputs are enough to play the game again move by move
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    pub seed: u32,
+    pub rules: GameRules,
+    pub inputs: Vec<ReplayInput>,
+
+    // total number of snake moves
+    pub ticks_count: u32,
+}
@@ -1,8 +1,60 @@
-use super::Cube;
+use crate::helpers::gl_context::GlContext;
+
+use super::{
+    Cube, Hud, ObjectMeshes, Particles, PostProcessing, Profiler, Quality, RenderStats, Theme,
+    PALETTES, THEMES,
+};
 
 #[derive(Default)]
 pub struct Scene {
     pub canvas: Option<web_sys::HtmlCanvasElement>,
-    pub ctx: Option<web_sys::WebGlRenderingContext>,
+    pub ctx: Option<GlContext>,
     pub cube: Cube,
+    pub objects: ObjectMeshes,
+    pub particles: Particles,
+    pub post_processing: PostProcessing,
+    pub stats: RenderStats,
+    pub profiler: Profiler,
+    pub quality: Quality,
+
+    // nothing is drawn while webgl context is lost, since all its resources
+    // are gone until they are created again on restore
+    pub is_context_lost: bool,
+
+    // index in built-in themes
+    pub theme_index: usize,
+
+    // index in built-in palettes, which recolor the theme
+    pub palette_index: usize,
+
+    // objects painted on sides get shapes, so they differ not only by color
+    pub is_shape_coding_on: bool,
+
+    // atlas image of current theme, if it has one. sprites are painted only
+    // after image is loaded
+    pub sprites_image: Option<web_sys::HtmlImageElement>,
+    pub are_sprites_loaded: bool,
+
+    // current frame is saved as PNG right after it is drawn
+    pub is_screenshot_requested: bool,
+
+    // score, timer, messages and status panel over the cube
+    pub hud: Hud,
+
+    // unfolded cube in the corner, toggled by player
+    pub minimap: Option<web_sys::HtmlCanvasElement>,
+    pub minimap_ctx: Option<web_sys::CanvasRenderingContext2d>,
+    pub is_minimap_hidden: bool,
+
+    // frame counters and graph in the corner, toggled by player
+    pub perf_hud: Option<web_sys::HtmlCanvasElement>,
+    pub perf_hud_ctx: Option<web_sys::CanvasRenderingContext2d>,
+    pub is_perf_hud_shown: bool,
+}
+
+impl Scene {
+    /// Current theme with colors of current palette
+    pub fn theme(&self) -> Theme {
+        PALETTES[self.palette_index].apply(&THEMES[self.theme_index])
+    }
 }
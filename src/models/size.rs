@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy)]
 pub struct Size {
     pub width: f64,
     pub height: f64,
@@ -1,9 +1,14 @@
 use std::{collections::VecDeque, time::Duration};
 
-use super::{CubePosition, CubeSideType, Direction};
+use super::{CubePosition, CubeSideType, Direction, PlayerId};
 
 pub struct Snake {
+    pub player_id: PlayerId,
     pub parts: VecDeque<CubePosition>,
+
+    // parts before last move, to draw snake moving between cells
+    pub prev_parts: VecDeque<CubePosition>,
+
     pub direction: Direction,
     pub last_move_time: Option<f64>,
     pub move_period: Duration,
@@ -13,11 +18,13 @@ pub struct Snake {
 impl Default for Snake {
     fn default() -> Self {
         Snake {
+            player_id: 0,
             parts: VecDeque::from([CubePosition {
                 side: CubeSideType::Front,
                 row: 0,
                 col: 0,
             }]),
+            prev_parts: VecDeque::new(),
             direction: Direction::Right,
             last_move_time: None,
             move_period: Duration::from_millis(150),
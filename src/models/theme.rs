@@ -0,0 +1,117 @@
+use super::{Color, PostEffect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpriteType {
+    SnakeHead,
+    SnakeBody,
+    Apple,
+    Stone,
+}
+
+/// Image with square sprites laid out in a single row, in order of `sprites`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteAtlas {
+    pub url: &'static str,
+    pub sprite_size: i32,
+    pub sprites: &'static [SpriteType],
+}
+
+/// Look of the game. Colors are used for objects without sprites, and for 3D
+/// meshes, which are not covered by sprites
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+
+    pub background: Color,
+    pub grid_line: Color,
+    pub grid_line_width: f64,
+
+    pub snake_head: Color,
+    pub snake_body: Color,
+    pub rival: Color,
+    pub apple: Color,
+    pub stone: Color,
+
+    pub overlay_background: Color,
+    pub overlay_border: Color,
+    pub overlay_text: Color,
+    pub title_font: &'static str,
+    pub hint_font: &'static str,
+
+    pub sprites: Option<SpriteAtlas>,
+
+    // full-screen effects used with this theme. status effects are shown only
+    // when game is in that status
+    pub post_effects: &'static [PostEffect],
+}
+
+pub const THEMES: [Theme; 3] = [
+    Theme {
+        name: "classic",
+        background: Color(255, 255, 255),
+        grid_line: Color(0, 0, 0),
+        grid_line_width: 1.0,
+        snake_head: Color(255, 0, 0),
+        snake_body: Color(255, 0, 0),
+        rival: Color(0, 0, 255),
+        apple: Color(0, 128, 0),
+        stone: Color(0, 0, 0),
+        overlay_background: Color(255, 255, 255),
+        overlay_border: Color(0, 0, 0),
+        overlay_text: Color(0, 0, 0),
+        title_font: "Consolas",
+        hint_font: "Consolas",
+        sprites: None,
+        post_effects: &[PostEffect::FailFade, PostEffect::PauseBlur],
+    },
+    Theme {
+        name: "night",
+        background: Color(24, 26, 38),
+        grid_line: Color(60, 64, 90),
+        grid_line_width: 2.0,
+        snake_head: Color(255, 210, 80),
+        snake_body: Color(240, 160, 40),
+        rival: Color(90, 200, 255),
+        apple: Color(120, 230, 120),
+        stone: Color(110, 110, 130),
+        overlay_background: Color(24, 26, 38),
+        overlay_border: Color(255, 210, 80),
+        overlay_text: Color(230, 230, 240),
+        title_font: "Georgia",
+        hint_font: "Verdana",
+        sprites: None,
+        post_effects: &[
+            PostEffect::Bloom,
+            PostEffect::FailFade,
+            PostEffect::PauseBlur,
+            PostEffect::Vignette,
+        ],
+    },
+    Theme {
+        name: "pixel",
+        background: Color(250, 245, 230),
+        grid_line: Color(210, 200, 180),
+        grid_line_width: 1.0,
+        snake_head: Color(220, 40, 40),
+        snake_body: Color(230, 60, 60),
+        rival: Color(60, 90, 220),
+        apple: Color(40, 170, 40),
+        stone: Color(120, 120, 125),
+        overlay_background: Color(250, 245, 230),
+        overlay_border: Color(110, 70, 30),
+        overlay_text: Color(110, 70, 30),
+        title_font: "Courier New",
+        hint_font: "Courier New",
+        sprites: Some(SpriteAtlas {
+            url: "sprites.png",
+            sprite_size: 16,
+            sprites: &[
+                SpriteType::SnakeHead,
+                SpriteType::SnakeBody,
+                SpriteType::Apple,
+                SpriteType::Stone,
+            ],
+        }),
+        post_effects: &[PostEffect::FailFade, PostEffect::Vignette, PostEffect::Crt],
+    },
+];
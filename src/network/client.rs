@@ -0,0 +1,84 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+
+use super::protocol::ClientMessage;
+
+/// WebSocket connection to relay server. Only moves bytes around: decoding
+/// and applying messages to game state happens in network actions
+pub struct NetworkClient {
+    socket: web_sys::WebSocket,
+
+    // messages sent before connection is open
+    pending: Rc<RefCell<Vec<ClientMessage>>>,
+
+    // closures should live as long as socket can call them
+    _on_open: Closure<dyn FnMut()>,
+    _on_message: Closure<dyn FnMut(web_sys::MessageEvent)>,
+    _on_close: Closure<dyn FnMut()>,
+}
+
+impl NetworkClient {
+    pub fn connect(
+        url: &str,
+        mut on_open: Box<dyn FnMut()>,
+        mut on_message: Box<dyn FnMut(Vec<u8>)>,
+        on_close: Box<dyn FnMut()>,
+    ) -> Result<Self, JsValue> {
+        let socket = web_sys::WebSocket::new(url)?;
+
+        // receive binary frames as array buffers instead of blobs, so they can
+        // be read synchronously
+        socket.set_binary_type(web_sys::BinaryType::Arraybuffer);
+
+        let pending: Rc<RefCell<Vec<ClientMessage>>> = Rc::default();
+
+        let socket_clone = socket.clone();
+        let pending_clone = pending.clone();
+        let on_open = Closure::wrap(Box::new(move || {
+            for msg in pending_clone.borrow_mut().drain(..) {
+                socket_clone
+                    .send_with_u8_array(&msg.encode())
+                    .expect("failed to send message to server");
+            }
+            on_open();
+        }) as Box<dyn FnMut()>);
+        let on_message = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+            if let Ok(buffer) = event.data().dyn_into::<js_sys::ArrayBuffer>() {
+                on_message(js_sys::Uint8Array::new(&buffer).to_vec());
+            }
+        }) as Box<dyn FnMut(web_sys::MessageEvent)>);
+        let on_close = Closure::wrap(on_close);
+
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Ok(NetworkClient {
+            socket,
+            pending,
+            _on_open: on_open,
+            _on_message: on_message,
+            _on_close: on_close,
+        })
+    }
+
+    /// Sends message right away if connection is open, or as soon as it opens
+    pub fn send(&self, msg: ClientMessage) -> Result<(), JsValue> {
+        if self.socket.ready_state() == web_sys::WebSocket::CONNECTING {
+            self.pending.borrow_mut().push(msg);
+            return Ok(());
+        }
+
+        self.socket.send_with_u8_array(&msg.encode())
+    }
+}
+
+impl Drop for NetworkClient {
+    fn drop(&mut self) {
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+        self.socket.close().ok();
+    }
+}
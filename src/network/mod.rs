@@ -0,0 +1,4 @@
+pub mod client;
+pub mod protocol;
+pub mod snapshot;
+pub mod snapshot_transport;
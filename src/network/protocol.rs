@@ -0,0 +1,341 @@
+//! Binary protocol of network game.
+//!
+//! Game is simulated in lockstep: clients never send their state, only inputs
+//! tagged with tick numbers. Relay server merges inputs of all players into a
+//! single stream of ticks and broadcasts it, so every client runs the same
+//! deterministic simulation. After each tick clients report state hash, which
+//! lets server detect when simulations diverge.
+//!
+//! Server also relays game snapshots from publishing client to spectators, see
+//! `snapshot` module.
+//!
+//! Each message is a single WebSocket binary frame: message type byte followed
+//! by little-endian fields.
+
+use crate::{
+    helpers::binary::{BinaryReader, BinaryWriter},
+    models::{Direction, GameRules, PlayerId},
+};
+
+pub const PROTOCOL_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientMessage {
+    Join { version: u8, name: String },
+    Input { tick: u32, direction: Direction },
+    StateHash { tick: u32, hash: u64 },
+    Spectate,
+    Publish { snapshot: Vec<u8> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerMessage {
+    Welcome {
+        player_id: PlayerId,
+    },
+    Rejected {
+        reason: String,
+    },
+    PlayerJoined {
+        player_id: PlayerId,
+        name: String,
+    },
+    Start {
+        seed: u32,
+        rules: GameRules,
+        player_ids: Vec<PlayerId>,
+        tick_period_ms: u32,
+    },
+    // inputs should be applied before moving snakes on that tick. players that
+    // left are removed at the same tick on all clients
+    Tick {
+        tick: u32,
+        inputs: Vec<(PlayerId, Direction)>,
+        left_player_ids: Vec<PlayerId>,
+    },
+    Desync {
+        tick: u32,
+    },
+    Snapshot {
+        snapshot: Vec<u8>,
+    },
+    // asks publishers for full snapshot, so new spectator doesn't wait for it
+    SpectatorJoined,
+}
+
+mod client_message_type {
+    pub const JOIN: u8 = 0;
+    pub const INPUT: u8 = 1;
+    pub const STATE_HASH: u8 = 2;
+    pub const SPECTATE: u8 = 3;
+    pub const PUBLISH: u8 = 4;
+}
+
+mod server_message_type {
+    pub const WELCOME: u8 = 0;
+    pub const REJECTED: u8 = 1;
+    pub const PLAYER_JOINED: u8 = 2;
+    pub const START: u8 = 3;
+    pub const TICK: u8 = 4;
+    pub const DESYNC: u8 = 5;
+    pub const SNAPSHOT: u8 = 6;
+    pub const SPECTATOR_JOINED: u8 = 7;
+}
+
+impl ClientMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = BinaryWriter::new();
+
+        match self {
+            ClientMessage::Join { version, name } => {
+                w.write_u8(client_message_type::JOIN);
+                w.write_u8(*version);
+                w.write_str(name);
+            }
+            ClientMessage::Input { tick, direction } => {
+                w.write_u8(client_message_type::INPUT);
+                w.write_u32(*tick);
+                w.write_direction(*direction);
+            }
+            ClientMessage::StateHash { tick, hash } => {
+                w.write_u8(client_message_type::STATE_HASH);
+                w.write_u32(*tick);
+                w.write_u64(*hash);
+            }
+            ClientMessage::Spectate => {
+                w.write_u8(client_message_type::SPECTATE);
+            }
+            ClientMessage::Publish { snapshot } => {
+                w.write_u8(client_message_type::PUBLISH);
+                w.write_bytes_with_len(snapshot);
+            }
+        }
+
+        w.bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut r = BinaryReader::new(bytes);
+
+        let msg = match r.read_u8()? {
+            client_message_type::JOIN => ClientMessage::Join {
+                version: r.read_u8()?,
+                name: r.read_str()?,
+            },
+            client_message_type::INPUT => ClientMessage::Input {
+                tick: r.read_u32()?,
+                direction: r.read_direction()?,
+            },
+            client_message_type::STATE_HASH => ClientMessage::StateHash {
+                tick: r.read_u32()?,
+                hash: r.read_u64()?,
+            },
+            client_message_type::SPECTATE => ClientMessage::Spectate,
+            client_message_type::PUBLISH => ClientMessage::Publish {
+                snapshot: r.read_bytes_with_len()?,
+            },
+            t => return Err(format!("unknown client message type: {t}")),
+        };
+
+        Ok(msg)
+    }
+}
+
+impl ServerMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = BinaryWriter::new();
+
+        match self {
+            ServerMessage::Welcome { player_id } => {
+                w.write_u8(server_message_type::WELCOME);
+                w.write_u32(*player_id);
+            }
+            ServerMessage::Rejected { reason } => {
+                w.write_u8(server_message_type::REJECTED);
+                w.write_str(reason);
+            }
+            ServerMessage::PlayerJoined { player_id, name } => {
+                w.write_u8(server_message_type::PLAYER_JOINED);
+                w.write_u32(*player_id);
+                w.write_str(name);
+            }
+            ServerMessage::Start {
+                seed,
+                rules,
+                player_ids,
+                tick_period_ms,
+            } => {
+                w.write_u8(server_message_type::START);
+                w.write_u32(*seed);
+                w.write_u16(rules.grid_size as u16);
+                w.write_u16(rules.apples_count as u16);
+                w.write_u16(rules.stones_count as u16);
+                w.write_u8(player_ids.len() as u8);
+                player_ids.iter().for_each(|id| w.write_u32(*id));
+                w.write_u32(*tick_period_ms);
+            }
+            ServerMessage::Tick {
+                tick,
+                inputs,
+                left_player_ids,
+            } => {
+                w.write_u8(server_message_type::TICK);
+                w.write_u32(*tick);
+                w.write_u8(inputs.len() as u8);
+                for (player_id, direction) in inputs {
+                    w.write_u32(*player_id);
+                    w.write_direction(*direction);
+                }
+                w.write_u8(left_player_ids.len() as u8);
+                left_player_ids.iter().for_each(|id| w.write_u32(*id));
+            }
+            ServerMessage::Desync { tick } => {
+                w.write_u8(server_message_type::DESYNC);
+                w.write_u32(*tick);
+            }
+            ServerMessage::Snapshot { snapshot } => {
+                w.write_u8(server_message_type::SNAPSHOT);
+                w.write_bytes_with_len(snapshot);
+            }
+            ServerMessage::SpectatorJoined => {
+                w.write_u8(server_message_type::SPECTATOR_JOINED);
+            }
+        }
+
+        w.bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut r = BinaryReader::new(bytes);
+
+        let msg = match r.read_u8()? {
+            server_message_type::WELCOME => ServerMessage::Welcome {
+                player_id: r.read_u32()?,
+            },
+            server_message_type::REJECTED => ServerMessage::Rejected {
+                reason: r.read_str()?,
+            },
+            server_message_type::PLAYER_JOINED => ServerMessage::PlayerJoined {
+                player_id: r.read_u32()?,
+                name: r.read_str()?,
+            },
+            server_message_type::START => {
+                let seed = r.read_u32()?;
+                let rules = GameRules {
+                    grid_size: r.read_u16()? as i32,
+                    apples_count: r.read_u16()? as usize,
+                    stones_count: r.read_u16()? as usize,
+                };
+                let players_count = r.read_u8()?;
+                let player_ids = (0..players_count)
+                    .map(|_| r.read_u32())
+                    .collect::<Result<_, _>>()?;
+
+                ServerMessage::Start {
+                    seed,
+                    rules,
+                    player_ids,
+                    tick_period_ms: r.read_u32()?,
+                }
+            }
+            server_message_type::TICK => {
+                let tick = r.read_u32()?;
+                let inputs_count = r.read_u8()?;
+                let inputs = (0..inputs_count)
+                    .map(|_| Ok((r.read_u32()?, r.read_direction()?)))
+                    .collect::<Result<_, String>>()?;
+                let left_count = r.read_u8()?;
+                let left_player_ids = (0..left_count)
+                    .map(|_| r.read_u32())
+                    .collect::<Result<_, _>>()?;
+
+                ServerMessage::Tick {
+                    tick,
+                    inputs,
+                    left_player_ids,
+                }
+            }
+            server_message_type::DESYNC => ServerMessage::Desync {
+                tick: r.read_u32()?,
+            },
+            server_message_type::SNAPSHOT => ServerMessage::Snapshot {
+                snapshot: r.read_bytes_with_len()?,
+            },
+            server_message_type::SPECTATOR_JOINED => ServerMessage::SpectatorJoined,
+            t => return Err(format!("unknown server message type: {t}")),
+        };
+
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_messages_round_trip() {
+        let messages = [
+            ClientMessage::Join {
+                version: PROTOCOL_VERSION,
+                name: "player".to_string(),
+            },
+            ClientMessage::Input {
+                tick: 10,
+                direction: Direction::Up,
+            },
+            ClientMessage::StateHash {
+                tick: 11,
+                hash: u64::MAX,
+            },
+            ClientMessage::Spectate,
+            ClientMessage::Publish {
+                snapshot: vec![1, 2, 3],
+            },
+        ];
+
+        for msg in messages {
+            assert_eq!(ClientMessage::decode(&msg.encode()).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn server_messages_round_trip() {
+        let messages = [
+            ServerMessage::Welcome { player_id: 3 },
+            ServerMessage::Rejected {
+                reason: "full".to_string(),
+            },
+            ServerMessage::PlayerJoined {
+                player_id: 4,
+                name: "player".to_string(),
+            },
+            ServerMessage::Start {
+                seed: 123,
+                rules: GameRules::default(),
+                player_ids: vec![1, 2],
+                tick_period_ms: 150,
+            },
+            ServerMessage::Tick {
+                tick: 5,
+                inputs: vec![(1, Direction::Left), (2, Direction::Down)],
+                left_player_ids: vec![3],
+            },
+            ServerMessage::Desync { tick: 6 },
+            ServerMessage::Snapshot {
+                snapshot: vec![1, 2, 3],
+            },
+            ServerMessage::SpectatorJoined,
+        ];
+
+        for msg in messages {
+            assert_eq!(ServerMessage::decode(&msg.encode()).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn unknown_message_type() {
+        assert!(ClientMessage::decode(&[100]).is_err());
+        assert!(ServerMessage::decode(&[100]).is_err());
+    }
+}
@@ -0,0 +1,570 @@
+//! Compact snapshots of game state for spectators.
+//!
+//! Playing instance publishes full snapshot from time to time, and deltas
+//! against previous message in between. Spectator applies deltas only if it
+//! has seen previous message, otherwise it waits for the next full snapshot.
+//! Binary layout is described in `docs/snapshot-format.md`
+
+use std::collections::HashSet;
+
+use num_traits::FromPrimitive;
+
+use crate::{
+    helpers::binary::{BinaryReader, BinaryWriter},
+    models::{CubePosition, Direction, GameRules, GameState, GameStatus, PlayerId, Snake},
+};
+
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"S3DS";
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+// number of deltas between full snapshots. spectators that miss a message wait
+// for the next full snapshot, new ones ask for it
+const FULL_SNAPSHOT_PERIOD: u32 = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnakeSnapshot {
+    pub player_id: PlayerId,
+    pub direction: Direction,
+    pub is_crashed: bool,
+    pub parts: Vec<CubePosition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameSnapshot {
+    pub tick: u32,
+    pub status: GameStatus,
+    pub grid_size: i32,
+    pub snakes: Vec<SnakeSnapshot>,
+    pub apples: Vec<CubePosition>,
+    pub stones: Vec<CubePosition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnakeDelta {
+    pub player_id: PlayerId,
+    pub direction: Direction,
+    pub is_crashed: bool,
+    pub removed_tail_parts_count: u16,
+
+    // parts added to the head, head first
+    pub added_head_parts: Vec<CubePosition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameSnapshotDelta {
+    pub tick: u32,
+    pub status: GameStatus,
+
+    // snakes missing here were removed
+    pub snakes: Vec<SnakeDelta>,
+    pub removed_apples: Vec<CubePosition>,
+    pub added_apples: Vec<CubePosition>,
+    pub removed_stones: Vec<CubePosition>,
+    pub added_stones: Vec<CubePosition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotMessagePayload {
+    Full(GameSnapshot),
+    Delta(GameSnapshotDelta),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotMessage {
+    // increments with each message, so spectator can tell it missed a delta
+    pub sequence: u32,
+    pub payload: SnapshotMessagePayload,
+}
+
+pub fn create_snapshot(state: &GameState) -> GameSnapshot {
+    let mut snakes: Vec<SnakeSnapshot> = std::iter::once(&state.snake)
+        .chain(state.rivals.iter())
+        .map(|snake| SnakeSnapshot {
+            player_id: snake.player_id,
+            direction: snake.direction,
+            is_crashed: snake.is_crashed,
+            parts: snake.parts.iter().copied().collect(),
+        })
+        .collect();
+    snakes.sort_by_key(|snake| snake.player_id);
+
+    let mut apples: Vec<CubePosition> = state.apples.iter().copied().collect();
+    let mut stones: Vec<CubePosition> = state.stones.iter().copied().collect();
+    apples.sort();
+    stones.sort();
+
+    GameSnapshot {
+        tick: state.tick,
+        status: state.status,
+        grid_size: state.rules.grid_size,
+        snakes,
+        apples,
+        stones,
+    }
+}
+
+pub fn create_snapshot_delta(prev: &GameSnapshot, next: &GameSnapshot) -> GameSnapshotDelta {
+    let snakes = next
+        .snakes
+        .iter()
+        .map(|snake| {
+            let prev_parts = prev
+                .snakes
+                .iter()
+                .find(|prev_snake| prev_snake.player_id == snake.player_id)
+                .map(|prev_snake| &prev_snake.parts[..])
+                .unwrap_or(&[]);
+
+            let (added_count, removed_count) = get_parts_diff(prev_parts, &snake.parts);
+
+            SnakeDelta {
+                player_id: snake.player_id,
+                direction: snake.direction,
+                is_crashed: snake.is_crashed,
+                removed_tail_parts_count: removed_count as u16,
+                added_head_parts: snake.parts[..added_count].to_vec(),
+            }
+        })
+        .collect();
+
+    let (removed_apples, added_apples) = get_positions_diff(&prev.apples, &next.apples);
+    let (removed_stones, added_stones) = get_positions_diff(&prev.stones, &next.stones);
+
+    GameSnapshotDelta {
+        tick: next.tick,
+        status: next.status,
+        snakes,
+        removed_apples,
+        added_apples,
+        removed_stones,
+        added_stones,
+    }
+}
+
+// finds how many parts were added to the head and removed from the tail, so
+// moving snake costs one part instead of entire body. falls back to replacing
+// all parts when body changed in other ways (eg. grew from the tail)
+fn get_parts_diff(prev: &[CubePosition], next: &[CubePosition]) -> (usize, usize) {
+    for added_count in 0..next.len() {
+        let kept = &next[added_count..];
+        if kept.len() <= prev.len() && kept == &prev[..kept.len()] {
+            return (added_count, prev.len() - kept.len());
+        }
+    }
+
+    (next.len(), prev.len())
+}
+
+fn get_positions_diff(
+    prev: &[CubePosition],
+    next: &[CubePosition],
+) -> (Vec<CubePosition>, Vec<CubePosition>) {
+    let prev_set: HashSet<&CubePosition> = prev.iter().collect();
+    let next_set: HashSet<&CubePosition> = next.iter().collect();
+
+    let removed = prev.iter().filter(|p| !next_set.contains(p)).copied();
+    let added = next.iter().filter(|p| !prev_set.contains(p)).copied();
+
+    (removed.collect(), added.collect())
+}
+
+pub fn apply_snapshot_delta(snapshot: &mut GameSnapshot, delta: &GameSnapshotDelta) {
+    snapshot.tick = delta.tick;
+    snapshot.status = delta.status;
+
+    snapshot.snakes = delta
+        .snakes
+        .iter()
+        .map(|snake_delta| {
+            let mut parts = snapshot
+                .snakes
+                .iter()
+                .find(|snake| snake.player_id == snake_delta.player_id)
+                .map(|snake| snake.parts.clone())
+                .unwrap_or_default();
+
+            let kept_count = parts
+                .len()
+                .saturating_sub(snake_delta.removed_tail_parts_count as usize);
+            parts.truncate(kept_count);
+            parts.splice(0..0, snake_delta.added_head_parts.iter().copied());
+
+            SnakeSnapshot {
+                player_id: snake_delta.player_id,
+                direction: snake_delta.direction,
+                is_crashed: snake_delta.is_crashed,
+                parts,
+            }
+        })
+        .collect();
+
+    for (positions, removed, added) in [
+        (
+            &mut snapshot.apples,
+            &delta.removed_apples,
+            &delta.added_apples,
+        ),
+        (
+            &mut snapshot.stones,
+            &delta.removed_stones,
+            &delta.added_stones,
+        ),
+    ] {
+        positions.retain(|pos| !removed.contains(pos));
+        positions.extend(added.iter().copied());
+        positions.sort();
+    }
+}
+
+/// Turns snapshot into game state to be rendered. First snake is treated as
+/// the main one, so camera can follow it. Snapshot with board which can't be
+/// drawn is rejected, and state is left as it was
+pub fn apply_snapshot_to_state(
+    snapshot: &GameSnapshot,
+    state: &mut GameState,
+) -> Result<(), String> {
+    GameRules {
+        grid_size: snapshot.grid_size,
+        apples_count: snapshot.apples.len(),
+        stones_count: snapshot.stones.len(),
+    }
+    .validate()?;
+
+    let mut snakes = snapshot.snakes.iter().map(|snake| Snake {
+        player_id: snake.player_id,
+        direction: snake.direction,
+        is_crashed: snake.is_crashed,
+        parts: snake.parts.iter().copied().collect(),
+        ..Snake::default()
+    });
+
+    state.snake = snakes.next().unwrap_or_default();
+    state.rivals = snakes.collect();
+    state.apples = snapshot.apples.iter().copied().collect();
+    state.stones = snapshot.stones.iter().copied().collect();
+    state.status = snapshot.status;
+    state.tick = snapshot.tick;
+    state.rules.grid_size = snapshot.grid_size;
+    state.scene.cube.grid.rows_count = snapshot.grid_size;
+    state.scene.cube.grid.cols_count = snapshot.grid_size;
+
+    Ok(())
+}
+
+/// Produces messages for spectators out of game state changes
+#[derive(Default)]
+pub struct SnapshotPublisher {
+    last_snapshot: Option<GameSnapshot>,
+    sequence: u32,
+    deltas_count: u32,
+}
+
+impl SnapshotPublisher {
+    /// Returns message if anything changed since previous call
+    pub fn publish(&mut self, state: &GameState) -> Option<SnapshotMessage> {
+        let snapshot = create_snapshot(state);
+
+        let payload = match &self.last_snapshot {
+            Some(last) if *last == snapshot => return None,
+            Some(last) if self.deltas_count < FULL_SNAPSHOT_PERIOD => {
+                self.deltas_count += 1;
+                SnapshotMessagePayload::Delta(create_snapshot_delta(last, &snapshot))
+            }
+            _ => {
+                self.deltas_count = 0;
+                SnapshotMessagePayload::Full(snapshot.clone())
+            }
+        };
+
+        let msg = SnapshotMessage {
+            sequence: self.sequence,
+            payload,
+        };
+
+        self.sequence = self.sequence.wrapping_add(1);
+        self.last_snapshot = Some(snapshot);
+
+        Some(msg)
+    }
+
+    /// Makes the next message full snapshot, even if nothing changed
+    pub fn request_full_snapshot(&mut self) {
+        self.last_snapshot = None;
+    }
+}
+
+/// Restores snapshots out of messages on spectator side
+#[derive(Default)]
+pub struct SnapshotReceiver {
+    snapshot: Option<GameSnapshot>,
+    last_sequence: Option<u32>,
+}
+
+impl SnapshotReceiver {
+    /// Returns latest snapshot if message could be applied
+    pub fn receive(&mut self, msg: SnapshotMessage) -> Option<&GameSnapshot> {
+        let is_next = self.last_sequence.map(|s| s.wrapping_add(1)) == Some(msg.sequence);
+
+        match msg.payload {
+            SnapshotMessagePayload::Full(snapshot) => {
+                self.snapshot = Some(snapshot);
+            }
+            SnapshotMessagePayload::Delta(delta) => match &mut self.snapshot {
+                Some(snapshot) if is_next => apply_snapshot_delta(snapshot, &delta),
+                _ => {
+                    // missed previous message, wait for the next full snapshot
+                    self.snapshot = None;
+                    self.last_sequence = None;
+                    return None;
+                }
+            },
+        }
+
+        self.last_sequence = Some(msg.sequence);
+        self.snapshot.as_ref()
+    }
+}
+
+mod payload_kind {
+    pub const FULL: u8 = 0;
+    pub const DELTA: u8 = 1;
+}
+
+impl SnapshotMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = BinaryWriter::new();
+
+        w.bytes.extend_from_slice(SNAPSHOT_MAGIC);
+        w.write_u8(SNAPSHOT_VERSION);
+
+        match &self.payload {
+            SnapshotMessagePayload::Full(snapshot) => {
+                w.write_u8(payload_kind::FULL);
+                w.write_u32(self.sequence);
+                w.write_u32(snapshot.tick);
+                w.write_u8(snapshot.status as u8);
+                w.write_u16(snapshot.grid_size as u16);
+
+                w.write_u8(snapshot.snakes.len() as u8);
+                for snake in &snapshot.snakes {
+                    w.write_u32(snake.player_id);
+                    w.write_direction(snake.direction);
+                    w.write_u8(snake.is_crashed as u8);
+                    write_positions(&mut w, &snake.parts);
+                }
+
+                write_positions(&mut w, &snapshot.apples);
+                write_positions(&mut w, &snapshot.stones);
+            }
+            SnapshotMessagePayload::Delta(delta) => {
+                w.write_u8(payload_kind::DELTA);
+                w.write_u32(self.sequence);
+                w.write_u32(delta.tick);
+                w.write_u8(delta.status as u8);
+
+                w.write_u8(delta.snakes.len() as u8);
+                for snake in &delta.snakes {
+                    w.write_u32(snake.player_id);
+                    w.write_direction(snake.direction);
+                    w.write_u8(snake.is_crashed as u8);
+                    w.write_u16(snake.removed_tail_parts_count);
+                    write_positions(&mut w, &snake.added_head_parts);
+                }
+
+                write_positions(&mut w, &delta.removed_apples);
+                write_positions(&mut w, &delta.added_apples);
+                write_positions(&mut w, &delta.removed_stones);
+                write_positions(&mut w, &delta.added_stones);
+            }
+        }
+
+        w.bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut r = BinaryReader::new(bytes);
+
+        let magic = [r.read_u8()?, r.read_u8()?, r.read_u8()?, r.read_u8()?];
+        if &magic != SNAPSHOT_MAGIC {
+            return Err("not a snapshot".to_string());
+        }
+
+        let version = r.read_u8()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(format!("unsupported snapshot version: {version}"));
+        }
+
+        let kind = r.read_u8()?;
+        let sequence = r.read_u32()?;
+        let tick = r.read_u32()?;
+        let status = read_status(&mut r)?;
+
+        let payload = match kind {
+            payload_kind::FULL => {
+                let grid_size = r.read_u16()? as i32;
+
+                let snakes_count = r.read_u8()?;
+                let snakes = (0..snakes_count)
+                    .map(|_| {
+                        Ok(SnakeSnapshot {
+                            player_id: r.read_u32()?,
+                            direction: r.read_direction()?,
+                            is_crashed: r.read_u8()? != 0,
+                            parts: read_positions(&mut r)?,
+                        })
+                    })
+                    .collect::<Result<_, String>>()?;
+
+                SnapshotMessagePayload::Full(GameSnapshot {
+                    tick,
+                    status,
+                    grid_size,
+                    snakes,
+                    apples: read_positions(&mut r)?,
+                    stones: read_positions(&mut r)?,
+                })
+            }
+            payload_kind::DELTA => {
+                let snakes_count = r.read_u8()?;
+                let snakes = (0..snakes_count)
+                    .map(|_| {
+                        Ok(SnakeDelta {
+                            player_id: r.read_u32()?,
+                            direction: r.read_direction()?,
+                            is_crashed: r.read_u8()? != 0,
+                            removed_tail_parts_count: r.read_u16()?,
+                            added_head_parts: read_positions(&mut r)?,
+                        })
+                    })
+                    .collect::<Result<_, String>>()?;
+
+                SnapshotMessagePayload::Delta(GameSnapshotDelta {
+                    tick,
+                    status,
+                    snakes,
+                    removed_apples: read_positions(&mut r)?,
+                    added_apples: read_positions(&mut r)?,
+                    removed_stones: read_positions(&mut r)?,
+                    added_stones: read_positions(&mut r)?,
+                })
+            }
+            k => return Err(format!("unknown snapshot kind: {k}")),
+        };
+
+        Ok(SnapshotMessage { sequence, payload })
+    }
+}
+
+fn write_positions(w: &mut BinaryWriter, positions: &[CubePosition]) {
+    w.write_u16(positions.len() as u16);
+    positions.iter().for_each(|pos| w.write_position(pos));
+}
+
+fn read_positions(r: &mut BinaryReader) -> Result<Vec<CubePosition>, String> {
+    let count = r.read_u16()?;
+    (0..count).map(|_| r.read_position()).collect()
+}
+
+fn read_status(r: &mut BinaryReader) -> Result<GameStatus, String> {
+    let v = r.read_u8()?;
+    FromPrimitive::from_u8(v).ok_or_else(|| format!("invalid game status: {v}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{actions::game_actions::plant_objects, actions::snake_actions::move_snakes};
+
+    fn create_state() -> GameState {
+        let mut state = GameState {
+            seed: 5,
+            ..GameState::default()
+        };
+        plant_objects(&mut state);
+        state.status = GameStatus::InGame;
+        state
+    }
+
+    #[test]
+    fn full_snapshot_round_trip() {
+        let msg = SnapshotMessage {
+            sequence: 7,
+            payload: SnapshotMessagePayload::Full(create_snapshot(&create_state())),
+        };
+
+        assert_eq!(SnapshotMessage::decode(&msg.encode()).unwrap(), msg);
+    }
+
+    #[test]
+    fn deltas_restore_state() {
+        let mut state = create_state();
+        let mut publisher = SnapshotPublisher::default();
+        let mut receiver = SnapshotReceiver::default();
+
+        for _ in 0..50 {
+            let msg = publisher.publish(&state).unwrap();
+            let msg = SnapshotMessage::decode(&msg.encode()).unwrap();
+
+            let received = receiver.receive(msg).unwrap();
+            assert_eq!(*received, create_snapshot(&state));
+
+            move_snakes(&mut state);
+        }
+    }
+
+    #[test]
+    fn moving_snake_costs_single_part() {
+        let mut state = create_state();
+        state.snake.parts.extend([state.snake.parts[0]; 4]);
+
+        let prev = create_snapshot(&state);
+        move_snakes(&mut state);
+        let next = create_snapshot(&state);
+
+        let delta = create_snapshot_delta(&prev, &next);
+        assert_eq!(delta.snakes[0].added_head_parts.len(), 1);
+        assert_eq!(delta.snakes[0].removed_tail_parts_count, 1);
+    }
+
+    #[test]
+    fn unchanged_state_is_not_published() {
+        let state = create_state();
+        let mut publisher = SnapshotPublisher::default();
+
+        assert!(publisher.publish(&state).is_some());
+        assert!(publisher.publish(&state).is_none());
+
+        // unless new spectator asks for it
+        publisher.request_full_snapshot();
+        let msg = publisher.publish(&state).unwrap();
+        assert!(matches!(msg.payload, SnapshotMessagePayload::Full(_)));
+    }
+
+    #[test]
+    fn snapshot_with_invalid_grid_is_rejected() {
+        let mut state = create_state();
+        let mut snapshot = create_snapshot(&state);
+
+        for grid_size in [0, 65] {
+            snapshot.grid_size = grid_size;
+            assert!(apply_snapshot_to_state(&snapshot, &mut state).is_err());
+            assert_eq!(state.scene.cube.grid.rows_count, state.rules.grid_size);
+        }
+    }
+
+    #[test]
+    fn missed_delta_waits_for_full_snapshot() {
+        let mut state = create_state();
+        let mut publisher = SnapshotPublisher::default();
+        let mut receiver = SnapshotReceiver::default();
+
+        receiver.receive(publisher.publish(&state).unwrap());
+        move_snakes(&mut state);
+        publisher.publish(&state).unwrap(); // lost
+        move_snakes(&mut state);
+
+        assert!(receiver
+            .receive(publisher.publish(&state).unwrap())
+            .is_none());
+    }
+}
@@ -0,0 +1,123 @@
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+
+use super::{
+    client::NetworkClient,
+    protocol::{ClientMessage, ServerMessage},
+};
+
+const BROADCAST_CHANNEL_NAME: &str = "snake-3d-snapshots";
+
+// posted by spectator over broadcast channel, to get full snapshot right away
+const SPECTATE_REQUEST: &str = "spectate";
+
+// address which selects broadcast channel instead of websocket
+pub const BROADCAST_ADDRESS: &str = "tab";
+
+/// Carries encoded snapshots from playing instance to spectators. Broadcast
+/// channel reaches other tabs of the same browser, relay server reaches other
+/// machines
+pub enum SnapshotTransport {
+    BroadcastChannel {
+        channel: web_sys::BroadcastChannel,
+        _on_message: Option<Closure<dyn FnMut(web_sys::MessageEvent)>>,
+    },
+    WebSocket(NetworkClient),
+}
+
+impl SnapshotTransport {
+    /// Opens transport for publishing, `address` is either `BROADCAST_ADDRESS`
+    /// or relay server url. `on_spectator_joined` is called when new spectator
+    /// needs full snapshot
+    pub fn publisher(
+        address: &str,
+        mut on_spectator_joined: Box<dyn FnMut()>,
+    ) -> Result<Self, JsValue> {
+        if address == BROADCAST_ADDRESS {
+            let channel = web_sys::BroadcastChannel::new(BROADCAST_CHANNEL_NAME)?;
+
+            let on_message = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+                if event.data().as_string().as_deref() == Some(SPECTATE_REQUEST) {
+                    on_spectator_joined();
+                }
+            }) as Box<dyn FnMut(web_sys::MessageEvent)>);
+
+            channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+            return Ok(SnapshotTransport::BroadcastChannel {
+                channel,
+                _on_message: Some(on_message),
+            });
+        }
+
+        let client = NetworkClient::connect(
+            address,
+            Box::new(|| {}),
+            Box::new(move |bytes| {
+                if let Ok(ServerMessage::SpectatorJoined) = ServerMessage::decode(&bytes) {
+                    on_spectator_joined();
+                }
+            }),
+            Box::new(|| web_sys::console::warn_1(&"snapshot publisher disconnected".into())),
+        )?;
+
+        Ok(SnapshotTransport::WebSocket(client))
+    }
+
+    pub fn spectator(
+        address: &str,
+        mut on_snapshot: Box<dyn FnMut(Vec<u8>)>,
+    ) -> Result<Self, JsValue> {
+        if address == BROADCAST_ADDRESS {
+            let channel = web_sys::BroadcastChannel::new(BROADCAST_CHANNEL_NAME)?;
+
+            let on_message = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+                if let Ok(bytes) = event.data().dyn_into::<js_sys::Uint8Array>() {
+                    on_snapshot(bytes.to_vec());
+                }
+            }) as Box<dyn FnMut(web_sys::MessageEvent)>);
+
+            channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+            channel.post_message(&SPECTATE_REQUEST.into())?;
+
+            return Ok(SnapshotTransport::BroadcastChannel {
+                channel,
+                _on_message: Some(on_message),
+            });
+        }
+
+        let client = NetworkClient::connect(
+            address,
+            Box::new(|| {}),
+            Box::new(move |bytes| {
+                if let Ok(ServerMessage::Snapshot { snapshot }) = ServerMessage::decode(&bytes) {
+                    on_snapshot(snapshot);
+                }
+            }),
+            Box::new(|| web_sys::console::warn_1(&"spectator disconnected".into())),
+        )?;
+
+        client.send(ClientMessage::Spectate)?;
+
+        Ok(SnapshotTransport::WebSocket(client))
+    }
+
+    pub fn publish(&self, snapshot: Vec<u8>) -> Result<(), JsValue> {
+        match self {
+            SnapshotTransport::BroadcastChannel { channel, .. } => {
+                channel.post_message(&js_sys::Uint8Array::from(&snapshot[..]))
+            }
+            SnapshotTransport::WebSocket(client) => {
+                client.send(ClientMessage::Publish { snapshot })
+            }
+        }
+    }
+}
+
+impl Drop for SnapshotTransport {
+    fn drop(&mut self) {
+        if let SnapshotTransport::BroadcastChannel { channel, .. } = self {
+            channel.set_onmessage(None);
+            channel.close();
+        }
+    }
+}